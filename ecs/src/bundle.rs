@@ -0,0 +1,32 @@
+use crate::{component::Component, entity::EntityManager};
+
+/// A set of components which are attached to an entity together, see [EntityManager::spawn_bundle].
+///
+/// Bundles are implemented for tuples of up to six components, and can be derived with `#[derive(Bundle)]` for
+/// structs whose fields are all components.
+pub trait Bundle {
+    /// Attaches every component of the bundle to the entity, registering the component types as needed.
+    fn insert(self, manager: &mut EntityManager, entity: usize);
+}
+
+macro_rules! bundle {
+    ($($T:ident),+) => {
+        impl<$($T : Component,)+> Bundle for ($($T, )+) {
+            #[allow(non_snake_case)]
+            fn insert(self, manager: &mut EntityManager, entity: usize) {
+                let ($($T,)+) = self;
+
+                $(
+                    manager.entity_with(entity, $T);
+                )+
+            }
+        }
+    };
+}
+
+bundle!(T1);
+bundle!(T1, T2);
+bundle!(T1, T2, T3);
+bundle!(T1, T2, T3, T4);
+bundle!(T1, T2, T3, T4, T5);
+bundle!(T1, T2, T3, T4, T5, T6);
@@ -0,0 +1,53 @@
+use std::sync::mpsc::{self, Receiver, SyncSender, TryRecvError};
+
+use crate::component::Component;
+
+/// A `Channel` is a bounded queue which can be used to pass data from systems running on other threads
+/// back to the systems running on the main thread.
+///
+/// The receiving half is owned by the `Channel` itself, and is meant to be attached to an entity like any other
+/// component. Background work can obtain a [SyncSender] through [Channel::sender] and push its results into the queue,
+/// which will then be picked up by the next [Channel::try_recv] call.
+///
+/// # Type Parameters
+///
+/// - `T`: The type of the messages sent through the channel. Must be `Send`, as the values cross thread boundaries.
+pub struct Channel<T>
+where
+    T: Send + 'static,
+{
+    sender: SyncSender<T>,
+    receiver: Receiver<T>,
+}
+
+impl<T> Channel<T>
+where
+    T: Send + 'static,
+{
+    /// Creates a new `Channel` which can hold at most `bound` messages at once.
+    ///
+    /// Once the channel is full, [SyncSender::send] blocks until the messages are received, while
+    /// [SyncSender::try_send] returns an error.
+    pub fn new(bound: usize) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(bound);
+
+        Self { sender, receiver }
+    }
+
+    /// Returns a new sending half of the channel, which can be moved to another thread.
+    pub fn sender(&self) -> SyncSender<T> {
+        self.sender.clone()
+    }
+
+    /// Receives a single message from the channel, without blocking.
+    ///
+    /// Returns `None` if no messages are currently queued.
+    pub fn try_recv(&self) -> Option<T> {
+        match self.receiver.try_recv() {
+            Ok(value) => Some(value),
+            Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => None,
+        }
+    }
+}
+
+impl<T> Component for Channel<T> where T: Send + 'static {}
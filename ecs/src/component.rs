@@ -1,7 +1,6 @@
 use std::{
     any::{Any, TypeId},
     collections::HashMap,
-    mem::transmute,
 };
 
 /// A `Component` is a piece of data that can be linked to an entity.
@@ -10,20 +9,20 @@ use std::{
 ///
 /// This trait can be automatically implemented for a struct using the `ecs-macros::EntityComponent` derive macro:
 ///
-/// ```
+/// ```ignore
 /// #[derive(EntityComponent)]
 /// struct MyStruct;
 /// ```
 ///
 /// This will generate the following implementation:
 ///
-/// ```
+/// ```ignore
 /// impl Component for MyStruct {}
 /// ```
 ///
 /// # Examples
 ///
-/// ```
+/// ```ignore
 /// #[derive(EntityComponent)]
 /// struct Position {
 ///     x: i32,
@@ -35,7 +34,31 @@ use std::{
 /// ```
 ///
 /// These structs can be used as components within a `World` system.
-pub trait Component: Sized + Any {}
+///
+/// # Hooks
+///
+/// The derived implementation keeps the default, no-op hooks. To run logic when a component is attached to or
+/// detached from an entity, implement the trait manually:
+///
+/// ```
+/// use ecs::component::Component;
+///
+/// struct Mesh;
+///
+/// impl Component for Mesh {
+///     fn on_add(&mut self, entity: usize) {
+///         println!("mesh added to {}", entity);
+///     }
+/// }
+/// ```
+pub trait Component: Sized + Any {
+    /// Called right after the component has been attached to `entity`.
+    fn on_add(&mut self, _entity: usize) {}
+
+    /// Called right before the component is detached from `entity`, either because the component or the whole entity
+    /// is removed.
+    fn on_remove(&mut self, _entity: usize) {}
+}
 
 /// `SimpleComponentManager` is a struct that stores and manages components, entities, and entity indexes.
 /// It implements the [ComponentManager] and [TypedComponentManager] traits.
@@ -73,6 +96,21 @@ where
     pub fn borrow_components_mut(&mut self) -> &mut Vec<T> {
         &mut self.components
     }
+
+    /// Iterates over all components, alongside the entities they belong to.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut T)> {
+        self.entities
+            .iter()
+            .copied()
+            .zip(self.components.iter_mut())
+    }
+
+    /// Reserves capacity for at least `additional` more components.
+    pub fn reserve(&mut self, additional: usize) {
+        self.components.reserve(additional);
+        self.entities.reserve(additional);
+        self.entity_idx.reserve(additional);
+    }
 }
 
 impl<T> ComponentManager for SimpleComponentManager<T>
@@ -80,7 +118,7 @@ where
     T: Component,
 {
     fn has(&self, entity: usize) -> bool {
-        self.entities.contains(&entity)
+        self.entity_idx.contains_key(&entity)
     }
 
     fn clear(&mut self, entity: usize) {
@@ -90,6 +128,8 @@ where
 
         let index = *self.entity_idx.get(&entity).unwrap();
 
+        self.components[index].on_remove(entity);
+
         self.entity_idx
             .insert(*self.entities.last().unwrap(), index);
         self.components.swap_remove(index);
@@ -97,6 +137,16 @@ where
         self.entity_idx.remove(&entity);
     }
 
+    fn clear_all(&mut self) {
+        for (entity, component) in self.entities.iter().zip(self.components.iter_mut()) {
+            component.on_remove(*entity);
+        }
+
+        self.components.clear();
+        self.entities.clear();
+        self.entity_idx.clear();
+    }
+
     fn get_type_id(&self) -> TypeId {
         TypeId::of::<T>()
     }
@@ -114,6 +164,8 @@ where
         self.components.push(component);
         self.entities.push(entity);
         self.entity_idx.insert(entity, self.components.len() - 1);
+
+        self.components.last_mut().unwrap().on_add(entity);
     }
 
     fn component(&self, entity: usize) -> Option<&T> {
@@ -140,6 +192,48 @@ where
     }
 }
 
+/// `Snapshot` is an immutable copy of all components of a single type, taken at a single point in time.
+///
+/// Snapshots are created through `EntityManager::read_snapshot`, and allow reading the components of every entity
+/// while the live components are being mutated.
+pub struct Snapshot<T> {
+    components: Vec<T>,
+    entities: Vec<usize>,
+    entity_idx: HashMap<usize, usize>,
+}
+
+impl<T> Snapshot<T> {
+    pub fn get(&self, entity: usize) -> Option<&T> {
+        let index = self.entity_idx.get(&entity)?;
+        Some(&self.components[*index])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.entities.iter().copied().zip(self.components.iter())
+    }
+
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+}
+
+impl<T> SimpleComponentManager<T>
+where
+    T: Component + Clone,
+{
+    pub fn snapshot(&self) -> Snapshot<T> {
+        Snapshot {
+            components: self.components.clone(),
+            entities: self.entities.clone(),
+            entity_idx: self.entity_idx.clone(),
+        }
+    }
+}
+
 pub fn borrow_manager_ref<T>(manager: &dyn ComponentManager) -> &T
 where
     T: 'static + ComponentManager,
@@ -160,14 +254,6 @@ where
     downcast
 }
 
-pub fn cast_manager_mut_unsafe<T: 'static + Component>(
-    manager: &Box<dyn ComponentManager>,
-) -> &mut SimpleComponentManager<T> {
-    let ptr = borrow_manager_ref(manager.as_ref()) as *const SimpleComponentManager<T>
-        as *mut SimpleComponentManager<T>;
-    unsafe { transmute(ptr) }
-}
-
 /// `ComponentManager` is a marker trait that defines type-independent functions for managing components.
 /// It is implemented for the [SimpleComponentManager] struct and allows for bridging between [SimpleComponentManager] and [TypedComponentManager]
 /// without having to store the [SimpleComponentManager] struct. This allows for dynamic generic types instead of being limited to a single generic type.
@@ -176,10 +262,12 @@ pub fn cast_manager_mut_unsafe<T: 'static + Component>(
 ///
 /// - `has`: Returns a boolean indicating whether the given entity has a component of this type.
 /// - `clear`: Removes the component of this type from the given entity.
+/// - `clear_all`: Removes the components of this type from all entities.
 /// - `get_type_id`: Returns the `TypeId` of the component type being managed.
 pub trait ComponentManager: Any + As<dyn Any> {
     fn has(&self, entity: usize) -> bool;
     fn clear(&mut self, entity_id: usize);
+    fn clear_all(&mut self);
     fn get_type_id(&self) -> TypeId;
 }
 
@@ -1,25 +1,52 @@
-use std::{any::TypeId, collections::HashMap};
+use std::{
+    any::{Any, TypeId},
+    collections::{hash_map::Entry, HashMap, HashSet},
+};
 
-use crate::component::{
-    self, cast_manager_mut_unsafe, Component, ComponentManager, SimpleComponentManager,
-    TypedComponentManager,
+use crate::{
+    bundle::Bundle,
+    component::{
+        self, Component, ComponentManager, SimpleComponentManager, Snapshot, TypedComponentManager,
+    },
+    event::Events,
+    reflect::{Reflect, ReflectRegistry},
 };
 
+/// A handle to an entity.
+///
+/// The slots of removed entities are reused, so an entity id alone may refer to a different entity later on. The
+/// generation of a handle increments every time its slot is reused, which allows [EntityManager::resolve] to detect
+/// handles of removed entities.
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
 pub struct Entity {
     id: u32,
+    generation: u32,
     alive: bool,
 }
 
 impl Entity {
     pub fn new(id: u32) -> Self {
-        Self { id, alive: true }
+        Self {
+            id,
+            generation: 0,
+            alive: true,
+        }
+    }
+
+    /// The id of the entity, as used by the [EntityManager].
+    pub fn index(&self) -> usize {
+        self.id as usize - 1
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
     }
 }
 
 struct EntityContainer {
     entities: Vec<Entity>,
     dead_idx: Vec<usize>,
+    disabled: HashSet<usize>,
 }
 
 unsafe impl Sync for EntityContainer {}
@@ -30,6 +57,7 @@ impl EntityContainer {
         Self {
             entities: vec![],
             dead_idx: vec![],
+            disabled: HashSet::new(),
         }
     }
 
@@ -39,10 +67,9 @@ impl EntityContainer {
 
     pub fn entity(&mut self) -> usize {
         if !self.dead_idx.is_empty() {
-            let index = self.dead_idx.remove(0) as usize;
-            let mut entity = self.entities[index];
+            let index = self.dead_idx.remove(0);
 
-            entity.alive = true;
+            self.revive(index);
             return index;
         }
 
@@ -54,18 +81,65 @@ impl EntityContainer {
     }
 
     pub fn entity_at(&mut self, id: usize) -> usize {
-        let entity = Entity::new(id as u32);
-        self.entities.push(entity);
+        // pad the container with dead entities, so the slot at `id` is never handed out by `entity()` afterwards.
+        while self.entities.len() < id {
+            let index = self.entities.len();
+            let mut entity = Entity::new((index + 1).try_into().unwrap());
+
+            entity.alive = false;
+
+            self.entities.push(entity);
+            self.dead_idx.push(index);
+        }
+
+        if id < self.entities.len() {
+            if !self.entities[id].alive {
+                self.dead_idx.retain(|index| *index != id);
+                self.revive(id);
+            }
+        } else {
+            self.entities
+                .push(Entity::new((id + 1).try_into().unwrap()));
+        }
+
         id
     }
 
+    /// Marks a dead slot as alive again, invalidating all handles to the previous entity in the slot.
+    fn revive(&mut self, index: usize) {
+        let entity = &mut self.entities[index];
+
+        entity.generation += 1;
+        entity.alive = true;
+    }
+
+    /// Returns the handle of the entity with the given id, if it's alive.
+    pub fn handle(&self, entity_id: usize) -> Option<Entity> {
+        self.entities
+            .get(entity_id)
+            .filter(|entity| entity.alive)
+            .copied()
+    }
+
     pub fn remove(&mut self, entity_id: usize) {
-        if !self.has(entity_id) {
+        if !self.has(entity_id) || !self.entities[entity_id].alive {
             return;
         }
 
         self.dead_idx.push(entity_id);
         self.entities[entity_id].alive = false;
+        self.disabled.remove(&entity_id);
+    }
+
+    /// Removes every living entity like [EntityContainer::remove], so the slots keep their generations and handles to
+    /// the removed entities can't resolve to the entities spawned afterwards.
+    pub fn clear(&mut self) {
+        for entity_id in 0..self.entities.len() {
+            self.remove(entity_id);
+        }
+
+        // the lowest ids are handed out first, as in a fresh container.
+        self.dead_idx.sort_unstable();
     }
 }
 
@@ -74,12 +148,17 @@ pub struct EntityManager {
     managers: HashMap<TypeId, Box<dyn ComponentManager>>,
     frame_map: HashMap<TypeId, u64>,
     frame: u64,
+    versions: HashMap<TypeId, u64>,
+    version: u64,
+    resources: HashMap<TypeId, Box<dyn Any>>,
+    event_queues: HashMap<TypeId, fn(&mut EntityManager)>,
+    reflect_registry: ReflectRegistry,
 }
 
 pub struct TupleData<'a> {
     entities: &'a Vec<usize>,
     type_id: TypeId,
-    update_frame: u64,
+    version: u64,
 }
 
 macro_rules! tuple {
@@ -92,13 +171,13 @@ macro_rules! tuple {
                     {
                         let type_id = TypeId::of::<$T>();
                         let entities = manager.query_entity_ids::<$T>()?;
-                        let update_frame = manager.get_updated_frame::<$T>();
+                        let version = manager.get_version_type(type_id);
 
                         f(
                             TupleData {
                                 entities,
                                 type_id,
-                                update_frame,
+                                version,
                             }
                         );
                     }
@@ -106,24 +185,93 @@ macro_rules! tuple {
 
                 None
             }
+
+            fn type_ids() -> Vec<TypeId> {
+                vec![$(TypeId::of::<$T>(),)+]
+            }
         }
     };
 }
 
 macro_rules! query {
-    ($name:ident<$($T:ident),+>) => {
+    ($name:ident, $checked:ident, $join:ident<$($T:ident),+>) => {
+        #[doc = concat!(
+            "Like [EntityManager::", stringify!($name), "], but takes an [Entity] handle. ",
+            "Returns `None` if the handle refers to an entity which has been removed."
+        )]
+        pub fn $checked<$($T : component::Component,)+>(&mut self, handle: Entity) -> Option<($(Option<&mut $T>,)+)> {
+            let entity = self.resolve(handle)?;
+            Some(self.$name::<$($T,)+>(entity))
+        }
+
+        /// # Panics
+        ///
+        /// Panics if the same component type is requested more than once, as that would hand out two mutable
+        /// references to the same component.
         pub fn $name<$($T : component::Component,)+>(&mut self, entity: usize) -> ($(Option<&mut $T>,)+) {
+            let mut managers = self.managers_mut([$(TypeId::of::<$T>(),)+]).into_iter();
+
             (
                 $(
                     {
-                        let type_id = TypeId::of::<$T>();
-                        let manager: &mut SimpleComponentManager<$T> = cast_manager_mut_unsafe(self.managers.get(&type_id).unwrap());
-
-                        manager.component_mut(entity)
+                        // unregistered component types simply aren't present on any entity.
+                        managers.next().unwrap().and_then(|manager| {
+                            let manager: &mut SimpleComponentManager<$T> =
+                                component::borrow_mut_manager(manager.as_mut());
+                            manager.component_mut(entity)
+                        })
                     },
                 )+
             )
         }
+
+        #[doc = concat!(
+            "Like [EntityManager::", stringify!($name), "], but every type is a [Join]: a component type `T` is ",
+            "required and returned as `&mut T`, while `Option<T>` is returned as `Option<&mut T>`.\n\n",
+            "Returns `None` if the entity lacks any of the required components.\n\n",
+            "# Panics\n\n",
+            "Panics if the same component type is requested more than once."
+        )]
+        pub fn $join<$($T : Join,)+>(&mut self, entity: usize) -> Option<($(<$T as Join>::Item<'_>,)+)> {
+            let mut managers = self.managers_mut([$(TypeId::of::<$T::Component>(),)+]).into_iter();
+
+            Some((
+                $(
+                    $T::join(managers.next().unwrap().and_then(|manager| {
+                        let manager: &mut SimpleComponentManager<$T::Component> =
+                            component::borrow_mut_manager(manager.as_mut());
+                        manager.component_mut(entity)
+                    }))?,
+                )+
+            ))
+        }
+    }
+}
+
+/// A component type which is joined by the `join_entity` queries of [EntityManager], either required or optional.
+pub trait Join {
+    type Component: Component;
+    type Item<'a>;
+
+    /// Maps the component of an entity, if it has one, to the item returned by the query. `None` skips the entity.
+    fn join(component: Option<&mut Self::Component>) -> Option<Self::Item<'_>>;
+}
+
+impl<T: Component> Join for T {
+    type Component = T;
+    type Item<'a> = &'a mut T;
+
+    fn join(component: Option<&mut T>) -> Option<&mut T> {
+        component
+    }
+}
+
+impl<T: Component> Join for Option<T> {
+    type Component = T;
+    type Item<'a> = Option<&'a mut T>;
+
+    fn join(component: Option<&mut T>) -> Option<Option<&mut T>> {
+        Some(component)
     }
 }
 
@@ -131,6 +279,21 @@ pub trait Tuple {
     fn for_every_type<K, V>(manager: &mut EntityManager, f: K) -> Option<()>
     where
         K: FnMut(TupleData) -> V;
+
+    fn type_ids() -> Vec<TypeId>;
+}
+
+impl Tuple for () {
+    fn for_every_type<K, V>(_: &mut EntityManager, _: K) -> Option<()>
+    where
+        K: FnMut(TupleData) -> V,
+    {
+        None
+    }
+
+    fn type_ids() -> Vec<TypeId> {
+        vec![]
+    }
 }
 
 tuple!(T1);
@@ -139,6 +302,12 @@ tuple!(T1, T2, T3);
 tuple!(T1, T2, T3, T4);
 tuple!(T1, T2, T3, T4, T5);
 tuple!(T1, T2, T3, T4, T5, T6);
+tuple!(T1, T2, T3, T4, T5, T6, T7);
+tuple!(T1, T2, T3, T4, T5, T6, T7, T8);
+tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9);
+tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
 
 unsafe impl Sync for EntityManager {}
 unsafe impl Send for EntityManager {}
@@ -150,6 +319,11 @@ impl EntityManager {
             managers: HashMap::new(),
             frame_map: HashMap::new(),
             frame: 0,
+            versions: HashMap::new(),
+            version: 0,
+            resources: HashMap::new(),
+            event_queues: HashMap::new(),
+            reflect_registry: ReflectRegistry::new(),
         }
     }
 
@@ -174,6 +348,21 @@ impl EntityManager {
         return 0;
     }
 
+    /// Returns the version of the set of entities holding a component of the given type.
+    ///
+    /// Unlike the updated frame, the version changes every time a component of the type is added or removed, even if
+    /// that happens multiple times during the same frame. This is used to invalidate cached queries.
+    pub fn get_version_type(&self, type_id: TypeId) -> u64 {
+        self.versions.get(&type_id).copied().unwrap_or(0)
+    }
+
+    /// Records that the set of entities holding a component of the given type has changed.
+    fn membership_changed(&mut self, type_id: TypeId, frame: u64) {
+        self.version += 1;
+        self.versions.insert(type_id, self.version);
+        self.frame_map.insert(type_id, frame);
+    }
+
     pub fn register<T>(&mut self) -> &mut Self
     where
         T: 'static + Component,
@@ -186,7 +375,7 @@ impl EntityManager {
 
         self.managers
             .insert(type_id, Box::new(SimpleComponentManager::<T>::new()));
-        self.frame_map.insert(type_id, self.frame);
+        self.membership_changed(type_id, self.frame);
 
         self
     }
@@ -195,26 +384,228 @@ impl EntityManager {
         self.container.entity()
     }
 
+    /// Registers a component type to be reflected by [EntityManager::reflect].
+    pub fn register_reflect<T: 'static + Component + Reflect>(&mut self) -> &mut Self {
+        self.reflect_registry.register::<T>();
+        self
+    }
+
+    /// Reflects every component of the entity whose type has been registered with [EntityManager::register_reflect].
+    ///
+    /// # Returns
+    ///
+    /// The type name and the reflection of every such component, ordered by type name.
+    pub fn reflect(&mut self, entity_id: usize) -> Vec<(&'static str, &mut dyn Reflect)> {
+        let registry = &self.reflect_registry;
+        let mut reflected = vec![];
+
+        for (type_id, manager) in self.managers.iter_mut() {
+            if let Some(entry) = registry.reflect(type_id, manager.as_mut(), entity_id) {
+                reflected.push(entry);
+            }
+        }
+
+        reflected.sort_by_key(|entry| entry.0);
+        reflected
+    }
+
+    /// Inserts a resource, replacing the previous resource of the same type.
+    ///
+    /// Resources are singletons which don't belong to any entity, such as the active camera or the time delta of the
+    /// current frame. There is at most one resource of every type.
+    pub fn insert_resource<R: 'static>(&mut self, resource: R) -> &mut Self {
+        self.resources.insert(TypeId::of::<R>(), Box::new(resource));
+        self
+    }
+
+    pub fn resource<R: 'static>(&self) -> Option<&R> {
+        self.resources.get(&TypeId::of::<R>())?.downcast_ref()
+    }
+
+    pub fn resource_mut<R: 'static>(&mut self) -> Option<&mut R> {
+        self.resources.get_mut(&TypeId::of::<R>())?.downcast_mut()
+    }
+
+    pub fn remove_resource<R: 'static>(&mut self) -> Option<R> {
+        let resource = self.resources.remove(&TypeId::of::<R>())?;
+        Some(*resource.downcast().ok()?)
+    }
+
+    /// Registers the event queue of type `E`, which is inserted as an [Events] resource and updated by
+    /// [EntityManager::update_events]. Registering a queue twice keeps the existing one.
+    pub fn add_event<E: 'static>(&mut self) -> &mut Self {
+        fn update<E: 'static>(manager: &mut EntityManager) {
+            if let Some(events) = manager.resource_mut::<Events<E>>() {
+                events.update();
+            }
+        }
+
+        let type_id = TypeId::of::<Events<E>>();
+
+        if let Entry::Vacant(entry) = self.event_queues.entry(type_id) {
+            entry.insert(update::<E>);
+            self.insert_resource(Events::<E>::new());
+        }
+
+        self
+    }
+
+    /// Sends an event through the queue of type `E`, registering the queue if needed. See [Events::send].
+    pub fn send_event<E: 'static>(&mut self, event: E) -> &mut Self {
+        self.add_event::<E>();
+
+        if let Some(events) = self.resource_mut::<Events<E>>() {
+            events.send(event);
+        }
+
+        self
+    }
+
+    /// Swaps the buffers of every registered event queue, see [Events::update].
+    pub fn update_events(&mut self) {
+        let queues = self.event_queues.values().copied().collect::<Vec<_>>();
+
+        for update in queues {
+            update(self);
+        }
+    }
+
+    /// Returns the handle of the entity with the given id, or `None` if there's no living entity with the id.
+    pub fn handle(&self, entity_id: usize) -> Option<Entity> {
+        self.container.handle(entity_id)
+    }
+
+    /// Resolves a handle to the id of its entity, or `None` if the entity has been removed since the handle was
+    /// created. This holds even if the id has been reused by another entity in the meantime.
+    pub fn resolve(&self, handle: Entity) -> Option<usize> {
+        let entity_id = handle.index();
+
+        match self.container.handle(entity_id) {
+            Some(entity) if entity.generation == handle.generation => Some(entity_id),
+            _ => None,
+        }
+    }
+
     pub fn entity_at(&mut self, id: usize) -> usize {
         self.container.entity_at(id)
     }
 
     pub fn remove_entity(&mut self, entity_id: usize) {
+        let mut cleared = vec![];
+
         for entry in self.managers.iter_mut() {
             let type_id = entry.0;
             let manager = entry.1;
 
-            manager.clear(entity_id);
-            self.frame_map.insert(*type_id, self.frame + 1);
+            if manager.has(entity_id) {
+                manager.clear(entity_id);
+                cleared.push(*type_id);
+            }
+        }
+
+        for type_id in cleared {
+            self.membership_changed(type_id, self.frame + 1);
         }
 
         self.container.remove(entity_id);
     }
 
+    /// Removes all entities and their components, e.g. when switching scenes.
+    ///
+    /// Component types stay registered and resources are kept. Entities spawned afterwards reuse the ids of the removed
+    /// ones, starting with the lowest, but handles taken before the clear don't resolve to them, see
+    /// [EntityManager::resolve].
+    pub fn clear_all(&mut self) {
+        for manager in self.managers.values_mut() {
+            manager.clear_all();
+        }
+
+        // every type gets a new version, so cached queries are rebuilt.
+        for type_id in self.managers.keys() {
+            self.version += 1;
+            self.versions.insert(*type_id, self.version);
+        }
+
+        self.frame_map.clear();
+        self.container.clear();
+    }
+
+    /// Enables or disables an entity. The queries of [EntityQueryTable] skip disabled entities, but their components
+    /// stay in the managers untouched, and are returned by the queries again once the entity is enabled.
+    ///
+    /// Components accessed directly, e.g. through [EntityManager::query_entity] or [EntityManager::iter], are still
+    /// reachable while the entity is disabled. Removing the entity enables its slot again.
+    pub fn set_enabled(&mut self, entity_id: usize, enabled: bool) -> &mut Self {
+        if self.container.handle(entity_id).is_none() || self.is_enabled(entity_id) == enabled {
+            return self;
+        }
+
+        match enabled {
+            true => self.container.disabled.remove(&entity_id),
+            false => self.container.disabled.insert(entity_id),
+        };
+
+        // the cached queries of every type the entity has are rebuilt without (or with) the entity.
+        let changed = self
+            .managers
+            .iter()
+            .filter(|(_, manager)| manager.has(entity_id))
+            .map(|(type_id, _)| *type_id)
+            .collect::<Vec<_>>();
+
+        for type_id in changed {
+            self.membership_changed(type_id, self.frame);
+        }
+
+        self
+    }
+
+    /// Returns whether the entity is enabled, see [EntityManager::set_enabled]. Entities are enabled when spawned.
+    pub fn is_enabled(&self, entity_id: usize) -> bool {
+        !self.container.disabled.contains(&entity_id)
+    }
+
+    /// Removes the component of type `T` from the entity, leaving its other components untouched.
+    ///
+    /// Removing a component the entity doesn't have is a no-op.
+    pub fn remove_component<T: 'static + Component>(&mut self, entity_id: usize) -> &mut Self {
+        let type_id = TypeId::of::<T>();
+
+        let removed = match self.managers.get_mut(&type_id) {
+            Some(manager) if manager.has(entity_id) => {
+                manager.clear(entity_id);
+                true
+            }
+            _ => false,
+        };
+
+        if removed {
+            self.membership_changed(type_id, self.frame);
+        }
+
+        self
+    }
+
     pub fn tick_frame(&mut self) {
         self.frame += 1;
     }
 
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Marks the components of type `T` as changed during the current frame, so they are picked up by
+    /// [EntityQueryTable::query_changed]. Mutating a component through a query doesn't do this automatically.
+    pub fn mark_changed<T: 'static + Component>(&mut self) -> &mut Self {
+        let type_id = TypeId::of::<T>();
+
+        if self.managers.contains_key(&type_id) {
+            self.frame_map.insert(type_id, self.frame);
+        }
+
+        self
+    }
+
     pub fn entity_with<T>(&mut self, entity_id: usize, component: T) -> &mut Self
     where
         T: 'static + Component,
@@ -229,10 +620,75 @@ impl EntityManager {
             manager.with(entity_id, component);
         }
 
-        self.frame_map.insert(type_id, self.frame);
+        self.membership_changed(type_id, self.frame);
+        self
+    }
+
+    /// Creates a new entity and attaches every component of the bundle to it.
+    pub fn spawn_bundle<B: Bundle>(&mut self, bundle: B) -> usize {
+        let entity = self.entity();
+
+        bundle.insert(self, entity);
+        entity
+    }
+
+    /// Attaches a component to each of the given entities in a single pass.
+    ///
+    /// This behaves like calling [EntityManager::entity_with] for every entry, but reserves the required capacity
+    /// up front and only updates the frame map once, which is significantly faster for large amounts of entities.
+    pub fn entity_with_many<T>(&mut self, components: Vec<(usize, T)>) -> &mut Self
+    where
+        T: 'static + Component,
+    {
+        let type_id = TypeId::of::<T>();
+
+        if !self.managers.contains_key(&type_id) {
+            self.register::<T>();
+        }
+
+        if let Some(manager) = self.borrow_manager_mut::<T>() {
+            manager.reserve(components.len());
+
+            for (entity_id, component) in components {
+                manager.with(entity_id, component);
+            }
+        }
+
+        self.membership_changed(type_id, self.frame);
         self
     }
 
+    /// Returns whether the entity has a component of the given type. Unregistered types are never present.
+    pub fn has_type(&self, type_id: TypeId, entity_id: usize) -> bool {
+        match self.managers.get(&type_id) {
+            Some(manager) => manager.has(entity_id),
+            None => false,
+        }
+    }
+
+    /// Returns whether the entity has a component of type `T`, see [EntityManager::has_type].
+    pub fn has_component<T: 'static + Component>(&self, entity_id: usize) -> bool {
+        self.has_type(TypeId::of::<T>(), entity_id)
+    }
+
+    /// Returns whether there's a living entity with the given id, i.e. it has been spawned and not removed since.
+    pub fn is_alive(&self, entity_id: usize) -> bool {
+        self.container.handle(entity_id).is_some()
+    }
+
+    /// Returns the type-erased manager of the components of the given type, if the type is registered.
+    #[cfg(feature = "serde")]
+    pub(crate) fn component_manager(&self, type_id: TypeId) -> Option<&dyn ComponentManager> {
+        Some(self.managers.get(&type_id)?.as_ref())
+    }
+
+    /// Returns the amount of components of type `T`, or `0` if the type isn't registered.
+    pub fn count<T: 'static + Component>(&self) -> usize {
+        self.borrow_manager::<T>()
+            .map(|manager| manager.components.len())
+            .unwrap_or(0)
+    }
+
     pub fn borrow_manager<T: 'static + Component>(&self) -> Option<&SimpleComponentManager<T>> {
         let type_id = TypeId::of::<T>();
         let inner = self.managers.get(&type_id)?.as_ref();
@@ -249,28 +705,66 @@ impl EntityManager {
         Some(component::borrow_mut_manager(inner))
     }
 
+    /// Takes an immutable snapshot of all components of type `T`.
+    ///
+    /// This allows a system to read the components of every entity (e.g. the positions of neighbors), while mutating the
+    /// live components of the entity it's currently processing. Note that every component is cloned, so the cost of this
+    /// call grows with the amount and size of the components.
+    pub fn read_snapshot<T: 'static + Component + Clone>(&self) -> Option<Snapshot<T>> {
+        Some(self.borrow_manager::<T>()?.snapshot())
+    }
+
+    /// Iterates over all components of type `T`, alongside the entities they belong to.
+    ///
+    /// Unlike the queries of [EntityQueryTable], this walks the components directly without building a list of
+    /// entities first, which is the cheapest way to touch every component of a type. Yields nothing if the type isn't
+    /// registered.
+    pub fn iter<T: 'static + Component>(&mut self) -> impl Iterator<Item = (usize, &mut T)> {
+        self.borrow_manager_mut::<T>()
+            .into_iter()
+            .flat_map(|manager| manager.iter_mut())
+    }
+
     pub fn query_entity_ids<T: 'static + Component>(&self) -> Option<&Vec<usize>> {
         Some(&self.borrow_manager::<T>()?.entities)
     }
 
     pub fn query<T: Component>(&mut self) -> Option<&mut Vec<T>> {
-        Some(
-            component::cast_manager_mut_unsafe(self.managers.get(&TypeId::of::<T>()).unwrap())
-                .borrow_components_mut(),
-        )
+        Some(self.borrow_manager_mut::<T>()?.borrow_components_mut())
     }
 
-    query!(query_entity<T>);
-    query!(query_entity_two<T1, T2>);
-    query!(query_entity_three<T, T2, T3>);
-    query!(query_entity_four<T1, T2, T3, T4>);
-    query!(query_entity_five<T1, T2, T3, T4, T5>);
+    /// Borrows the managers of several distinct component types at once, `None` for unregistered types.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a type is requested more than once.
+    fn managers_mut<const N: usize>(
+        &mut self,
+        type_ids: [TypeId; N],
+    ) -> [Option<&mut Box<dyn ComponentManager>>; N] {
+        for (index, type_id) in type_ids.iter().enumerate() {
+            assert!(
+                !type_ids[..index].contains(type_id),
+                "the same component type was requested more than once"
+            );
+        }
+
+        self.managers.get_disjoint_mut(type_ids.each_ref())
+    }
+
+    query!(query_entity, query_handle, join_entity<T>);
+    query!(query_entity_two, query_handle_two, join_entity_two<T1, T2>);
+    query!(query_entity_three, query_handle_three, join_entity_three<T, T2, T3>);
+    query!(query_entity_four, query_handle_four, join_entity_four<T1, T2, T3, T4>);
+    query!(query_entity_five, query_handle_five, join_entity_five<T1, T2, T3, T4, T5>);
+    query!(query_entity_six, query_handle_six, join_entity_six<T1, T2, T3, T4, T5, T6>);
 }
 
 #[derive(Debug)]
 pub struct EntityQueryTable {
+    // the enabled entities of every component type, shared by all queries.
     query_cache: HashMap<TypeId, Vec<usize>>,
-    frames: HashMap<TypeId, u64>,
+    versions: HashMap<TypeId, u64>,
 }
 
 unsafe impl Send for EntityQueryTable {}
@@ -280,93 +774,153 @@ impl EntityQueryTable {
     pub fn new() -> Self {
         Self {
             query_cache: HashMap::new(),
-            frames: HashMap::new(),
+            versions: HashMap::new(),
         }
     }
 
+    /// Queries the enabled entities which have a component of type `T`.
+    ///
+    /// The result is cached, and only rebuilt once an entity gains or loses a component of type `T`, or an entity with
+    /// one is enabled or disabled.
     pub fn query_single<T>(&mut self, manager: &mut EntityManager) -> Option<&Vec<usize>>
     where
         T: Component,
     {
         let type_id = TypeId::of::<T>();
+        let entities = manager.query_entity_ids::<T>()?;
+        let version = manager.get_version_type(type_id);
 
-        if !self.query_cache.contains_key(&type_id) {
-            self.query_cache.insert(type_id, vec![]);
-
-            let entities = &manager.borrow_manager::<T>()?.entities;
-            let cache = self.query_cache.get_mut(&type_id)?;
+        if self.versions.get(&type_id) != Some(&version) || !self.query_cache.contains_key(&type_id)
+        {
+            let cache = self.query_cache.entry(type_id).or_default();
 
             cache.clear();
-
-            for entity in entities {
-                cache.push(*entity);
-            }
+            cache.extend(
+                entities
+                    .iter()
+                    .copied()
+                    .filter(|entity| manager.is_enabled(*entity)),
+            );
+
+            self.versions.insert(type_id, version);
         }
 
         self.query_cache.get(&type_id)
     }
 
+    /// Queries the first entity which has a component of type `T`.
+    pub fn query_first_single<T>(&mut self, manager: &mut EntityManager) -> Option<&usize>
+    where
+        T: Component,
+    {
+        self.query_single::<T>(manager)?.first()
+    }
+
+    /// Queries the enabled entities with a component of type `T`, if the components of that type have changed since
+    /// `last_seen_frame`.
+    ///
+    /// Changes are tracked per component type, so either all entities with `T` are returned, or none of them. A component
+    /// type counts as changed whenever a component is added or removed, or [EntityManager::mark_changed] is called.
+    ///
+    /// # Returns
+    ///
+    /// The changed entities, and the frame which should be passed as `last_seen_frame` on the next call. Changes made
+    /// after this call during the same frame are considered seen. Returns `None` if `T` isn't registered.
+    pub fn query_changed<T>(
+        &mut self,
+        manager: &mut EntityManager,
+        last_seen_frame: u64,
+    ) -> Option<(Vec<usize>, u64)>
+    where
+        T: Component,
+    {
+        manager.query_entity_ids::<T>()?;
+        let next_frame = manager.frame() + 1;
+
+        if manager.get_updated_frame::<T>() < last_seen_frame {
+            return Some((vec![], next_frame));
+        }
+
+        Some((self.query_single::<T>(manager)?.clone(), next_frame))
+    }
+
+    /// Queries the entities which have all components in `Include`, but none of the components in `Exclude`.
+    ///
+    /// Both `Include` and `Exclude` are tuples of components. With an empty `Exclude` tuple, this behaves exactly like
+    /// [EntityQueryTable::query].
+    pub fn query_filtered<Include, Exclude>(
+        &mut self,
+        manager: &mut EntityManager,
+    ) -> Option<Vec<usize>>
+    where
+        Include: Tuple,
+        Exclude: Tuple,
+    {
+        let mut entities = self.query::<Include>(manager)?;
+        let excluded = Exclude::type_ids();
+
+        entities.retain(|entity| {
+            !excluded
+                .iter()
+                .any(|type_id| manager.has_type(*type_id, *entity))
+        });
+
+        Some(entities)
+    }
+
+    /// Queries the enabled entities which have every component in the tuple `T`.
+    ///
+    /// # Returns
+    ///
+    /// The intersection of the entities of every component type in `T`, in the order of the first component type.
     pub fn query<T>(&mut self, manager: &mut EntityManager) -> Option<Vec<usize>>
     where
         T: Tuple,
     {
-        let mut vec = Vec::<usize>::new();
+        let type_ids = T::type_ids();
+        let mut rebuilt = vec![];
+        let mut visited = 0;
 
         T::for_every_type::<_, Option<()>>(manager, |data| {
             let type_id = data.type_id;
-            let entities = data.entities;
-
-            let mut update = true;
-
-            if !self.query_cache.contains_key(&type_id) {
-                self.query_cache.insert(type_id, vec![]);
-            } else {
-                let updated_frame = self.frames.get(&type_id);
-
-                if data.update_frame == *updated_frame.unwrap_or(&(data.update_frame + 1)) {
-                    update = false;
-                }
-            }
 
-            let cache = self.query_cache.get_mut(&type_id)?;
+            let update = !self.query_cache.contains_key(&type_id)
+                || self.versions.get(&type_id) != Some(&data.version);
 
             if update {
-                cache.clear();
-
-                for entity in entities {
-                    cache.push(*entity);
-                }
+                let cache = self.query_cache.entry(type_id).or_default();
 
-                self.frames.insert(type_id, data.update_frame);
-            }
+                cache.clear();
+                cache.extend(data.entities.iter().copied());
 
-            for entity in cache.iter() {
-                vec.push(*entity);
+                self.versions.insert(type_id, data.version);
+                rebuilt.push(type_id);
             }
 
+            visited += 1;
             None
         });
 
-        T::for_every_type::<_, Option<()>>(manager, |data| {
-            let entities = data.entities;
-
-            let mut to_remove = Vec::<usize>::new();
-
-            for e in &vec {
-                if entities.contains(e) {
-                    continue;
-                }
+        // one of the component types isn't registered, so no entity can have all of them.
+        if visited < type_ids.len() {
+            return Some(vec![]);
+        }
 
-                to_remove.push(*e);
+        // the caches are shared with `query_single`, so they only ever hold enabled entities.
+        for type_id in rebuilt {
+            if let Some(cache) = self.query_cache.get_mut(&type_id) {
+                cache.retain(|entity| manager.is_enabled(*entity));
             }
+        }
 
-            for entity in &to_remove {
-                vec.retain(|e| e == entity);
-            }
+        let mut caches = type_ids.iter().map(|type_id| &self.query_cache[type_id]);
+        let mut entities = caches.next().cloned().unwrap_or_default();
 
-            None
-        });
+        for cache in caches {
+            let cached = cache.iter().collect::<HashSet<_>>();
+            entities.retain(|entity| cached.contains(entity));
+        }
 
-        Some(vec)
+        Some(entities)
     }
 }
@@ -0,0 +1,62 @@
+use std::vec::Drain;
+
+/// A double-buffered queue of events of type `E`, which lets systems communicate without depending on each other.
+///
+/// `Events` are stored as a resource, and are registered with `EntityManager::add_event`. Systems [Events::send]
+/// events during a frame, which become readable through [Events::iter] and [Events::drain] during the next frame.
+/// Events which haven't been drained by then are dropped at the end of that frame.
+///
+/// # Type Parameters
+///
+/// - `E`: The type of the events.
+pub struct Events<E> {
+    sent: Vec<E>,
+    readable: Vec<E>,
+}
+
+impl<E> Events<E> {
+    pub fn new() -> Self {
+        Self {
+            sent: Vec::new(),
+            readable: Vec::new(),
+        }
+    }
+
+    /// Queues an event, which is readable during the next frame.
+    pub fn send(&mut self, event: E) {
+        self.sent.push(event);
+    }
+
+    /// Iterates over the events sent during the previous frame, leaving them in the queue for other readers.
+    pub fn iter(&self) -> impl Iterator<Item = &E> {
+        self.readable.iter()
+    }
+
+    /// Removes and returns the events sent during the previous frame.
+    pub fn drain(&mut self) -> Drain<'_, E> {
+        self.readable.drain(..)
+    }
+
+    /// Returns the amount of readable events.
+    pub fn len(&self) -> usize {
+        self.readable.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.readable.is_empty()
+    }
+
+    /// Makes the events sent since the last call readable, and drops the events which were readable until now.
+    ///
+    /// This is called by the `World` at the end of every frame.
+    pub fn update(&mut self) {
+        self.readable.clear();
+        std::mem::swap(&mut self.sent, &mut self.readable);
+    }
+}
+
+impl<E> Default for Events<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
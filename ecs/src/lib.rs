@@ -1,14 +1,36 @@
+pub mod bundle;
+pub mod channel;
 pub mod component;
 pub mod entity;
+pub mod event;
+pub mod reflect;
+#[cfg(feature = "serde")]
+pub mod save;
 pub mod system;
 pub mod world;
 
+// lets the derive macros, which refer to `ecs::...`, be used within this crate's tests.
+#[cfg(test)]
+extern crate self as ecs;
+
 #[cfg(test)]
 mod test {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        thread,
+    };
+
     use crate::{
+        bundle::Bundle,
+        channel::Channel,
         component::Component,
         entity::{EntityManager, EntityQueryTable},
-        system::System,
+        event::Events,
+        reflect::Value,
+        system::{PostLoopSystem, System},
         world::{SystemType, World},
     };
 
@@ -74,4 +96,1146 @@ mod test {
             world.update(SystemType::Loop, &());
         }
     }
+
+    #[test]
+    fn channel_test() {
+        struct Received(Vec<u32>);
+        impl Component for Received {}
+
+        struct ReceiveSystem;
+
+        impl System<()> for ReceiveSystem {
+            fn update(
+                &mut self,
+                manager: &mut EntityManager,
+                table: &mut EntityQueryTable,
+                _: &(),
+            ) -> Option<()> {
+                for entity in table.query::<(Channel<u32>, Received)>(manager)? {
+                    let queried = manager.query_entity_two::<Channel<u32>, Received>(entity);
+                    let (channel, received) = (queried.0?, queried.1?);
+
+                    while let Some(value) = channel.try_recv() {
+                        received.0.push(value);
+                    }
+                }
+
+                None
+            }
+        }
+
+        let mut world = World::<()>::new();
+        world.with_system(SystemType::Loop, ReceiveSystem);
+
+        let entity = world.entity();
+        let channel = Channel::<u32>::new(4);
+        let sender = channel.sender();
+
+        world
+            .with::<Channel<u32>>(entity, channel)
+            .with::<Received>(entity, Received(vec![]));
+
+        world.update(SystemType::Loop, &());
+
+        thread::spawn(move || sender.send(37).unwrap())
+            .join()
+            .unwrap();
+
+        world.update(SystemType::Loop, &());
+
+        let received = world.entity_manager.query_entity::<Received>(entity).0;
+        assert_eq!(received.unwrap().0, vec![37]);
+    }
+
+    #[test]
+    fn pause_test() {
+        struct CountingSystem {
+            count: Arc<AtomicUsize>,
+            runs_while_paused: bool,
+        }
+
+        impl System<()> for CountingSystem {
+            fn update(
+                &mut self,
+                _: &mut EntityManager,
+                _: &mut EntityQueryTable,
+                _: &(),
+            ) -> Option<()> {
+                self.count.fetch_add(1, Ordering::SeqCst);
+                None
+            }
+
+            fn runs_while_paused(&self) -> bool {
+                self.runs_while_paused
+            }
+        }
+
+        let gameplay = Arc::new(AtomicUsize::new(0));
+        let render = Arc::new(AtomicUsize::new(0));
+
+        let mut world = World::<()>::new();
+
+        world
+            .with_system(
+                SystemType::Loop,
+                CountingSystem {
+                    count: gameplay.clone(),
+                    runs_while_paused: false,
+                },
+            )
+            .with_system(
+                SystemType::Loop,
+                CountingSystem {
+                    count: render.clone(),
+                    runs_while_paused: true,
+                },
+            );
+
+        world.update(SystemType::Loop, &());
+        world.set_paused(true);
+        world.update(SystemType::Loop, &());
+        world.update(SystemType::Loop, &());
+
+        assert_eq!(gameplay.load(Ordering::SeqCst), 1);
+        assert_eq!(render.load(Ordering::SeqCst), 3);
+
+        world.set_paused(false);
+        world.update(SystemType::Loop, &());
+
+        assert_eq!(gameplay.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn snapshot_test() {
+        #[derive(Clone)]
+        struct Position(f32);
+
+        impl Component for Position {}
+
+        let mut manager = EntityManager::new();
+
+        for x in [0.0, 3.0, 6.0] {
+            let entity = manager.entity();
+            manager.entity_with::<Position>(entity, Position(x));
+        }
+
+        let snapshot = manager.read_snapshot::<Position>().unwrap();
+
+        // move every entity to the average position of its neighbors, the snapshot isn't affected by the writes.
+        for (entity, _) in snapshot.iter() {
+            let neighbors = snapshot
+                .iter()
+                .filter(|(other, _)| *other != entity)
+                .map(|(_, position)| position.0)
+                .collect::<Vec<_>>();
+
+            let position = manager.query_entity::<Position>(entity).0.unwrap();
+            position.0 = neighbors.iter().sum::<f32>() / neighbors.len() as f32;
+        }
+
+        let positions = manager.borrow_manager::<Position>().unwrap();
+        let positions = positions.components.iter().map(|p| p.0).collect::<Vec<_>>();
+
+        assert_eq!(positions, vec![4.5, 3.0, 1.5]);
+    }
+
+    #[test]
+    fn changed_query_test() {
+        struct Position(f32);
+        impl Component for Position {}
+
+        let mut manager = EntityManager::new();
+        let mut table = EntityQueryTable::new();
+
+        let entity = manager.entity();
+        manager.entity_with::<Position>(entity, Position(0.0));
+
+        let (changed, last_seen) = table.query_changed::<Position>(&mut manager, 0).unwrap();
+        assert_eq!(changed, vec![entity]);
+
+        manager.tick_frame();
+
+        let (changed, last_seen) = table
+            .query_changed::<Position>(&mut manager, last_seen)
+            .unwrap();
+        assert!(changed.is_empty());
+
+        // the world ticks the frame after every system, so other systems mutate during a later frame.
+        manager.tick_frame();
+        manager.query_entity::<Position>(entity).0.unwrap().0 += 1.0;
+        manager.mark_changed::<Position>();
+
+        let (changed, last_seen) = table
+            .query_changed::<Position>(&mut manager, last_seen)
+            .unwrap();
+        assert_eq!(changed, vec![entity]);
+
+        let (changed, _) = table
+            .query_changed::<Position>(&mut manager, last_seen)
+            .unwrap();
+        assert!(changed.is_empty());
+    }
+
+    #[test]
+    fn filtered_query_test() {
+        struct Transform;
+        struct Instanced;
+
+        impl Component for Transform {}
+        impl Component for Instanced {}
+
+        let mut manager = EntityManager::new();
+        let mut table = EntityQueryTable::new();
+
+        let plain = manager.entity();
+        let instanced = manager.entity();
+        let other = manager.entity();
+
+        manager
+            .entity_with::<Transform>(plain, Transform)
+            .entity_with::<Transform>(instanced, Transform)
+            .entity_with::<Instanced>(instanced, Instanced)
+            .entity_with::<Instanced>(other, Instanced);
+
+        let entities = table
+            .query_filtered::<(Transform,), (Instanced,)>(&mut manager)
+            .unwrap();
+        assert_eq!(entities, vec![plain]);
+
+        let mut entities = table
+            .query_filtered::<(Transform,), ()>(&mut manager)
+            .unwrap();
+        entities.sort();
+        assert_eq!(entities, vec![plain, instanced]);
+    }
+
+    #[test]
+    fn intersection_test() {
+        struct A;
+        struct B;
+        struct C;
+
+        impl Component for A {}
+        impl Component for B {}
+        impl Component for C {}
+
+        let mut manager = EntityManager::new();
+        let mut table = EntityQueryTable::new();
+
+        let entities = (0..4).map(|_| manager.entity()).collect::<Vec<_>>();
+
+        manager
+            .entity_with::<A>(entities[0], A)
+            .entity_with::<B>(entities[0], B)
+            .entity_with::<A>(entities[1], A)
+            .entity_with::<B>(entities[1], B)
+            .entity_with::<C>(entities[1], C)
+            .entity_with::<B>(entities[2], B)
+            .entity_with::<C>(entities[2], C)
+            .entity_with::<A>(entities[3], A)
+            .entity_with::<C>(entities[3], C);
+
+        let queried = table.query::<(A, B, C)>(&mut manager).unwrap();
+        assert_eq!(queried, vec![entities[1]]);
+
+        let queried = table.query::<(A, B)>(&mut manager).unwrap();
+        assert_eq!(queried, vec![entities[0], entities[1]]);
+    }
+
+    #[test]
+    fn query_single_cache_test() {
+        struct Mesh;
+
+        impl Component for Mesh {}
+
+        let mut manager = EntityManager::new();
+        let mut table = EntityQueryTable::new();
+
+        let first = manager.entity();
+        manager.entity_with::<Mesh>(first, Mesh);
+
+        assert_eq!(table.query_single::<Mesh>(&mut manager), Some(&vec![first]));
+
+        // the second mesh is added during the same frame, after the first query was cached.
+        let second = manager.entity();
+        manager.entity_with::<Mesh>(second, Mesh);
+
+        let queried = table.query_single::<Mesh>(&mut manager).unwrap();
+        assert_eq!(queried, &vec![first, second]);
+
+        manager.remove_entity(first);
+
+        let queried = table.query_single::<Mesh>(&mut manager).unwrap();
+        assert_eq!(queried, &vec![second]);
+        assert_eq!(
+            table.query_first_single::<Mesh>(&mut manager),
+            Some(&second)
+        );
+    }
+
+    #[test]
+    fn count_test() {
+        struct A;
+
+        impl Component for A {}
+
+        let mut manager = EntityManager::new();
+        assert_eq!(manager.count::<A>(), 0);
+
+        let entities = (0..3).map(|_| manager.entity()).collect::<Vec<_>>();
+
+        for entity in &entities {
+            manager.entity_with::<A>(*entity, A);
+        }
+
+        assert_eq!(manager.count::<A>(), 3);
+
+        manager.remove_entity(entities[1]);
+        assert_eq!(manager.count::<A>(), 2);
+    }
+
+    #[test]
+    fn wide_query_test() {
+        struct A(u32);
+        struct B(u32);
+        struct C(u32);
+        struct D(u32);
+        struct E(u32);
+        struct F(u32);
+        struct G;
+
+        impl Component for A {}
+        impl Component for B {}
+        impl Component for C {}
+        impl Component for D {}
+        impl Component for E {}
+        impl Component for F {}
+        impl Component for G {}
+
+        let mut manager = EntityManager::new();
+        let mut table = EntityQueryTable::new();
+
+        let entity = manager.entity();
+        let other = manager.entity();
+
+        manager
+            .entity_with::<A>(entity, A(1))
+            .entity_with::<B>(entity, B(2))
+            .entity_with::<C>(entity, C(3))
+            .entity_with::<D>(entity, D(4))
+            .entity_with::<E>(entity, E(5))
+            .entity_with::<F>(entity, F(6))
+            .entity_with::<G>(entity, G)
+            .entity_with::<A>(other, A(0));
+
+        let queried = table.query::<(A, B, C, D, E, F, G)>(&mut manager).unwrap();
+        assert_eq!(queried, vec![entity]);
+
+        let (a, b, c, d, e) = manager.query_entity_five::<A, B, C, D, E>(entity);
+        let (a, b, c, d, e) = (a.unwrap(), b.unwrap(), c.unwrap(), d.unwrap(), e.unwrap());
+
+        a.0 += 10;
+        b.0 += 10;
+        c.0 += 10;
+        d.0 += 10;
+        e.0 += 10;
+
+        let (a, b, c, d, e, f) = manager.query_entity_six::<A, B, C, D, E, F>(entity);
+        let values = (
+            a.unwrap(),
+            b.unwrap(),
+            c.unwrap(),
+            d.unwrap(),
+            e.unwrap(),
+            f.unwrap(),
+        );
+
+        assert_eq!(
+            [
+                values.0 .0,
+                values.1 .0,
+                values.2 .0,
+                values.3 .0,
+                values.4 .0,
+                values.5 .0
+            ],
+            [11, 12, 13, 14, 15, 6]
+        );
+    }
+
+    #[test]
+    fn remove_component_test() {
+        struct A;
+        struct B;
+
+        impl Component for A {}
+        impl Component for B {}
+
+        let mut manager = EntityManager::new();
+        let mut table = EntityQueryTable::new();
+
+        let entity = manager.entity();
+
+        manager
+            .entity_with::<A>(entity, A)
+            .entity_with::<B>(entity, B);
+
+        assert_eq!(table.query_single::<A>(&mut manager), Some(&vec![entity]));
+
+        manager.remove_component::<A>(entity);
+
+        assert_eq!(table.query_single::<A>(&mut manager), Some(&vec![]));
+        assert_eq!(table.query_single::<B>(&mut manager), Some(&vec![entity]));
+
+        // removing a missing component is a no-op.
+        manager.remove_component::<A>(entity);
+        manager.remove_component::<A>(entity + 1);
+        assert_eq!(manager.count::<B>(), 1);
+    }
+
+    #[test]
+    fn generation_test() {
+        struct A(u32);
+
+        impl Component for A {}
+
+        let mut manager = EntityManager::new();
+
+        let entity = manager.entity();
+        manager.entity_with::<A>(entity, A(1));
+
+        let handle = manager.handle(entity).unwrap();
+        assert_eq!(manager.resolve(handle), Some(entity));
+        assert_eq!(manager.query_handle::<A>(handle).unwrap().0.unwrap().0, 1);
+
+        manager.remove_entity(entity);
+        assert!(manager.handle(entity).is_none());
+
+        // the slot of the removed entity is recycled.
+        let recycled = manager.entity();
+        manager.entity_with::<A>(recycled, A(2));
+
+        assert_eq!(recycled, entity);
+        assert!(manager.resolve(handle).is_none());
+        assert!(manager.query_handle::<A>(handle).is_none());
+
+        let handle = manager.handle(recycled).unwrap();
+        assert_eq!(handle.generation(), 1);
+        assert_eq!(manager.query_handle::<A>(handle).unwrap().0.unwrap().0, 2);
+    }
+
+    #[test]
+    fn deferred_system_test() {
+        struct FrameSystem {
+            frames: Arc<Mutex<Vec<u64>>>,
+            frame: Arc<AtomicUsize>,
+        }
+
+        impl System<()> for FrameSystem {
+            fn update(
+                &mut self,
+                _: &mut EntityManager,
+                _: &mut EntityQueryTable,
+                _: &(),
+            ) -> Option<()> {
+                let frame = self.frame.load(Ordering::SeqCst) as u64;
+                self.frames.lock().unwrap().push(frame);
+                None
+            }
+        }
+
+        let frames = Arc::new(Mutex::new(vec![]));
+        let frame = Arc::new(AtomicUsize::new(0));
+
+        let mut world = World::<()>::new();
+
+        world.run_after_frame(
+            3,
+            FrameSystem {
+                frames: frames.clone(),
+                frame: frame.clone(),
+            },
+        );
+
+        for _ in 0..5 {
+            frame.store(world.frames() as usize + 1, Ordering::SeqCst);
+            world.update(SystemType::Loop, &());
+        }
+
+        assert_eq!(*frames.lock().unwrap(), vec![3]);
+        assert_eq!(world.frames(), 5);
+    }
+
+    #[test]
+    fn iter_test() {
+        struct A(usize);
+        struct B;
+
+        impl Component for A {}
+        impl Component for B {}
+
+        let mut manager = EntityManager::new();
+
+        assert_eq!(manager.iter::<A>().count(), 0);
+
+        let entities = (0..100).map(|_| manager.entity()).collect::<Vec<_>>();
+
+        for entity in &entities {
+            manager.entity_with::<A>(*entity, A(0));
+        }
+
+        for (entity, component) in manager.iter::<A>() {
+            component.0 = entity + 1;
+        }
+
+        for entity in &entities {
+            assert_eq!(manager.query_entity::<A>(*entity).0.unwrap().0, entity + 1);
+        }
+
+        assert_eq!(manager.iter::<B>().count(), 0);
+    }
+
+    #[test]
+    fn post_loop_system_test() {
+        struct Counter(u32);
+
+        impl Component for Counter {}
+
+        struct IncrementSystem;
+
+        impl PostLoopSystem for IncrementSystem {
+            fn update(
+                &mut self,
+                manager: &mut EntityManager,
+                _: &mut EntityQueryTable,
+            ) -> Option<()> {
+                for (_, counter) in manager.iter::<Counter>() {
+                    counter.0 += 1;
+                }
+
+                None
+            }
+        }
+
+        let mut world = World::<()>::new();
+        let entity = world.entity();
+
+        world
+            .with::<Counter>(entity, Counter(0))
+            .with_post_loop_system(IncrementSystem);
+
+        world.update(SystemType::Loop, &());
+        world.update(SystemType::Loop, &());
+
+        let counter = world.entity_manager.query_entity::<Counter>(entity).0;
+        assert_eq!(counter.unwrap().0, 2);
+    }
+
+    #[test]
+    fn reflect_test() {
+        #[derive(ecs_macro::Reflect)]
+        struct Health {
+            current: u32,
+            regeneration: f32,
+        }
+
+        struct Hidden;
+
+        impl Component for Health {}
+        impl Component for Hidden {}
+
+        let mut manager = EntityManager::new();
+        let entity = manager.entity();
+
+        manager
+            .register_reflect::<Health>()
+            .entity_with::<Health>(
+                entity,
+                Health {
+                    current: 80,
+                    regeneration: 0.5,
+                },
+            )
+            .entity_with::<Hidden>(entity, Hidden);
+
+        let mut reflected = manager.reflect(entity);
+        assert_eq!(reflected.len(), 1);
+
+        let (name, health) = &mut reflected[0];
+
+        assert!(name.ends_with("Health"));
+        assert_eq!(health.field_names(), vec!["current", "regeneration"]);
+        assert_eq!(health.field("current"), Some(Value::Int(80)));
+        assert_eq!(health.field("regeneration"), Some(Value::Float(0.5)));
+        assert_eq!(health.field("missing"), None);
+
+        assert!(health.set_field("current", &Value::Int(100)));
+        assert!(!health.set_field("current", &Value::Int(-1)));
+        assert!(!health.set_field("current", &Value::Text(String::new())));
+
+        let health = manager.query_entity::<Health>(entity).0.unwrap();
+        assert_eq!(health.current, 100);
+    }
+
+    #[test]
+    fn distinct_borrow_test() {
+        struct A(u32);
+        struct B(u32);
+
+        impl Component for A {}
+        impl Component for B {}
+
+        let mut manager = EntityManager::new();
+        let entity = manager.entity();
+
+        manager
+            .entity_with::<A>(entity, A(1))
+            .entity_with::<B>(entity, B(2));
+
+        let (a, b) = manager.query_entity_two::<A, B>(entity);
+        let (a, b) = (a.unwrap(), b.unwrap());
+
+        std::mem::swap(&mut a.0, &mut b.0);
+
+        assert_eq!(manager.query_entity::<A>(entity).0.unwrap().0, 2);
+        assert_eq!(manager.query_entity::<B>(entity).0.unwrap().0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "requested more than once")]
+    fn aliasing_borrow_test() {
+        struct A;
+
+        impl Component for A {}
+
+        let mut manager = EntityManager::new();
+        let entity = manager.entity();
+
+        manager.entity_with::<A>(entity, A);
+        manager.query_entity_two::<A, A>(entity);
+    }
+
+    #[test]
+    fn hooks_test() {
+        static ADDED: AtomicUsize = AtomicUsize::new(0);
+        static REMOVED: AtomicUsize = AtomicUsize::new(0);
+
+        struct Hooked;
+
+        impl Component for Hooked {
+            fn on_add(&mut self, _: usize) {
+                ADDED.fetch_add(1, Ordering::SeqCst);
+            }
+
+            fn on_remove(&mut self, _: usize) {
+                REMOVED.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let mut manager = EntityManager::new();
+        let entities = (0..3).map(|_| manager.entity()).collect::<Vec<_>>();
+
+        for entity in &entities {
+            manager.entity_with::<Hooked>(*entity, Hooked);
+        }
+
+        // attaching a second component of the same type is ignored, and so is removing a missing one.
+        manager.entity_with::<Hooked>(entities[0], Hooked);
+        manager.remove_component::<Hooked>(entities[0]);
+        manager.remove_component::<Hooked>(entities[0]);
+        manager.remove_entity(entities[1]);
+
+        assert_eq!(ADDED.load(Ordering::SeqCst), 3);
+        assert_eq!(REMOVED.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn generic_component_test() {
+        #[derive(ecs_macro::EntityComponent)]
+        struct Bounded<T: Clone + 'static>(T);
+
+        #[derive(ecs_macro::EntityComponent)]
+        struct Clauses<T, R>
+        where
+            T: 'static,
+            R: Copy + 'static,
+        {
+            first: T,
+            second: R,
+        }
+
+        let mut manager = EntityManager::new();
+        let entity = manager.entity();
+
+        manager
+            .entity_with::<Bounded<u32>>(entity, Bounded(1))
+            .entity_with::<Clauses<String, f32>>(
+                entity,
+                Clauses {
+                    first: String::from("first"),
+                    second: 2.0,
+                },
+            );
+
+        let (bounded, clauses) =
+            manager.query_entity_two::<Bounded<u32>, Clauses<String, f32>>(entity);
+        let (bounded, clauses) = (bounded.unwrap(), clauses.unwrap());
+
+        assert_eq!(bounded.0, 1);
+        assert_eq!((clauses.first.as_str(), clauses.second), ("first", 2.0));
+    }
+
+    #[test]
+    fn bundle_test() {
+        struct A(u32);
+        struct B(u32);
+
+        impl Component for A {}
+        impl Component for B {}
+
+        #[derive(ecs_macro::Bundle)]
+        struct Pair {
+            a: A,
+            b: B,
+        }
+
+        let mut world = World::<()>::new();
+
+        let entity = world.spawn_bundle(Pair { a: A(1), b: B(2) });
+        let (a, b) = world.entity_manager.query_entity_two::<A, B>(entity);
+
+        assert_eq!((a.unwrap().0, b.unwrap().0), (1, 2));
+
+        let other = world.entity();
+        (A(3),).insert(&mut world.entity_manager, other);
+
+        assert_eq!(world.entity_manager.count::<A>(), 2);
+        assert_eq!(world.entity_manager.count::<B>(), 1);
+    }
+
+    #[test]
+    fn update_once_test() {
+        struct Count(u32);
+
+        impl Component for Count {}
+
+        struct CountSystem(u32);
+
+        impl System<()> for CountSystem {
+            fn update(
+                &mut self,
+                manager: &mut EntityManager,
+                _: &mut EntityQueryTable,
+                _: &(),
+            ) -> Option<()> {
+                manager.iter::<Count>().next()?.1 .0 += self.0;
+                None
+            }
+        }
+
+        let mut world = World::<()>::new();
+        let entity = world.entity();
+        world.with(entity, Count(0));
+        world
+            .with_system(SystemType::Init, CountSystem(100))
+            .with_system(SystemType::Loop, CountSystem(1));
+
+        world.update_once(&());
+        world.update_once(&());
+
+        let (count,) = world.entity_manager.query_entity::<Count>(entity);
+        assert_eq!(count.unwrap().0, 102);
+        assert_eq!(world.frames(), 2);
+    }
+
+    #[test]
+    fn optional_join_test() {
+        struct Mesh(u32);
+        struct Uniform(u32);
+
+        impl Component for Mesh {}
+        impl Component for Uniform {}
+
+        let mut manager = EntityManager::new();
+        let with_uniform = manager.entity();
+        let without_uniform = manager.entity();
+        let without_mesh = manager.entity();
+
+        manager
+            .entity_with(with_uniform, Mesh(1))
+            .entity_with(with_uniform, Uniform(2))
+            .entity_with(without_uniform, Mesh(3))
+            .entity_with(without_mesh, Uniform(4));
+
+        let (mesh, uniform) = manager
+            .join_entity_two::<Mesh, Option<Uniform>>(with_uniform)
+            .unwrap();
+        mesh.0 += 10;
+        assert_eq!(uniform.map(|uniform| uniform.0), Some(2));
+
+        let (mesh, uniform) = manager
+            .join_entity_two::<Mesh, Option<Uniform>>(without_uniform)
+            .unwrap();
+        assert_eq!(mesh.0, 3);
+        assert!(uniform.is_none());
+
+        assert!(manager
+            .join_entity_two::<Mesh, Option<Uniform>>(without_mesh)
+            .is_none());
+        assert_eq!(manager.query_entity::<Mesh>(with_uniform).0.unwrap().0, 11);
+    }
+
+    #[test]
+    fn clear_test() {
+        static REMOVED: AtomicUsize = AtomicUsize::new(0);
+
+        struct Position(u32);
+
+        impl Component for Position {
+            fn on_remove(&mut self, _: usize) {
+                REMOVED.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        struct CountSystem(Arc<Mutex<u32>>);
+
+        impl System<()> for CountSystem {
+            fn update(
+                &mut self,
+                _: &mut EntityManager,
+                _: &mut EntityQueryTable,
+                _: &(),
+            ) -> Option<()> {
+                *self.0.lock().unwrap() += 1;
+                None
+            }
+        }
+
+        let runs = Arc::new(Mutex::new(0));
+        let mut world = World::<()>::new();
+        world.with_system(SystemType::Loop, CountSystem(runs.clone()));
+
+        for i in 0..3 {
+            let entity = world.entity();
+            world.with(entity, Position(i));
+        }
+
+        assert_eq!(
+            world
+                .entity_query_table
+                .query_single::<Position>(&mut world.entity_manager)
+                .map(Vec::len),
+            Some(3)
+        );
+
+        let stale = world.entity_manager.handle(0).unwrap();
+
+        world.update_once(&());
+        world.clear();
+
+        assert_eq!(REMOVED.load(Ordering::Relaxed), 3);
+        assert_eq!(world.entity_manager.resolve(stale), None);
+        assert_eq!(world.entity_manager.count::<Position>(), 0);
+        assert_eq!(
+            world
+                .entity_query_table
+                .query::<(Position,)>(&mut world.entity_manager),
+            Some(vec![])
+        );
+
+        let entity = world.entity();
+        world.with(entity, Position(7));
+        world.update_once(&());
+
+        assert_eq!(entity, 0);
+
+        // the id is reused, but the handle taken before the clear still refers to the removed entity.
+        let fresh = world.entity_manager.handle(entity).unwrap();
+
+        assert_eq!(world.entity_manager.resolve(stale), None);
+        assert_eq!(world.entity_manager.resolve(fresh), Some(entity));
+        assert_eq!(
+            world
+                .entity_query_table
+                .query_single::<Position>(&mut world.entity_manager),
+            Some(&vec![0])
+        );
+        assert_eq!(
+            world
+                .entity_manager
+                .query_entity::<Position>(entity)
+                .0
+                .map(|position| position.0),
+            Some(7)
+        );
+        assert_eq!(*runs.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn enable_test() {
+        #[derive(Debug, PartialEq)]
+        struct Position(u32);
+        struct Velocity;
+
+        impl Component for Position {}
+        impl Component for Velocity {}
+
+        let mut manager = EntityManager::new();
+        let mut table = EntityQueryTable::new();
+        let first = manager.entity();
+        let second = manager.entity();
+
+        manager
+            .entity_with(first, Position(1))
+            .entity_with(first, Velocity)
+            .entity_with(second, Position(2))
+            .entity_with(second, Velocity);
+
+        assert_eq!(
+            table.query_single::<Position>(&mut manager),
+            Some(&vec![0, 1])
+        );
+
+        manager.set_enabled(first, false);
+
+        assert!(!manager.is_enabled(first));
+        assert_eq!(table.query_single::<Position>(&mut manager), Some(&vec![1]));
+        assert_eq!(
+            table.query::<(Position, Velocity)>(&mut manager),
+            Some(vec![1])
+        );
+        assert_eq!(manager.count::<Position>(), 2);
+
+        manager.set_enabled(first, true);
+
+        assert_eq!(
+            table.query_single::<Position>(&mut manager),
+            Some(&vec![0, 1])
+        );
+        assert_eq!(
+            table.query::<(Position, Velocity)>(&mut manager),
+            Some(vec![0, 1])
+        );
+        assert_eq!(
+            manager.query_entity::<Position>(first).0,
+            Some(&mut Position(1))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn save_load_test() {
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Position(f32, f32);
+        // stands in for a GPU-backed component, which isn't whitelisted.
+        struct Mesh;
+
+        impl Component for Position {}
+        impl Component for Mesh {}
+
+        let mut world = World::<()>::new();
+        world.register_save::<Position>("position");
+
+        let first = world.entity();
+        let second = world.entity();
+        let third = world.entity();
+        world
+            .with(first, Position(1.0, 2.0))
+            .with(first, Mesh)
+            .with(third, Position(-3.0, 0.5));
+        world.remove_entity(second);
+
+        let mut save = vec![];
+        world.save(&mut save).unwrap();
+
+        let mut loaded = World::<()>::new();
+        loaded.register_save::<Position>("position");
+        loaded.load(save.as_slice()).unwrap();
+
+        let manager = &mut loaded.entity_manager;
+        assert_eq!(
+            manager.query_entity::<Position>(first).0,
+            Some(&mut Position(1.0, 2.0))
+        );
+        assert_eq!(
+            manager.query_entity::<Position>(third).0,
+            Some(&mut Position(-3.0, 0.5))
+        );
+        assert_eq!(manager.count::<Position>(), 2);
+        assert_eq!(manager.count::<Mesh>(), 0);
+        assert!(manager.handle(second).is_none());
+
+        let mut unknown = World::<()>::new();
+        assert!(unknown.load(save.as_slice()).is_err());
+    }
+
+    #[test]
+    fn component_count_test() {
+        struct Mesh;
+        struct Camera;
+
+        impl Component for Mesh {}
+        impl Component for Camera {}
+
+        let mut manager = EntityManager::new();
+
+        assert_eq!(manager.count::<Mesh>(), 0);
+        assert!(!manager.is_alive(0));
+
+        let first = manager.entity();
+        let second = manager.entity();
+
+        manager
+            .entity_with(first, Mesh)
+            .entity_with(second, Mesh)
+            .entity_with(second, Camera);
+
+        assert_eq!(manager.count::<Mesh>(), 2);
+        assert_eq!(manager.count::<Camera>(), 1);
+        assert!(manager.has_component::<Camera>(second));
+        assert!(!manager.has_component::<Camera>(first));
+
+        manager.remove_component::<Mesh>(first);
+        manager.remove_entity(second);
+
+        assert_eq!(manager.count::<Mesh>(), 0);
+        assert_eq!(manager.count::<Camera>(), 0);
+        assert!(!manager.has_component::<Camera>(second));
+        assert!(manager.is_alive(first));
+        assert!(!manager.is_alive(second));
+    }
+
+    #[test]
+    fn events_test() {
+        #[derive(Debug, Clone, PartialEq)]
+        struct Hit(usize, usize);
+
+        struct CollisionSystem;
+        struct SoundSystem(Arc<Mutex<Vec<Vec<Hit>>>>);
+
+        impl System<()> for CollisionSystem {
+            fn update(
+                &mut self,
+                manager: &mut EntityManager,
+                _: &mut EntityQueryTable,
+                _: &(),
+            ) -> Option<()> {
+                if manager.resource::<u32>().is_none() {
+                    manager.insert_resource(0u32);
+                    manager.send_event(Hit(1, 2)).send_event(Hit(3, 4));
+                }
+
+                None
+            }
+        }
+
+        impl System<()> for SoundSystem {
+            fn update(
+                &mut self,
+                manager: &mut EntityManager,
+                _: &mut EntityQueryTable,
+                _: &(),
+            ) -> Option<()> {
+                let events = manager.resource_mut::<Events<Hit>>()?;
+                self.0.lock().unwrap().push(events.drain().collect());
+                None
+            }
+        }
+
+        let heard = Arc::new(Mutex::new(vec![]));
+        let mut world = World::<()>::new();
+        world
+            .add_event::<Hit>()
+            .with_system(SystemType::Loop, CollisionSystem)
+            .with_system(SystemType::Loop, SoundSystem(heard.clone()));
+
+        for _ in 0..3 {
+            world.update_once(&());
+        }
+
+        assert_eq!(
+            *heard.lock().unwrap(),
+            vec![vec![], vec![Hit(1, 2), Hit(3, 4)], vec![]]
+        );
+    }
+
+    #[test]
+    fn system_priority_test() {
+        struct Ordered(&'static str, Arc<Mutex<Vec<&'static str>>>);
+
+        impl System<()> for Ordered {
+            fn update(
+                &mut self,
+                _: &mut EntityManager,
+                _: &mut EntityQueryTable,
+                _: &(),
+            ) -> Option<()> {
+                self.1.lock().unwrap().push(self.0);
+                None
+            }
+        }
+
+        let order = Arc::new(Mutex::new(vec![]));
+        let mut world = World::<()>::new();
+        world
+            .with_system_ordered(SystemType::Loop, 10, Ordered("render", order.clone()))
+            .with_system(SystemType::Loop, Ordered("input", order.clone()))
+            .with_system_ordered(SystemType::Loop, -5, Ordered("rotate", order.clone()))
+            .with_system_ordered(SystemType::Loop, 0, Ordered("physics", order.clone()));
+
+        world.update(SystemType::Loop, &());
+
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec!["rotate", "input", "physics", "render"]
+        );
+    }
+
+    #[test]
+    fn disabled_query_cache_test() {
+        struct Position;
+        struct Velocity;
+
+        impl Component for Position {}
+        impl Component for Velocity {}
+
+        let mut manager = EntityManager::new();
+        let mut table = EntityQueryTable::new();
+        let first = manager.entity();
+        let second = manager.entity();
+
+        manager
+            .entity_with(first, Position)
+            .entity_with(first, Velocity)
+            .entity_with(second, Position);
+
+        manager.set_enabled(first, false);
+
+        // the tuple query fills the cache shared with `query_single`, which must not hand out the disabled entity.
+        assert_eq!(table.query::<(Position,)>(&mut manager), Some(vec![second]));
+        assert_eq!(
+            table.query_single::<Position>(&mut manager),
+            Some(&vec![second])
+        );
+
+        let (changed, _) = table.query_changed::<Position>(&mut manager, 0).unwrap();
+        assert_eq!(changed, vec![second]);
+        assert_eq!(
+            table.query_filtered::<(Position,), (Velocity,)>(&mut manager),
+            Some(vec![second])
+        );
+
+        manager.set_enabled(first, true);
+
+        assert_eq!(
+            table.query_single::<Position>(&mut manager),
+            Some(&vec![first, second])
+        );
+        assert_eq!(
+            table.query::<(Position, Velocity)>(&mut manager),
+            Some(vec![first])
+        );
+    }
 }
@@ -0,0 +1,160 @@
+use std::{
+    any::{type_name, TypeId},
+    collections::HashMap,
+};
+
+use crate::component::{
+    borrow_mut_manager, Component, ComponentManager, SimpleComponentManager, TypedComponentManager,
+};
+
+/// The value of a reflected field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+/// A field type which can be read and written through [Value]s.
+pub trait ReflectValue {
+    fn reflect(&self) -> Value;
+
+    /// Overwrites the field with the given value. Returns `false` if the value has the wrong type or is out of range.
+    fn apply(&mut self, value: &Value) -> bool;
+}
+
+macro_rules! reflect_int {
+    ($($T:ty),+) => {
+        $(
+            impl ReflectValue for $T {
+                fn reflect(&self) -> Value {
+                    Value::Int(*self as i64)
+                }
+
+                fn apply(&mut self, value: &Value) -> bool {
+                    match value {
+                        Value::Int(value) => match (*value).try_into() {
+                            Ok(value) => {
+                                *self = value;
+                                true
+                            }
+                            Err(_) => false,
+                        },
+                        _ => false,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+macro_rules! reflect_float {
+    ($($T:ty),+) => {
+        $(
+            impl ReflectValue for $T {
+                fn reflect(&self) -> Value {
+                    Value::Float(*self as f64)
+                }
+
+                fn apply(&mut self, value: &Value) -> bool {
+                    match value {
+                        Value::Float(value) => *self = *value as $T,
+                        Value::Int(value) => *self = *value as $T,
+                        _ => return false,
+                    }
+
+                    true
+                }
+            }
+        )+
+    };
+}
+
+reflect_int!(i8, i16, i32, i64, u8, u16, u32, u64, usize, isize);
+reflect_float!(f32, f64);
+
+impl ReflectValue for bool {
+    fn reflect(&self) -> Value {
+        Value::Bool(*self)
+    }
+
+    fn apply(&mut self, value: &Value) -> bool {
+        match value {
+            Value::Bool(value) => {
+                *self = *value;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl ReflectValue for String {
+    fn reflect(&self) -> Value {
+        Value::Text(self.clone())
+    }
+
+    fn apply(&mut self, value: &Value) -> bool {
+        match value {
+            Value::Text(value) => {
+                *self = value.clone();
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Exposes the named fields of a component, so they can be shown and edited by an inspector.
+///
+/// This is usually derived with `#[derive(Reflect)]`, which requires every field to implement [ReflectValue].
+pub trait Reflect {
+    fn field_names(&self) -> Vec<&'static str>;
+
+    fn field(&self, name: &str) -> Option<Value>;
+
+    /// Overwrites a field. Returns `false` if there's no such field, or the value can't be applied to it.
+    fn set_field(&mut self, name: &str, value: &Value) -> bool;
+}
+
+type Reflector = fn(&mut dyn ComponentManager, usize) -> Option<&mut dyn Reflect>;
+
+/// The component types which can be reflected, see [EntityManager::register_reflect].
+///
+/// [EntityManager::register_reflect]: crate::entity::EntityManager::register_reflect
+#[derive(Default)]
+pub struct ReflectRegistry {
+    reflectors: HashMap<TypeId, (&'static str, Reflector)>,
+}
+
+impl ReflectRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<T: 'static + Component + Reflect>(&mut self) {
+        fn reflect<T: 'static + Component + Reflect>(
+            manager: &mut dyn ComponentManager,
+            entity: usize,
+        ) -> Option<&mut dyn Reflect> {
+            let manager: &mut SimpleComponentManager<T> = borrow_mut_manager(manager);
+            Some(manager.component_mut(entity)?)
+        }
+
+        self.reflectors
+            .insert(TypeId::of::<T>(), (type_name::<T>(), reflect::<T>));
+    }
+
+    /// Returns the type name and the reflection of the component of the given type, if it's registered and present
+    /// on the entity.
+    pub fn reflect<'a>(
+        &self,
+        type_id: &TypeId,
+        manager: &'a mut dyn ComponentManager,
+        entity: usize,
+    ) -> Option<(&'static str, &'a mut dyn Reflect)> {
+        let (name, reflector) = self.reflectors.get(type_id)?;
+        Some((name, reflector(manager, entity)?))
+    }
+}
@@ -0,0 +1,132 @@
+use std::{
+    any::TypeId,
+    collections::BTreeMap,
+    io::{Read, Write},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::{
+    component::{
+        borrow_manager_ref, Component, ComponentManager, SimpleComponentManager,
+        TypedComponentManager,
+    },
+    entity::EntityManager,
+};
+
+/// Serializes the components as a sequence of `(entity, component)` pairs.
+impl<T> Serialize for SimpleComponentManager<T>
+where
+    T: Component + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.entities.iter().zip(self.components.iter()))
+    }
+}
+
+impl<'de, T> Deserialize<'de> for SimpleComponentManager<T>
+where
+    T: Component + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut manager = Self::new();
+
+        for (entity, component) in Vec::<(usize, T)>::deserialize(deserializer)? {
+            manager.with(entity, component);
+        }
+
+        Ok(manager)
+    }
+}
+
+type Saver = fn(&dyn ComponentManager) -> serde_json::Result<Value>;
+type Loader = fn(&mut EntityManager, Value) -> serde_json::Result<()>;
+
+/// The component types which are written by `World::save` and read by `World::load`, each under a stable name.
+///
+/// Only plain-data components should be registered. Components holding GPU resources, such as meshes and textures,
+/// can't be serialized and have to be recreated after loading.
+#[derive(Default)]
+pub struct SaveRegistry {
+    types: BTreeMap<&'static str, (TypeId, Saver, Loader)>,
+}
+
+/// The format of a save, the components of every registered type by name.
+#[derive(Serialize, Deserialize)]
+struct SaveData {
+    components: BTreeMap<String, Value>,
+}
+
+impl SaveRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a component type under `name`, which identifies the type in saves. Registering another type under
+    /// the same name replaces the previous type.
+    pub fn register<T>(&mut self, name: &'static str)
+    where
+        T: Component + Serialize + DeserializeOwned,
+    {
+        fn save<T: Component + Serialize>(
+            manager: &dyn ComponentManager,
+        ) -> serde_json::Result<Value> {
+            let manager: &SimpleComponentManager<T> = borrow_manager_ref(manager);
+            serde_json::to_value(manager)
+        }
+
+        fn load<T: Component + DeserializeOwned>(
+            manager: &mut EntityManager,
+            value: Value,
+        ) -> serde_json::Result<()> {
+            let components = serde_json::from_value::<Vec<(usize, T)>>(value)?;
+
+            for (entity, _) in &components {
+                manager.entity_at(*entity);
+            }
+
+            manager.entity_with_many(components);
+            Ok(())
+        }
+
+        self.types
+            .insert(name, (TypeId::of::<T>(), save::<T>, load::<T>));
+    }
+
+    /// Writes the components of all registered types, other components are skipped.
+    pub fn save(&self, manager: &EntityManager, writer: impl Write) -> serde_json::Result<()> {
+        let mut components = BTreeMap::new();
+
+        for (name, (type_id, save, _)) in &self.types {
+            if let Some(manager) = manager.component_manager(*type_id) {
+                components.insert(name.to_string(), save(manager)?);
+            }
+        }
+
+        serde_json::to_writer(writer, &SaveData { components })
+    }
+
+    /// Reads the components written by [SaveRegistry::save] into `manager`, spawning their entities with the ids
+    /// they were saved with.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the save can't be parsed, or contains a component type which isn't registered.
+    pub fn load(&self, manager: &mut EntityManager, reader: impl Read) -> serde_json::Result<()> {
+        let data: SaveData = serde_json::from_reader(reader)?;
+
+        for (name, value) in data.components {
+            let Some((_, _, load)) = self.types.get(name.as_str()) else {
+                return Err(serde::de::Error::custom(format!(
+                    "unknown component `{}`",
+                    name
+                )));
+            };
+
+            load(manager, value)?;
+        }
+
+        Ok(())
+    }
+}
@@ -9,4 +9,24 @@ pub trait System<T>: Send + Sync {
         table: &mut EntityQueryTable,
         data: &T,
     ) -> Option<()>;
+
+    /// Whether this system should keep running while the `World` is paused.
+    ///
+    /// Gameplay systems are frozen while paused by default, systems which should keep running (rendering, UI)
+    /// should override this to return `true`.
+    fn runs_while_paused(&self) -> bool {
+        false
+    }
+}
+
+/// A system which runs after the loop systems, see [crate::world::World::with_post_loop_system].
+///
+/// Unlike [System], a post loop system doesn't receive the world's data (e.g. a `Display`).
+pub trait PostLoopSystem {
+    fn update(&mut self, manager: &mut EntityManager, table: &mut EntityQueryTable) -> Option<()>;
+
+    /// Whether this system should keep running while the `World` is paused, see [System::runs_while_paused].
+    fn runs_while_paused(&self) -> bool {
+        false
+    }
 }
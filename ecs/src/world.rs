@@ -4,26 +4,50 @@ use std::{
 };
 
 use crate::{
+    bundle::Bundle,
     component::Component,
     entity::{EntityManager, EntityQueryTable},
-    system::System,
+    system::{PostLoopSystem, System},
 };
 
+#[cfg(feature = "serde")]
+use std::io::{Read, Write};
+
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(feature = "serde")]
+use crate::save::SaveRegistry;
+
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum SystemType {
     Init,
     Loop,
 }
 
+type SharedSystem<T> = Arc<Mutex<dyn System<T>>>;
+
+/// A system together with the priority it was registered with, see `World::with_system_ordered`.
+type PrioritizedSystem<T> = (i32, SharedSystem<T>);
+
 pub struct SystemContainer<T> {
-    loop_systems: Vec<Arc<Mutex<dyn System<T>>>>,
-    init_systems: Vec<Arc<Mutex<dyn System<T>>>>,
+    // both are sorted by the priority the systems were registered with, see `World::with_system_ordered`.
+    loop_systems: Vec<PrioritizedSystem<T>>,
+    init_systems: Vec<PrioritizedSystem<T>>,
+    // the systems to run once, together with the frame they're due after.
+    deferred_systems: Vec<(u64, SharedSystem<T>)>,
+    post_loop_systems: Vec<Box<dyn PostLoopSystem>>,
 }
 
 pub struct World<F> {
     pub entity_manager: EntityManager,
     pub entity_query_table: EntityQueryTable,
     pub system_container: SystemContainer<F>,
+    paused: bool,
+    frames: u64,
+    initialized: bool,
+    #[cfg(feature = "serde")]
+    save_registry: SaveRegistry,
 }
 
 impl<F> World<F> {
@@ -34,7 +58,14 @@ impl<F> World<F> {
             system_container: SystemContainer {
                 loop_systems: vec![],
                 init_systems: vec![],
+                deferred_systems: vec![],
+                post_loop_systems: vec![],
             },
+            paused: false,
+            frames: 0,
+            initialized: false,
+            #[cfg(feature = "serde")]
+            save_registry: SaveRegistry::new(),
         }
     }
 
@@ -50,6 +81,14 @@ impl<F> World<F> {
         self.entity_manager.remove_entity(entity);
     }
 
+    /// Removes all entities and their components, see [EntityManager::clear_all], and drops the cached queries.
+    ///
+    /// Systems and resources are kept, so the world can be filled with the entities of the next scene right away.
+    pub fn clear(&mut self) {
+        self.entity_manager.clear_all();
+        self.entity_query_table = EntityQueryTable::new();
+    }
+
     pub fn register<T>(&mut self) -> &mut Self
     where
         T: Component + 'static,
@@ -72,7 +111,47 @@ impl<F> World<F> {
         self
     }
 
+    /// Inserts a resource, see [EntityManager::insert_resource]. Systems access resources through their manager.
+    pub fn insert_resource<R: 'static>(&mut self, resource: R) -> &mut Self {
+        self.entity_manager.insert_resource(resource);
+        self
+    }
+
+    pub fn resource<R: 'static>(&self) -> Option<&R> {
+        self.entity_manager.resource()
+    }
+
+    pub fn resource_mut<R: 'static>(&mut self) -> Option<&mut R> {
+        self.entity_manager.resource_mut()
+    }
+
+    /// Registers an event queue, see [EntityManager::add_event].
+    pub fn add_event<E: 'static>(&mut self) -> &mut Self {
+        self.entity_manager.add_event::<E>();
+        self
+    }
+
+    /// Creates a new entity with every component of the bundle, see [EntityManager::spawn_bundle].
+    pub fn spawn_bundle<B: Bundle>(&mut self, bundle: B) -> usize {
+        self.entity_manager.spawn_bundle(bundle)
+    }
+
+    /// Adds a system with priority `0`, see [World::with_system_ordered].
     pub fn with_system<T>(&mut self, system_type: SystemType, system: T) -> &mut Self
+    where
+        T: System<F> + 'static,
+    {
+        self.with_system_ordered(system_type, 0, system)
+    }
+
+    /// Adds a system which runs before the systems of the same type with a higher `priority`, and after the ones
+    /// with a lower or the same priority, so systems with equal priorities run in registration order.
+    pub fn with_system_ordered<T>(
+        &mut self,
+        system_type: SystemType,
+        priority: i32,
+        system: T,
+    ) -> &mut Self
     where
         T: System<F> + 'static,
     {
@@ -82,23 +161,168 @@ impl<F> World<F> {
             SystemType::Loop => &mut self.system_container.init_systems,
         };
 
-        systems.push(reference_counted);
+        let index = systems.partition_point(|(other, _)| *other <= priority);
+        systems.insert(index, (priority, reference_counted));
+
+        self
+    }
+
+    /// Adds a system which runs during every [World::update] with [SystemType::Loop], after the loop systems.
+    ///
+    /// The systems run on the thread calling the update, as the entities can hold GL objects which must not leave the
+    /// thread owning the context.
+    pub fn with_post_loop_system<T>(&mut self, system: T) -> &mut Self
+    where
+        T: PostLoopSystem + 'static,
+    {
+        self.system_container
+            .post_loop_systems
+            .push(Box::new(system));
+
+        self
+    }
+
+    /// Schedules a system to run exactly once, right after the loop systems of frame `frame` have run. The system is
+    /// removed afterwards.
+    ///
+    /// Frames are counted by [World::update] calls with [SystemType::Loop], starting at `1`. This is useful for setup
+    /// which has to happen after the first real frame, rather than during initialization.
+    pub fn run_after_frame<T>(&mut self, frame: u64, system: T) -> &mut Self
+    where
+        T: System<F> + 'static,
+    {
+        self.system_container
+            .deferred_systems
+            .push((frame, Arc::new(Mutex::new(system))));
+
+        self
+    }
+
+    /// Returns the amount of frames, i.e. [World::update] calls with [SystemType::Loop], which have run so far.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
 
+    /// Pauses or resumes the world. While paused, [World::update] only runs the systems for which
+    /// [System::runs_while_paused] returns `true`.
+    pub fn set_paused(&mut self, paused: bool) -> &mut Self {
+        self.paused = paused;
         self
     }
 
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
     pub fn update(&mut self, system_type: SystemType, data: &F) {
         let systems = match system_type {
             SystemType::Init => &mut self.system_container.loop_systems,
             SystemType::Loop => &mut self.system_container.init_systems,
         };
 
-        for system in systems.iter_mut() {
+        for (_, system) in systems.iter_mut() {
             let mut system = system.lock().unwrap();
 
+            if self.paused && !system.runs_while_paused() {
+                continue;
+            }
+
             system.update(&mut self.entity_manager, &mut self.entity_query_table, data);
 
             self.entity_manager.tick_frame();
         }
+
+        if system_type == SystemType::Loop {
+            self.run_post_loop();
+
+            self.frames += 1;
+            self.run_deferred(data);
+
+            // events sent during this frame become readable during the next one.
+            self.entity_manager.update_events();
+        }
+    }
+
+    /// Runs a single frame without an event loop, e.g. for tests or headless rendering.
+    ///
+    /// The first call also runs the [SystemType::Init] systems before the frame, later calls only run the
+    /// [SystemType::Loop] systems.
+    pub fn update_once(&mut self, data: &F) {
+        if !self.initialized {
+            self.initialized = true;
+            self.update(SystemType::Init, data);
+        }
+
+        self.update(SystemType::Loop, data);
+    }
+
+    /// Runs the systems added with [World::with_post_loop_system].
+    fn run_post_loop(&mut self) {
+        for system in self.system_container.post_loop_systems.iter_mut() {
+            if self.paused && !system.runs_while_paused() {
+                continue;
+            }
+
+            system.update(&mut self.entity_manager, &mut self.entity_query_table);
+
+            self.entity_manager.tick_frame();
+        }
+    }
+
+    /// Runs and removes the deferred systems which are due. Systems which don't run while paused stay scheduled
+    /// until the world is resumed.
+    fn run_deferred(&mut self, data: &F) {
+        let frames = self.frames;
+        let paused = self.paused;
+        let mut due = vec![];
+
+        self.system_container
+            .deferred_systems
+            .retain(|(frame, system)| {
+                let ready =
+                    *frame <= frames && (!paused || system.lock().unwrap().runs_while_paused());
+
+                if ready {
+                    due.push(system.clone());
+                }
+
+                !ready
+            });
+
+        for system in due {
+            let mut system = system.lock().unwrap();
+
+            system.update(&mut self.entity_manager, &mut self.entity_query_table, data);
+
+            self.entity_manager.tick_frame();
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<F> World<F> {
+    /// Whitelists a plain-data component type for [World::save] and [World::load], under a `name` which has to stay
+    /// the same between the versions of a game reading each other's saves.
+    pub fn register_save<T>(&mut self, name: &'static str) -> &mut Self
+    where
+        T: Component + Serialize + DeserializeOwned,
+    {
+        self.save_registry.register::<T>(name);
+        self
+    }
+
+    /// Writes the components of every type registered with [World::register_save] as JSON. Other components, such as
+    /// the GPU-backed ones, are skipped.
+    pub fn save(&self, writer: impl Write) -> serde_json::Result<()> {
+        self.save_registry.save(&self.entity_manager, writer)
+    }
+
+    /// Replaces all entities with the ones written by [World::save], see [World::clear].
+    ///
+    /// The entities keep the ids they were saved with. Components which weren't saved, e.g. meshes, have to be added
+    /// again afterwards. On error, the world may contain part of the save.
+    pub fn load(&mut self, reader: impl Read) -> serde_json::Result<()> {
+        self.clear();
+        self.save_registry.load(&mut self.entity_manager, reader)
     }
 }
@@ -1,31 +1,105 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, DeriveInput};
+use syn::{parse_macro_input, Data, DataStruct, DeriveInput, Fields, Member};
 
 #[proc_macro_derive(EntityComponent)]
 pub fn derive_ecs_component(item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as DeriveInput);
     let name = input.ident;
-    let generics = input.generics;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
 
-    if generics.params.is_empty() {
-        quote! {
-            impl ecs::component::Component for #name {}
+    quote! {
+        impl #impl_generics ecs::component::Component for #name #type_generics #where_clause {}
+    }
+    .into()
+}
+
+/// Derives `ecs::reflect::Reflect` for a struct with named fields, every field has to implement
+/// `ecs::reflect::ReflectValue`.
+#[proc_macro_derive(Reflect)]
+pub fn derive_reflect(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    let name = input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(fields),
+            ..
+        }) => fields.named,
+        _ => {
+            return syn::Error::new(
+                name.span(),
+                "Reflect can only be derived for structs with named fields",
+            )
+            .to_compile_error()
+            .into()
         }
-        .into()
-    } else {
-        let where_clause = match generics.where_clause.as_ref() {
-            Some(clause) => quote! {
-                #clause
-            },
-            None => quote!(),
-        };
-
-        quote! {
-            impl #generics ecs::component::Component for #name #generics
-            #where_clause
-            {}
+    };
+
+    let idents = fields
+        .iter()
+        .map(|field| field.ident.clone().unwrap())
+        .collect::<Vec<_>>();
+    let names = idents
+        .iter()
+        .map(|ident| ident.to_string())
+        .collect::<Vec<_>>();
+
+    quote! {
+        impl #impl_generics ecs::reflect::Reflect for #name #type_generics #where_clause {
+            fn field_names(&self) -> Vec<&'static str> {
+                vec![#(#names,)*]
+            }
+
+            fn field(&self, name: &str) -> Option<ecs::reflect::Value> {
+                match name {
+                    #(#names => Some(ecs::reflect::ReflectValue::reflect(&self.#idents)),)*
+                    _ => None,
+                }
+            }
+
+            fn set_field(&mut self, name: &str, value: &ecs::reflect::Value) -> bool {
+                match name {
+                    #(#names => ecs::reflect::ReflectValue::apply(&mut self.#idents, value),)*
+                    _ => false,
+                }
+            }
+        }
+    }
+    .into()
+}
+
+/// Derives `ecs::bundle::Bundle` for a struct whose fields are all components.
+#[proc_macro_derive(Bundle)]
+pub fn derive_bundle(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    let name = input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let members = match input.data {
+        Data::Struct(data) => data
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(index.into()),
+            })
+            .collect::<Vec<_>>(),
+        _ => {
+            return syn::Error::new(name.span(), "Bundle can only be derived for structs")
+                .to_compile_error()
+                .into()
+        }
+    };
+
+    quote! {
+        impl #impl_generics ecs::bundle::Bundle for #name #type_generics #where_clause {
+            fn insert(self, manager: &mut ecs::entity::EntityManager, entity: usize) {
+                #(manager.entity_with(entity, self.#members);)*
+            }
         }
-        .into()
     }
+    .into()
 }
@@ -0,0 +1,170 @@
+use std::{collections::HashMap, path::Path};
+
+use ecs::{channel::Channel, system::System};
+use ecs_macro::EntityComponent;
+use glium::{backend::Facade, texture::RawImage2d, Texture2d};
+use image::ImageError;
+
+use crate::{error::RenderError, mesh::TextureType};
+
+/// The maximum amount of decoded images which can be queued before the background threads have to wait for the
+/// main thread to pick them up.
+const DECODED_QUEUE_SIZE: usize = 64;
+
+type DecodeResult = (usize, Result<RawImage2d<'static, u8>, ImageError>);
+
+/// A handle to a texture which has been queued through [AssetLoader::load_texture_async].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextureHandle(usize);
+
+/// The state of an asset which was queued through the [AssetLoader].
+pub enum AssetState {
+    /// The asset is still being decoded on a background thread.
+    Pending,
+    /// The asset has been decoded, but has not been uploaded to the GPU yet.
+    Decoded(RawImage2d<'static, u8>),
+    /// The asset has been uploaded to the GPU, and is ready to be used.
+    Loaded(TextureType),
+    /// The asset could not be decoded or uploaded.
+    Failed(RenderError),
+}
+
+/// `AssetLoader` decodes image files on a background thread pool, so large textures don't block the render thread.
+///
+/// Decoding happens on the rayon thread pool, after which the decoded [RawImage2d] data is sent back through a
+/// [Channel]. Uploading the data to the GPU has to happen on the thread owning the GL context, which is done through
+/// [AssetLoader::poll], or automatically by registering the [AssetLoaderSystem].
+#[derive(EntityComponent)]
+pub struct AssetLoader {
+    channel: Channel<DecodeResult>,
+    states: HashMap<usize, AssetState>,
+    next_id: usize,
+}
+
+impl AssetLoader {
+    pub fn new() -> Self {
+        Self {
+            channel: Channel::new(DECODED_QUEUE_SIZE),
+            states: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Queues the image at `path` to be decoded on a background thread.
+    ///
+    /// # Returns
+    ///
+    /// A [TextureHandle] which resolves to a [TextureType] once the texture has been uploaded, see
+    /// [AssetLoader::texture] and [AssetLoader::take_texture].
+    pub fn load_texture_async(&mut self, path: impl AsRef<Path>) -> TextureHandle {
+        let id = self.next_id;
+        let path = path.as_ref().to_path_buf();
+        let sender = self.channel.sender();
+
+        self.next_id += 1;
+        self.states.insert(id, AssetState::Pending);
+
+        rayon::spawn(move || {
+            let decoded = image::open(path).map(|image| {
+                let image = image.to_rgba8();
+                let dimensions = image.dimensions();
+
+                RawImage2d::from_raw_rgba_reversed(&image.into_raw(), dimensions)
+            });
+
+            // the loader might've been dropped in the meantime, in which case nobody is interested in the result.
+            let _ = sender.send((id, decoded));
+        });
+
+        TextureHandle(id)
+    }
+
+    /// Collects all images which have finished decoding since the last call, without uploading them.
+    pub fn receive(&mut self) {
+        while let Some((id, decoded)) = self.channel.try_recv() {
+            let state = match decoded {
+                Ok(image) => AssetState::Decoded(image),
+                Err(error) => AssetState::Failed(error.into()),
+            };
+
+            self.states.insert(id, state);
+        }
+    }
+
+    /// Collects all decoded images, and uploads them to the GPU.
+    ///
+    /// This must be called from the thread which owns the GL context of the `display`.
+    pub fn poll(&mut self, display: &impl Facade) {
+        self.receive();
+
+        for state in self.states.values_mut() {
+            if let AssetState::Decoded(_) = state {
+                if let AssetState::Decoded(image) = std::mem::replace(state, AssetState::Pending) {
+                    *state = match Texture2d::new(display, image) {
+                        Ok(texture) => AssetState::Loaded(TextureType::Texture2d(texture)),
+                        Err(error) => AssetState::Failed(error.into()),
+                    };
+                }
+            }
+        }
+    }
+
+    pub fn state(&self, handle: TextureHandle) -> Option<&AssetState> {
+        self.states.get(&handle.0)
+    }
+
+    pub fn is_loaded(&self, handle: TextureHandle) -> bool {
+        matches!(self.state(handle), Some(AssetState::Loaded(_)))
+    }
+
+    /// Returns a reference to the texture, if it has been uploaded.
+    pub fn texture(&self, handle: TextureHandle) -> Option<&TextureType> {
+        match self.state(handle)? {
+            AssetState::Loaded(texture) => Some(texture),
+            _ => None,
+        }
+    }
+
+    /// Removes the texture from the loader and returns it, if it has been uploaded.
+    ///
+    /// This is useful for passing the texture to a `MeshUniform`, which takes ownership of its textures.
+    pub fn take_texture(&mut self, handle: TextureHandle) -> Option<TextureType> {
+        if !self.is_loaded(handle) {
+            return None;
+        }
+
+        match self.states.remove(&handle.0)? {
+            AssetState::Loaded(texture) => Some(texture),
+            _ => None,
+        }
+    }
+}
+
+impl Default for AssetLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Uploads the textures decoded by every [AssetLoader] component.
+pub struct AssetLoaderSystem;
+
+impl<F: Facade> System<F> for AssetLoaderSystem {
+    fn update(
+        &mut self,
+        manager: &mut ecs::entity::EntityManager,
+        table: &mut ecs::entity::EntityQueryTable,
+        display: &F,
+    ) -> Option<()> {
+        for entity in table.query_single::<AssetLoader>(manager)?.clone() {
+            let loader = manager.query_entity::<AssetLoader>(entity).0?;
+            loader.poll(display);
+        }
+
+        None
+    }
+
+    fn runs_while_paused(&self) -> bool {
+        true
+    }
+}
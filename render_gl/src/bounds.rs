@@ -0,0 +1,211 @@
+use ecs::{
+    entity::{EntityManager, EntityQueryTable},
+    system::System,
+};
+use ecs_macro::EntityComponent;
+
+use crate::{
+    camera::Camera,
+    container::{multiply, Matrix4, Vec3, Vec4},
+    draw::instanced::Instanced,
+    mesh::Mesh,
+    uniform::perspective::Perspective,
+};
+
+/// A sphere enclosing an entity's `Mesh`, in the local space of the mesh.
+#[derive(EntityComponent, Debug, Clone, Copy)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(center: impl Into<Vec3>, radius: f32) -> Self {
+        Self {
+            center: center.into(),
+            radius,
+        }
+    }
+}
+
+impl BoundingSphere {
+    /// Returns the sphere enclosing this one after transforming it by a model matrix.
+    ///
+    /// The radius is scaled by the largest scale of the matrix along any axis, so the sphere stays conservative under
+    /// non-uniform scaling.
+    pub fn transformed(&self, matrix: &Matrix4) -> BoundingSphere {
+        let center = matrix.transform_vec4(Vec4::new(
+            self.center[0],
+            self.center[1],
+            self.center[2],
+            1.0,
+        ));
+        let scale = (0..3)
+            .map(|axis| Vec3::new(matrix[axis][0], matrix[axis][1], matrix[axis][2]).length())
+            .fold(0.0, f32::max);
+
+        BoundingSphere::new([center[0], center[1], center[2]], self.radius * scale)
+    }
+
+    /// Returns a sphere around the position of an instance which encloses this sphere, however the instance is
+    /// rotated.
+    pub fn around_instance(&self, instance: &Instanced) -> BoundingSphere {
+        let scale = instance
+            .scale
+            .iter()
+            .map(|scale| scale.abs())
+            .fold(0.0, f32::max);
+
+        BoundingSphere::new(
+            instance.world_position,
+            (self.center.length() + self.radius) * scale,
+        )
+    }
+}
+
+/// The axis-aligned box around an entity's `Mesh`, in the local space of the mesh.
+#[derive(EntityComponent, Debug, Clone, Copy)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: impl Into<Vec3>, max: impl Into<Vec3>) -> Self {
+        Self {
+            min: min.into(),
+            max: max.into(),
+        }
+    }
+
+    /// Returns the smallest box containing all `points`, or a box with zero volume at the origin if there are none.
+    pub fn from_points(points: impl IntoIterator<Item = [f32; 3]>) -> Self {
+        let mut points = points.into_iter();
+
+        let Some(first) = points.next() else {
+            return Self::new([0.0; 3], [0.0; 3]);
+        };
+
+        let (min, max) = points.fold((first, first), |(mut min, mut max), point| {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(point[axis]);
+                max[axis] = max[axis].max(point[axis]);
+            }
+
+            (min, max)
+        });
+
+        Self::new(min, max)
+    }
+
+    /// Returns the bounds of a mesh, see [Mesh::local_aabb].
+    pub fn of_mesh(mesh: &Mesh) -> Self {
+        let (min, max) = mesh.local_aabb();
+        Self::new(min, max)
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Returns the width, height and depth of the box.
+    pub fn size(&self) -> Vec3 {
+        self.max - self.min
+    }
+}
+
+/// Attaches an [Aabb] to every entity with a `Mesh` which doesn't have one yet.
+///
+/// The bounds are computed once per mesh, so an [Aabb] has to be removed to be recomputed after the vertices of its
+/// mesh have changed.
+pub struct AabbSystem;
+
+impl<T> System<T> for AabbSystem {
+    fn update(
+        &mut self,
+        manager: &mut EntityManager,
+        table: &mut EntityQueryTable,
+        _: &T,
+    ) -> Option<()> {
+        let entities = table.query_single::<Mesh>(manager)?.clone();
+
+        for entity in entities {
+            if manager.has_component::<Aabb>(entity) {
+                continue;
+            }
+
+            let aabb = Aabb::of_mesh(manager.query_entity::<Mesh>(entity).0?);
+            manager.entity_with(entity, aabb);
+        }
+
+        None
+    }
+
+    fn runs_while_paused(&self) -> bool {
+        true
+    }
+}
+
+/// Marks an entity which failed the frustum cull during the current frame.
+#[derive(EntityComponent, Debug, Clone, Copy)]
+pub struct Culled;
+
+/// The volume visible through a camera, bounded by six planes.
+///
+/// Every plane is stored as `(a, b, c, d)` with a normalized normal `(a, b, c)` pointing into the frustum, so a point
+/// `p` is inside if `a * p.x + b * p.y + c * p.z + d >= 0` holds for all planes.
+#[derive(Debug, Clone, Copy)]
+pub struct Frustum {
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Extracts the planes from a matrix transforming world space into clip space.
+    ///
+    /// The planes are the sums and differences of the last row of the matrix and each of its other rows, in the order
+    /// left, right, bottom, top, near, far.
+    pub fn from_matrix(view_projection: Matrix4) -> Self {
+        let row = |index: usize| {
+            Vec4::new(
+                view_projection[0][index],
+                view_projection[1][index],
+                view_projection[2][index],
+                view_projection[3][index],
+            )
+        };
+
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+
+        let planes = [w + x, w - x, w + y, w - y, w + z, w - z].map(|plane| {
+            let length = Vec3::new(plane[0], plane[1], plane[2]).length();
+
+            if length == 0.0 {
+                plane
+            } else {
+                plane * (1.0 / length)
+            }
+        });
+
+        Self { planes }
+    }
+
+    /// Builds the frustum of a camera seen through a perspective.
+    pub fn new(perspective: &Perspective, camera: &Camera) -> Self {
+        // `multiply` treats the inner arrays as rows, so the operands are swapped to get `projection * view`.
+        Self::from_matrix(multiply(camera.view_matrix(), perspective.matrix()))
+    }
+
+    /// Returns the planes in the order left, right, bottom, top, near, far.
+    pub fn planes(&self) -> &[Vec4; 6] {
+        &self.planes
+    }
+
+    /// Whether any part of the sphere around `center` is inside the frustum.
+    pub fn contains_sphere(&self, center: impl Into<Vec3>, radius: f32) -> bool {
+        let center = center.into();
+
+        self.planes.iter().all(|plane| {
+            plane[0] * center[0] + plane[1] * center[1] + plane[2] * center[2] + plane[3] >= -radius
+        })
+    }
+}
@@ -1,5 +1,15 @@
-use glium::{index::PrimitiveType, Display, IndexBuffer};
+use std::collections::HashMap;
 
+use ecs::component::Component;
+use glium::{
+    backend::Facade,
+    index::{self, PrimitiveType},
+    vertex::{BufferCreationError, VertexBufferSlice},
+    IndexBuffer, Vertex, VertexBuffer,
+};
+
+/// Creates index buffers and keeps them alive, so meshes can borrow the most recently created buffer of each index
+/// type.
 pub struct IndexBufferCreator {
     index_buffers_u32: Vec<IndexBuffer<u32>>,
     index_buffers_u16: Vec<IndexBuffer<u16>>,
@@ -15,57 +25,199 @@ impl IndexBufferCreator {
         }
     }
 
+    /// Creates an index buffer, which becomes the one returned by [IndexBufferCreator::get_index_buffer_u32].
+    ///
+    /// Returns an error if the primitive type isn't supported or the buffer couldn't be created, in which case the
+    /// previously created buffer stays the latest one.
     pub fn create_index_buffer_u32(
         &mut self,
-        display: &Display,
+        display: &impl Facade,
         vertices: &[u32],
         primitive_type: PrimitiveType,
-    ) -> &mut Self {
-        {
-            let index_buffer = IndexBuffer::new(display, primitive_type, vertices).unwrap();
-            self.index_buffers_u32.push(index_buffer);
-        }
+    ) -> Result<&mut Self, index::BufferCreationError> {
+        let index_buffer = IndexBuffer::new(display, primitive_type, vertices)?;
+        self.index_buffers_u32.push(index_buffer);
+
+        Ok(self)
+    }
 
-        self
+    /// Returns the latest `u32` index buffer, or `None` if none has been created yet.
+    pub fn get_index_buffer_u32(&self) -> Option<&IndexBuffer<u32>> {
+        self.index_buffers_u32.last()
     }
 
-    pub fn get_index_buffer_u32<'a>(&self) -> &IndexBuffer<u32> {
-        self.index_buffers_u32.last().unwrap()
+    /// Returns the latest `u32` index buffer, panicking if none has been created yet. Meant for demos, where a
+    /// missing buffer is a bug in the setup.
+    pub fn expect_index_buffer_u32(&self) -> &IndexBuffer<u32> {
+        self.get_index_buffer_u32()
+            .expect("No u32 index buffer has been created")
     }
 
+    /// Creates an index buffer, see [IndexBufferCreator::create_index_buffer_u32].
     pub fn create_index_buffer_u16(
         &mut self,
-        display: &Display,
+        display: &impl Facade,
         vertices: &[u16],
         primitive_type: PrimitiveType,
-    ) -> &mut Self {
-        {
-            let index_buffer = IndexBuffer::new(display, primitive_type, vertices).unwrap();
-            self.index_buffers_u16.push(index_buffer);
-        }
+    ) -> Result<&mut Self, index::BufferCreationError> {
+        let index_buffer = IndexBuffer::new(display, primitive_type, vertices)?;
+        self.index_buffers_u16.push(index_buffer);
+
+        Ok(self)
+    }
 
-        self
+    /// Returns the latest `u16` index buffer, or `None` if none has been created yet.
+    pub fn get_index_buffer_u16(&self) -> Option<&IndexBuffer<u16>> {
+        self.index_buffers_u16.last()
     }
 
-    pub fn get_index_buffer_u16<'a>(&self) -> &IndexBuffer<u16> {
-        self.index_buffers_u16.last().unwrap()
+    /// Returns the latest `u16` index buffer, see [IndexBufferCreator::expect_index_buffer_u32].
+    pub fn expect_index_buffer_u16(&self) -> &IndexBuffer<u16> {
+        self.get_index_buffer_u16()
+            .expect("No u16 index buffer has been created")
     }
 
+    /// Creates an index buffer, see [IndexBufferCreator::create_index_buffer_u32].
     pub fn create_index_buffer_u8(
         &mut self,
-        display: &Display,
+        display: &impl Facade,
         vertices: &[u8],
         primitive_type: PrimitiveType,
-    ) -> &mut Self {
-        {
-            let index_buffer = IndexBuffer::new(display, primitive_type, vertices).unwrap();
-            self.index_buffers_u8.push(index_buffer);
+    ) -> Result<&mut Self, index::BufferCreationError> {
+        let index_buffer = IndexBuffer::new(display, primitive_type, vertices)?;
+        self.index_buffers_u8.push(index_buffer);
+
+        Ok(self)
+    }
+
+    /// Returns the latest `u8` index buffer, or `None` if none has been created yet.
+    pub fn get_index_buffer_u8(&self) -> Option<&IndexBuffer<u8>> {
+        self.index_buffers_u8.last()
+    }
+
+    /// Returns the latest `u8` index buffer, see [IndexBufferCreator::expect_index_buffer_u32].
+    pub fn expect_index_buffer_u8(&self) -> &IndexBuffer<u8> {
+        self.get_index_buffer_u8()
+            .expect("No u8 index buffer has been created")
+    }
+}
+
+/// The smallest size class handed out by the [TransientBufferPool], in vertices.
+const MIN_SIZE_CLASS: usize = 64;
+
+/// A handle to a buffer which has been handed out by a [TransientBufferPool] for the current frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransientHandle {
+    index: usize,
+    len: usize,
+}
+
+/// `TransientBufferPool` hands out buffers for short-lived, per-frame data (debug draws, sprites, particles), and
+/// recycles them once the frame has ended.
+///
+/// Buffers are grouped in size classes (powers of two), so a request for any amount of vertices can be served by a
+/// previously allocated buffer of the same class. Once the pool has warmed up, no more buffers are allocated on the GPU.
+///
+/// # Type Parameters
+///
+/// - `B`: The type of the pooled buffers, usually a `VertexBuffer`.
+pub struct TransientBufferPool<B> {
+    free: HashMap<usize, Vec<B>>,
+    in_use: Vec<(usize, B)>,
+    allocations: usize,
+}
+
+impl<B> Component for TransientBufferPool<B> where B: 'static {}
+
+impl<B> TransientBufferPool<B> {
+    pub fn new() -> Self {
+        Self {
+            free: HashMap::new(),
+            in_use: vec![],
+            allocations: 0,
+        }
+    }
+
+    /// Returns the size class, the capacity of the buffer, used for `len` vertices.
+    pub fn size_class(len: usize) -> usize {
+        len.next_power_of_two().max(MIN_SIZE_CLASS)
+    }
+
+    /// Acquires a buffer which can hold at least `len` vertices for the current frame.
+    ///
+    /// A free buffer of the matching size class is reused if there is one, otherwise `allocate` is called with the
+    /// capacity of the size class to create a new buffer.
+    pub fn acquire<E>(
+        &mut self,
+        len: usize,
+        allocate: impl FnOnce(usize) -> Result<B, E>,
+    ) -> Result<TransientHandle, E> {
+        let size_class = Self::size_class(len);
+        let buffer = match self.free.get_mut(&size_class).and_then(|free| free.pop()) {
+            Some(buffer) => buffer,
+            None => {
+                self.allocations += 1;
+                allocate(size_class)?
+            }
+        };
+
+        self.in_use.push((size_class, buffer));
+
+        Ok(TransientHandle {
+            index: self.in_use.len() - 1,
+            len,
+        })
+    }
+
+    /// Returns the buffer belonging to the `handle`, as long as the frame it was acquired in hasn't ended yet.
+    pub fn buffer(&self, handle: TransientHandle) -> Option<&B> {
+        self.in_use.get(handle.index).map(|entry| &entry.1)
+    }
+
+    /// Recycles all buffers handed out during this frame. Any handles acquired before this call are invalidated.
+    pub fn end_frame(&mut self) {
+        for (size_class, buffer) in self.in_use.drain(..) {
+            self.free.entry(size_class).or_default().push(buffer);
+        }
+    }
+
+    /// Returns the total amount of buffers this pool has allocated.
+    pub fn allocations(&self) -> usize {
+        self.allocations
+    }
+}
+
+impl<B> Default for TransientBufferPool<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> TransientBufferPool<VertexBuffer<V>>
+where
+    V: Vertex + Send + 'static,
+{
+    /// Acquires a buffer for the current frame, and fills it with `data`.
+    ///
+    /// This is meant to be used instead of calling `VertexBuffer::dynamic` for every frame.
+    pub fn request(
+        &mut self,
+        display: &impl Facade,
+        data: &[V],
+    ) -> Result<TransientHandle, BufferCreationError> {
+        let handle = self.acquire(data.len(), |capacity| {
+            VertexBuffer::empty_dynamic(display, capacity)
+        })?;
+
+        if let Some(slice) = self.slice(handle) {
+            slice.write(data);
         }
 
-        self
+        Ok(handle)
     }
 
-    pub fn get_index_buffer_u8<'a>(&self) -> &IndexBuffer<u8> {
-        self.index_buffers_u8.last().unwrap()
+    /// Returns the part of the buffer belonging to the `handle` which was filled with data.
+    pub fn slice(&self, handle: TransientHandle) -> Option<VertexBufferSlice<'_, V>> {
+        self.buffer(handle)?.slice(0..handle.len)
     }
 }
@@ -1,12 +1,45 @@
+use ecs::{
+    entity::{EntityManager, EntityQueryTable},
+    system::{PostLoopSystem, System},
+};
 use ecs_macro::EntityComponent;
+use glium::glutin::event::{MouseButton, VirtualKeyCode};
 
-use crate::container::{Matrix4, Vec3};
+use crate::{
+    container::{multiply, Matrix4, Vec3},
+    draw::delta::TimeDelta,
+    input::Input,
+    picking::unproject_ray,
+    uniform::perspective::Perspective,
+};
+
+/// The largest angle, in degrees, the camera can look up or down, which keeps the direction from becoming parallel to
+/// the up vector.
+pub const MAX_PITCH: f32 = 89.0;
 
 #[derive(EntityComponent, Debug, Clone)]
 pub struct Camera {
     position: Vec3,
     direction: Vec3,
     up: Vec3,
+    /// The rotation around the up axis in degrees, `0` looking along `+z` and increasing towards `+x`.
+    yaw: f32,
+    /// The angle above the horizon in degrees, clamped to [MAX_PITCH].
+    pitch: f32,
+    /// The input queued for [MouseCamera], see [Camera::input_event].
+    input_events: Vec<CameraEvent>,
+}
+
+/// Input which is queued on a `Camera` by the event loop and handled by [MouseCamera].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CameraEvent {
+    /// The mouse moved by the given amount of pixels, `dy` growing downwards.
+    MouseMovement {
+        dx: f32,
+        dy: f32,
+    },
+    KeyPressed(VirtualKeyCode),
+    MouseButtonPressed(MouseButton),
 }
 
 impl From<[[f32; 3]; 3]> for Camera {
@@ -17,10 +50,16 @@ impl From<[[f32; 3]; 3]> for Camera {
 
 impl Camera {
     pub fn new(position: impl Into<Vec3>, direction: impl Into<Vec3>, up: impl Into<Vec3>) -> Self {
+        let direction = direction.into();
+        let (yaw, pitch) = yaw_pitch(direction);
+
         Self {
             position: position.into(),
-            direction: direction.into(),
+            direction,
             up: up.into(),
+            yaw,
+            pitch,
+            input_events: Vec::new(),
         }
     }
 
@@ -30,6 +69,7 @@ impl Camera {
 
     pub fn direction(&mut self, direction: impl Into<Vec3>) {
         self.direction = direction.into();
+        (self.yaw, self.pitch) = yaw_pitch(self.direction);
     }
 
     pub fn up(&mut self, up: impl Into<Vec3>) {
@@ -54,60 +94,408 @@ impl Camera {
     }
 
     pub fn add_direction(&mut self, direction: impl Into<Vec3>) -> &mut Self {
-        self.direction = self.direction + direction.into();
+        self.direction(self.direction + direction.into());
         self
     }
 
+    /// Turns the camera by `yaw` degrees to the right and `pitch` degrees up.
+    ///
+    /// The direction is rebuilt from the accumulated angles rather than rotated incrementally, so the camera never
+    /// rolls. The pitch is clamped to [MAX_PITCH] in both directions, so the view can't flip over when looking
+    /// straight up or down.
+    pub fn change_direction(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = (self.yaw + yaw) % 360.0;
+        self.pitch = (self.pitch + pitch).clamp(-MAX_PITCH, MAX_PITCH);
+
+        let (yaw, pitch) = (self.yaw.to_radians(), self.pitch.to_radians());
+
+        self.direction = Vec3::new(
+            pitch.cos() * yaw.sin(),
+            pitch.sin(),
+            pitch.cos() * yaw.cos(),
+        );
+    }
+
+    /// Queues an input event, which is handled by [MouseCamera] during the next update.
+    pub fn input_event(&mut self, event: CameraEvent) {
+        self.input_events.push(event);
+    }
+
+    /// Returns the rotation around the up axis in degrees, see [Camera::change_direction].
+    pub fn yaw(&self) -> f32 {
+        self.yaw
+    }
+
+    /// Returns the angle above the horizon in degrees, see [Camera::change_direction].
+    pub fn pitch(&self) -> f32 {
+        self.pitch
+    }
+
     pub fn add_up(&mut self, up: impl Into<Vec3>) -> &mut Self {
         self.up = self.up + up.into();
         self
     }
 
-    pub fn view_matrix(&self) -> Matrix4 {
-        let f = {
-            let len = (self.direction[0] * self.direction[0]
-                + self.direction[1] * self.direction[1]
-                + self.direction[2] * self.direction[2])
-                .sqrt();
-
-            [
-                self.direction[0] / len,
-                self.direction[1] / len,
-                self.direction[2] / len,
-            ]
-        };
+    /// Returns the normalized view direction.
+    pub fn forward(&self) -> Vec3 {
+        self.direction.normalize()
+    }
 
-        let up = self.up;
-        let s = [
-            up[1] * f[2] - up[2] * f[1],
-            up[2] * f[0] - up[0] * f[2],
-            up[0] * f[1] - up[1] * f[0],
-        ];
-
-        let s_norm = {
-            let len = s[0] * s[0] + s[1] * s[1] + s[2] * s[2];
-            let len = len.sqrt();
-            [s[0] / len, s[1] / len, s[2] / len]
-        };
+    /// Returns the normalized axis to the right of the view direction, perpendicular to both the direction and the up
+    /// vector.
+    pub fn right(&self) -> Vec3 {
+        self.up.cross(self.forward()).normalize()
+    }
 
-        let u = [
-            f[1] * s_norm[2] - f[2] * s_norm[1],
-            f[2] * s_norm[0] - f[0] * s_norm[2],
-            f[0] * s_norm[1] - f[1] * s_norm[0],
-        ];
+    /// Returns the normalized up axis of the view, which unlike the up vector the camera was created with is
+    /// perpendicular to the view direction.
+    pub fn world_up(&self) -> Vec3 {
+        self.forward().cross(self.right())
+    }
 
+    /// Returns the transform from the camera's local space to world space, the columns being the camera's right, up
+    /// and forward axes and its position.
+    pub fn world_matrix(&self) -> Matrix4 {
+        let (s, u, f) = (self.right(), self.world_up(), self.forward());
         let position = self.position;
-        let p = [
-            -position[0] * s_norm[0] - position[1] * s_norm[1] - position[2] * s_norm[2],
-            -position[0] * u[0] - position[1] * u[1] - position[2] * u[2],
-            -position[0] * f[0] - position[1] * f[1] - position[2] * f[2],
-        ];
 
         Matrix4::from([
-            [s_norm[0], u[0], f[0], 0.0],
-            [s_norm[1], u[1], f[1], 0.0],
-            [s_norm[2], u[2], f[2], 0.0],
-            [p[0], p[1], p[2], 1.0],
+            [s[0], s[1], s[2], 0.0],
+            [u[0], u[1], u[2], 0.0],
+            [f[0], f[1], f[2], 0.0],
+            [position[0], position[1], position[2], 1.0],
         ])
     }
+
+    /// Returns the transform from world space to the camera's view space, the inverse of [Camera::world_matrix].
+    ///
+    /// If the direction is zero or parallel to the up vector, the camera has no valid orientation and the identity
+    /// matrix is returned, see [Matrix4::look_at].
+    pub fn view_matrix(&self) -> Matrix4 {
+        Matrix4::look_at(self.position, self.position + self.direction, self.up)
+    }
+
+    /// Returns the world space ray through a point on the screen in normalized device coordinates, as its origin on
+    /// the near plane and its normalized direction, see [unproject_ray].
+    ///
+    /// If the view or the projection matrix can't be inverted, the ray starts at the camera's position and points
+    /// along its view direction.
+    pub fn screen_ray(&self, ndc_x: f32, ndc_y: f32, projection: &Matrix4) -> (Vec3, Vec3) {
+        unproject_ray(&self.view_matrix(), projection, ndc_x, ndc_y)
+            .unwrap_or((self.position, self.forward()))
+    }
+}
+
+/// Computes the yaw and pitch of a direction in degrees, see [Camera::change_direction].
+fn yaw_pitch(direction: Vec3) -> (f32, f32) {
+    let length = direction.length();
+
+    if length == 0.0 {
+        return (0.0, 0.0);
+    }
+
+    let yaw = direction[0].atan2(direction[2]).to_degrees();
+    let pitch = (direction[1] / length).asin().to_degrees();
+
+    (yaw, pitch.clamp(-MAX_PITCH, MAX_PITCH))
+}
+
+/// Refers to the `Camera` entity which is used for rendering.
+///
+/// Only the first `ActiveCamera` component is taken into account. If there is none, the first `Camera` is used.
+#[derive(EntityComponent, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActiveCamera(pub usize);
+
+/// Returns the entity of the camera which should be used for rendering, see [ActiveCamera].
+pub fn active_camera(manager: &mut EntityManager, table: &mut EntityQueryTable) -> Option<usize> {
+    let active = table
+        .query_single::<ActiveCamera>(manager)
+        .and_then(|entities| entities.first().copied())
+        .and_then(|entity| manager.query_entity::<ActiveCamera>(entity).0.copied());
+
+    match active {
+        Some(ActiveCamera(entity)) => Some(entity),
+        None => table.query_single::<Camera>(manager)?.first().copied(),
+    }
+}
+
+/// Returns the view matrix and the position of the camera the scene is rendered from.
+///
+/// A `Camera` resource takes precedence over `Camera` entities, see [active_camera] for how the entity is picked.
+pub fn camera_view(
+    manager: &mut EntityManager,
+    table: &mut EntityQueryTable,
+) -> Option<(Matrix4, Vec3)> {
+    if let Some(camera) = manager.resource::<Camera>() {
+        return Some((camera.view_matrix(), *camera.ref_position()));
+    }
+
+    let entity = active_camera(manager, table)?;
+    let camera = manager.query_entity::<Camera>(entity).0?;
+
+    Some((camera.view_matrix(), *camera.ref_position()))
+}
+
+/// Returns the perspective the scene is rendered with, the `Perspective` resource or else the first `Perspective`
+/// component.
+pub fn active_perspective(
+    manager: &mut EntityManager,
+    table: &mut EntityQueryTable,
+) -> Option<Perspective> {
+    match manager.resource::<Perspective>() {
+        Some(perspective) => Some(*perspective),
+        None => table
+            .query_first_single::<Perspective>(manager)
+            .copied()
+            .and_then(|entity| manager.query_entity::<Perspective>(entity).0.copied()),
+    }
+}
+
+/// The matrices of the camera the scene is rendered from, stored as resource by [CameraMatricesSystem].
+#[derive(Debug, Clone, Copy)]
+pub struct CameraMatrices {
+    pub view: Matrix4,
+    pub projection: Matrix4,
+    /// The projection matrix multiplied by the view matrix, transforming world space into clip space.
+    pub view_projection: Matrix4,
+    pub position: Vec3,
+}
+
+impl CameraMatrices {
+    pub fn new(view: Matrix4, projection: Matrix4, position: Vec3) -> Self {
+        Self {
+            view,
+            projection,
+            // `multiply` treats the inner arrays as rows, while they're columns to the shaders, so the operands are
+            // swapped to get `projection * view`.
+            view_projection: multiply(view, projection),
+            position,
+        }
+    }
+}
+
+/// Computes the [CameraMatrices] resource of the camera the scene is rendered from, see [camera_view].
+///
+/// The projection is taken from a `Perspective` resource, or the first `Perspective` component if there is no such
+/// resource. Without any perspective, the identity matrix is used. This system should be registered before any system
+/// reading the resource, so the matrices are up to date for the current frame.
+pub struct CameraMatricesSystem;
+
+impl<T> System<T> for CameraMatricesSystem {
+    fn update(
+        &mut self,
+        manager: &mut EntityManager,
+        table: &mut EntityQueryTable,
+        _: &T,
+    ) -> Option<()> {
+        let (view, position) = camera_view(manager, table)?;
+
+        let projection = match active_perspective(manager, table) {
+            Some(perspective) => perspective.matrix(),
+            None => Matrix4::identity(),
+        };
+
+        manager.insert_resource(CameraMatrices::new(view, projection, position));
+        None
+    }
+
+    fn runs_while_paused(&self) -> bool {
+        true
+    }
+}
+
+/// Cycles the [ActiveCamera] through all `Camera` entities whenever `key` is pressed, wrapping around after the last
+/// camera. Does nothing if there's only a single camera.
+///
+/// Keys are read from the `Input` resource, or else the first `Input` component.
+pub struct CameraCycleSystem {
+    pub key: VirtualKeyCode,
+}
+
+impl CameraCycleSystem {
+    pub fn new(key: VirtualKeyCode) -> Self {
+        Self { key }
+    }
+}
+
+impl<T> System<T> for CameraCycleSystem {
+    fn update(
+        &mut self,
+        manager: &mut EntityManager,
+        table: &mut EntityQueryTable,
+        _: &T,
+    ) -> Option<()> {
+        let pressed = match manager.resource::<Input>() {
+            Some(input) => input.just_pressed(self.key),
+            None => {
+                let entity = *table.query_first_single::<Input>(manager)?;
+                manager
+                    .query_entity::<Input>(entity)
+                    .0?
+                    .just_pressed(self.key)
+            }
+        };
+
+        if !pressed {
+            return None;
+        }
+
+        let mut cameras = table.query_single::<Camera>(manager)?.clone();
+
+        if cameras.len() <= 1 {
+            return None;
+        }
+
+        cameras.sort();
+
+        let holder = *table.query_single::<ActiveCamera>(manager)?.first()?;
+        let active = manager.query_entity::<ActiveCamera>(holder).0?;
+
+        let index = cameras.iter().position(|camera| *camera == active.0);
+        let next = match index {
+            Some(index) => cameras[(index + 1) % cameras.len()],
+            None => cameras[0],
+        };
+
+        active.0 = next;
+        None
+    }
+
+    fn runs_while_paused(&self) -> bool {
+        true
+    }
+}
+
+/// Moves the camera like a free flying spectator: W/S move along the view direction, A/D strafe and Space/Shift move
+/// along the up vector.
+///
+/// The camera moved is the `Camera` resource if there is one, else the camera picked by [active_camera]. Keys are read
+/// from the `Input` resource, or else the first `Input` component, and the movement is scaled by the first
+/// `TimeDelta`, see [fly_offset].
+pub struct FlyCameraSystem {
+    /// The distance moved per second.
+    pub speed: f32,
+}
+
+impl FlyCameraSystem {
+    pub fn new(speed: f32) -> Self {
+        Self { speed }
+    }
+}
+
+impl<T> System<T> for FlyCameraSystem {
+    fn update(
+        &mut self,
+        manager: &mut EntityManager,
+        table: &mut EntityQueryTable,
+        _: &T,
+    ) -> Option<()> {
+        let delta = *table.query_first_single::<TimeDelta>(manager)?;
+        let delta = manager
+            .query_entity::<TimeDelta>(delta)
+            .0?
+            .get_time_delta_sec();
+
+        let input = match manager.resource::<Input>() {
+            Some(input) => input.clone(),
+            None => {
+                let entity = *table.query_first_single::<Input>(manager)?;
+                manager.query_entity::<Input>(entity).0?.clone()
+            }
+        };
+
+        let camera = match manager.resource_mut::<Camera>() {
+            Some(camera) => camera,
+            None => {
+                let entity = active_camera(manager, table)?;
+                manager.query_entity::<Camera>(entity).0?
+            }
+        };
+
+        let offset = fly_offset(camera, &input, self.speed * delta);
+        camera.add_position(offset);
+        None
+    }
+}
+
+/// Computes how far the keys held in `input` move the `camera`, covering `distance` in total.
+///
+/// The pressed directions are combined and normalized, so moving diagonally isn't faster than moving straight.
+/// Opposing keys cancel each other out.
+pub fn fly_offset(camera: &Camera, input: &Input, distance: f32) -> Vec3 {
+    let forward = camera.forward();
+    let up = camera.ref_up().normalize();
+    let right = camera.right();
+
+    let axis = |positive: VirtualKeyCode, negative: VirtualKeyCode| {
+        input.is_pressed(positive) as i32 as f32 - input.is_pressed(negative) as i32 as f32
+    };
+
+    let direction = forward * axis(VirtualKeyCode::W, VirtualKeyCode::S)
+        + right * axis(VirtualKeyCode::D, VirtualKeyCode::A)
+        + up * axis(VirtualKeyCode::Space, VirtualKeyCode::LShift);
+
+    direction.normalize() * distance
+}
+
+/// Turns every `Camera`, and the `Camera` resource, by the mouse movements queued through [Camera::input_event].
+///
+/// Mouse look starts enabled. Pressing `release_key` disables it, e.g. so the cursor can leave the window, and
+/// pressing `grab_button` enables it again. Movements while it's disabled are dropped.
+pub struct MouseCamera {
+    /// The degrees the camera turns per pixel of mouse movement.
+    pub sensitivity: f32,
+    pub grab_button: MouseButton,
+    pub release_key: VirtualKeyCode,
+    looking: bool,
+}
+
+impl MouseCamera {
+    pub fn new(sensitivity: f32) -> Self {
+        Self {
+            sensitivity,
+            grab_button: MouseButton::Left,
+            release_key: VirtualKeyCode::Escape,
+            looking: true,
+        }
+    }
+
+    /// Returns whether mouse movements currently turn the camera.
+    pub fn is_looking(&self) -> bool {
+        self.looking
+    }
+
+    fn handle(&mut self, camera: &mut Camera) {
+        for event in std::mem::take(&mut camera.input_events) {
+            match event {
+                CameraEvent::MouseMovement { dx, dy } if self.looking => {
+                    // moving the mouse up reports a negative `dy`, which should look up.
+                    camera.change_direction(dx * self.sensitivity, -dy * self.sensitivity);
+                }
+                CameraEvent::MouseMovement { .. } => {}
+                CameraEvent::KeyPressed(key) if key == self.release_key => self.looking = false,
+                CameraEvent::KeyPressed(_) => {}
+                CameraEvent::MouseButtonPressed(button) if button == self.grab_button => {
+                    self.looking = true
+                }
+                CameraEvent::MouseButtonPressed(_) => {}
+            }
+        }
+    }
+}
+
+impl PostLoopSystem for MouseCamera {
+    fn update(&mut self, manager: &mut EntityManager, table: &mut EntityQueryTable) -> Option<()> {
+        if let Some(camera) = manager.resource_mut::<Camera>() {
+            self.handle(camera);
+        }
+
+        for entity in table.query_single::<Camera>(manager)?.clone() {
+            if let Some(camera) = manager.query_entity::<Camera>(entity).0 {
+                self.handle(camera);
+            }
+        }
+
+        None
+    }
 }
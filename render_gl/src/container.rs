@@ -1,4 +1,4 @@
-use std::ops::{Add, Index, IndexMut};
+use std::ops::{Add, Index, IndexMut, Mul, Sub};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Matrix4 {
@@ -9,6 +9,7 @@ pub struct Matrix4 {
 }
 
 impl Matrix4 {
+    /// Creates a matrix with all elements set to zero, use [Matrix4::identity] for a matrix which doesn't transform.
     pub fn new() -> Self {
         Self::from([
             [0.0, 0.0, 0.0, 0.0],
@@ -18,12 +19,46 @@ impl Matrix4 {
         ])
     }
 
+    /// Returns the matrix which doesn't transform at all.
+    pub fn identity() -> Self {
+        Self::from([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Returns the view matrix of an observer at `eye` looking towards `target`, the transform from world space to a
+    /// space with the observer at the origin, `up` along the y axis and the view direction along the z axis.
+    ///
+    /// If `eye` and `target` are the same point, or the view direction is parallel to `up`, the observer has no valid
+    /// orientation and the identity matrix is returned.
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        let f = (target - eye).normalize();
+        let s = up.cross(f).normalize();
+        let u = f.cross(s);
+
+        if f.length() == 0.0 || s.length() == 0.0 {
+            return Self::identity();
+        }
+
+        Self::from([
+            [s[0], u[0], f[0], 0.0],
+            [s[1], u[1], f[1], 0.0],
+            [s[2], u[2], f[2], 0.0],
+            [-s.dot(eye), -u.dot(eye), -f.dot(eye), 1.0],
+        ])
+    }
+
+    /// Translates the matrix, which is stored in its last column (`self[3]`), where the shaders and
+    /// [Matrix4::translation] read it from.
     pub fn translate(&mut self, x: f32, y: f32, z: f32) {
         let translate_matrix = Matrix4::from([
-            [1.0, 0.0, 0.0, x],
-            [0.0, 1.0, 0.0, y],
-            [0.0, 0.0, 1.0, z],
-            [0.0, 0.0, 0.0, 1.0],
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [x, y, z, 1.0],
         ]);
 
         let multiplied = multiply(translate_matrix, *self);
@@ -92,6 +127,125 @@ impl Matrix4 {
 
         [first.inner(), second.inner(), third.inner(), fourth.inner()]
     }
+
+    /// Returns the translation part of this matrix, stored in the last column (`self[3]`), see [Matrix4::translate].
+    pub fn translation(&self) -> Vec3 {
+        Vec3::new(self[3][0], self[3][1], self[3][2])
+    }
+
+    /// Transforms a vector by this matrix.
+    ///
+    /// The matrix is interpreted the same way it is handed to the shaders, meaning every inner array is a column.
+    pub fn transform_vec4(&self, vec: Vec4) -> Vec4 {
+        let mut result = Vec4::new(0.0, 0.0, 0.0, 0.0);
+
+        for row in 0..4 {
+            result[row] = self[0][row] * vec[0]
+                + self[1][row] * vec[1]
+                + self[2][row] * vec[2]
+                + self[3][row] * vec[3];
+        }
+
+        result
+    }
+
+    /// Returns the transpose of this matrix, swapping its rows and columns.
+    pub fn transpose(&self) -> Matrix4 {
+        let mut result = Matrix4::new();
+
+        for i in 0..4 {
+            for j in 0..4 {
+                result[i][j] = self[j][i];
+            }
+        }
+
+        result
+    }
+
+    /// Returns the inverse of this matrix, computed through cofactor expansion, or `None` if the matrix is singular.
+    pub fn inverse(&self) -> Option<Matrix4> {
+        let m = |i: usize| self[i / 4][i % 4];
+        let mut inv = [0.0f32; 16];
+
+        inv[0] = m(5) * m(10) * m(15) - m(5) * m(11) * m(14) - m(9) * m(6) * m(15)
+            + m(9) * m(7) * m(14)
+            + m(13) * m(6) * m(11)
+            - m(13) * m(7) * m(10);
+        inv[4] = -m(4) * m(10) * m(15) + m(4) * m(11) * m(14) + m(8) * m(6) * m(15)
+            - m(8) * m(7) * m(14)
+            - m(12) * m(6) * m(11)
+            + m(12) * m(7) * m(10);
+        inv[8] = m(4) * m(9) * m(15) - m(4) * m(11) * m(13) - m(8) * m(5) * m(15)
+            + m(8) * m(7) * m(13)
+            + m(12) * m(5) * m(11)
+            - m(12) * m(7) * m(9);
+        inv[12] = -m(4) * m(9) * m(14) + m(4) * m(10) * m(13) + m(8) * m(5) * m(14)
+            - m(8) * m(6) * m(13)
+            - m(12) * m(5) * m(10)
+            + m(12) * m(6) * m(9);
+        inv[1] = -m(1) * m(10) * m(15) + m(1) * m(11) * m(14) + m(9) * m(2) * m(15)
+            - m(9) * m(3) * m(14)
+            - m(13) * m(2) * m(11)
+            + m(13) * m(3) * m(10);
+        inv[5] = m(0) * m(10) * m(15) - m(0) * m(11) * m(14) - m(8) * m(2) * m(15)
+            + m(8) * m(3) * m(14)
+            + m(12) * m(2) * m(11)
+            - m(12) * m(3) * m(10);
+        inv[9] = -m(0) * m(9) * m(15) + m(0) * m(11) * m(13) + m(8) * m(1) * m(15)
+            - m(8) * m(3) * m(13)
+            - m(12) * m(1) * m(11)
+            + m(12) * m(3) * m(9);
+        inv[13] = m(0) * m(9) * m(14) - m(0) * m(10) * m(13) - m(8) * m(1) * m(14)
+            + m(8) * m(2) * m(13)
+            + m(12) * m(1) * m(10)
+            - m(12) * m(2) * m(9);
+        inv[2] = m(1) * m(6) * m(15) - m(1) * m(7) * m(14) - m(5) * m(2) * m(15)
+            + m(5) * m(3) * m(14)
+            + m(13) * m(2) * m(7)
+            - m(13) * m(3) * m(6);
+        inv[6] = -m(0) * m(6) * m(15) + m(0) * m(7) * m(14) + m(4) * m(2) * m(15)
+            - m(4) * m(3) * m(14)
+            - m(12) * m(2) * m(7)
+            + m(12) * m(3) * m(6);
+        inv[10] = m(0) * m(5) * m(15) - m(0) * m(7) * m(13) - m(4) * m(1) * m(15)
+            + m(4) * m(3) * m(13)
+            + m(12) * m(1) * m(7)
+            - m(12) * m(3) * m(5);
+        inv[14] = -m(0) * m(5) * m(14) + m(0) * m(6) * m(13) + m(4) * m(1) * m(14)
+            - m(4) * m(2) * m(13)
+            - m(12) * m(1) * m(6)
+            + m(12) * m(2) * m(5);
+        inv[3] = -m(1) * m(6) * m(11) + m(1) * m(7) * m(10) + m(5) * m(2) * m(11)
+            - m(5) * m(3) * m(10)
+            - m(9) * m(2) * m(7)
+            + m(9) * m(3) * m(6);
+        inv[7] = m(0) * m(6) * m(11) - m(0) * m(7) * m(10) - m(4) * m(2) * m(11)
+            + m(4) * m(3) * m(10)
+            + m(8) * m(2) * m(7)
+            - m(8) * m(3) * m(6);
+        inv[11] = -m(0) * m(5) * m(11) + m(0) * m(7) * m(9) + m(4) * m(1) * m(11)
+            - m(4) * m(3) * m(9)
+            - m(8) * m(1) * m(7)
+            + m(8) * m(3) * m(5);
+        inv[15] = m(0) * m(5) * m(10) - m(0) * m(6) * m(9) - m(4) * m(1) * m(10)
+            + m(4) * m(2) * m(9)
+            + m(8) * m(1) * m(6)
+            - m(8) * m(2) * m(5);
+
+        let det = m(0) * inv[0] + m(1) * inv[4] + m(2) * inv[8] + m(3) * inv[12];
+
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+
+        let mut result = Matrix4::new();
+
+        for (i, value) in inv.iter().enumerate() {
+            result[i / 4][i % 4] = value / det;
+        }
+
+        Some(result)
+    }
 }
 
 pub fn multiply(a: Matrix4, b: Matrix4) -> Matrix4 {
@@ -105,6 +259,46 @@ pub fn multiply(a: Matrix4, b: Matrix4) -> Matrix4 {
     result
 }
 
+/// Projects a point in world space to screen space, in pixels.
+///
+/// The point is transformed by the model, view and projection matrices (in that order), after which the perspective
+/// divide is applied and the resulting normalized device coordinates are mapped onto the `viewport`. The origin of the
+/// screen space is the top left corner of the viewport.
+///
+/// # Returns
+///
+/// The projected point, or `None` if the point is behind the camera.
+pub fn project_point(
+    world: Vec3,
+    model: &Matrix4,
+    view: &Matrix4,
+    proj: &Matrix4,
+    viewport: Vec2,
+) -> Option<Vec2> {
+    let point = Vec4::new(world[0], world[1], world[2], 1.0);
+    let clip = proj.transform_vec4(view.transform_vec4(model.transform_vec4(point)));
+
+    if clip[3] <= 0.0 {
+        return None;
+    }
+
+    let ndc = (clip[0] / clip[3], clip[1] / clip[3]);
+
+    Some(Vec2::new(
+        (ndc.0 * 0.5 + 0.5) * viewport[0],
+        (1.0 - (ndc.1 * 0.5 + 0.5)) * viewport[1],
+    ))
+}
+
+/// Multiplies two matrices, the same as [multiply].
+impl Mul for Matrix4 {
+    type Output = Matrix4;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        multiply(self, rhs)
+    }
+}
+
 unsafe impl Send for Matrix4 {}
 unsafe impl Sync for Matrix4 {}
 
@@ -164,6 +358,25 @@ impl Vec4 {
     pub fn inner(&self) -> [f32; 4] {
         [self[0], self[1], self[2], self[3]]
     }
+
+    pub fn dot(&self, other: Vec4) -> f32 {
+        self[0] * other[0] + self[1] * other[1] + self[2] * other[2] + self[3] * other[3]
+    }
+
+    pub fn length(&self) -> f32 {
+        self.dot(*self).sqrt()
+    }
+
+    /// Returns the vector scaled to a length of one, or the vector itself if its length is zero.
+    pub fn normalize(&self) -> Vec4 {
+        let length = self.length();
+
+        if length == 0.0 {
+            return *self;
+        }
+
+        *self * (1.0 / length)
+    }
 }
 
 impl Add for Vec4 {
@@ -179,6 +392,27 @@ impl Add for Vec4 {
     }
 }
 
+impl Sub for Vec4 {
+    type Output = Vec4;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Vec4::from([
+            self[0] - rhs[0],
+            self[1] - rhs[1],
+            self[2] - rhs[2],
+            self[3] - rhs[3],
+        ])
+    }
+}
+
+impl Mul<f32> for Vec4 {
+    type Output = Vec4;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        Vec4::from([self[0] * rhs, self[1] * rhs, self[2] * rhs, self[3] * rhs])
+    }
+}
+
 impl From<[f32; 4]> for Vec4 {
     fn from(value: [f32; 4]) -> Self {
         Self {
@@ -234,6 +468,33 @@ impl Vec3 {
     pub fn inner(&self) -> [f32; 3] {
         [self[0], self[1], self[2]]
     }
+
+    pub fn dot(&self, other: Vec3) -> f32 {
+        self[0] * other[0] + self[1] * other[1] + self[2] * other[2]
+    }
+
+    pub fn cross(&self, other: Vec3) -> Vec3 {
+        Vec3::new(
+            self[1] * other[2] - self[2] * other[1],
+            self[2] * other[0] - self[0] * other[2],
+            self[0] * other[1] - self[1] * other[0],
+        )
+    }
+
+    pub fn length(&self) -> f32 {
+        self.dot(*self).sqrt()
+    }
+
+    /// Returns the vector scaled to a length of one, or the vector itself if its length is zero.
+    pub fn normalize(&self) -> Vec3 {
+        let length = self.length();
+
+        if length == 0.0 {
+            return *self;
+        }
+
+        *self * (1.0 / length)
+    }
 }
 
 impl Add for Vec3 {
@@ -244,6 +505,22 @@ impl Add for Vec3 {
     }
 }
 
+impl Sub for Vec3 {
+    type Output = Vec3;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Vec3::from([self[0] - rhs[0], self[1] - rhs[1], self[2] - rhs[2]])
+    }
+}
+
+impl Mul<f32> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        Vec3::from([self[0] * rhs, self[1] * rhs, self[2] * rhs])
+    }
+}
+
 impl From<[f32; 3]> for Vec3 {
     fn from(value: [f32; 3]) -> Self {
         Self {
@@ -277,3 +554,228 @@ impl IndexMut<usize> for Vec3 {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy)]
+pub struct Vec2 {
+    x: f32,
+    y: f32,
+}
+
+unsafe impl Send for Vec2 {}
+unsafe impl Sync for Vec2 {}
+
+impl Vec2 {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn inner(&self) -> [f32; 2] {
+        [self[0], self[1]]
+    }
+
+    pub fn dot(&self, other: Vec2) -> f32 {
+        self[0] * other[0] + self[1] * other[1]
+    }
+
+    pub fn length(&self) -> f32 {
+        self.dot(*self).sqrt()
+    }
+
+    /// Returns the vector scaled to a length of one, or the vector itself if its length is zero.
+    pub fn normalize(&self) -> Vec2 {
+        let length = self.length();
+
+        if length == 0.0 {
+            return *self;
+        }
+
+        *self * (1.0 / length)
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Vec2::from([self[0] + rhs[0], self[1] + rhs[1]])
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Vec2::from([self[0] - rhs[0], self[1] - rhs[1]])
+    }
+}
+
+impl Mul<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        Vec2::from([self[0] * rhs, self[1] * rhs])
+    }
+}
+
+impl From<[f32; 2]> for Vec2 {
+    fn from(value: [f32; 2]) -> Self {
+        Self {
+            x: value[0],
+            y: value[1],
+        }
+    }
+}
+
+impl Index<usize> for Vec2 {
+    type Output = f32;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match index {
+            0 => &self.x,
+            1 => &self.y,
+            _ => panic!("Vec2 index out of range"),
+        }
+    }
+}
+
+impl IndexMut<usize> for Vec2 {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            _ => panic!("Vec2 index out of range"),
+        }
+    }
+}
+
+/// A rotation stored as unit quaternion, which can be composed and interpolated without accumulating drift.
+#[derive(Debug, Clone, Copy)]
+pub struct Quaternion {
+    x: f32,
+    y: f32,
+    z: f32,
+    w: f32,
+}
+
+unsafe impl Send for Quaternion {}
+unsafe impl Sync for Quaternion {}
+
+impl Quaternion {
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    /// Returns the quaternion which doesn't rotate at all.
+    pub fn identity() -> Self {
+        Self::new(0.0, 0.0, 0.0, 1.0)
+    }
+
+    /// Creates a rotation by `angle` radians around `axis`, which doesn't have to be normalized.
+    pub fn from_axis_angle(axis: impl Into<Vec3>, angle: f32) -> Self {
+        let axis = axis.into().normalize();
+        let (sin, cos) = (angle * 0.5).sin_cos();
+
+        Self::new(axis[0] * sin, axis[1] * sin, axis[2] * sin, cos)
+    }
+
+    pub fn inner(&self) -> [f32; 4] {
+        [self.x, self.y, self.z, self.w]
+    }
+
+    pub fn dot(&self, other: Quaternion) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    /// Returns the quaternion scaled to a length of one, or the identity if its length is zero.
+    pub fn normalize(&self) -> Quaternion {
+        let length = self.dot(*self).sqrt();
+
+        if length == 0.0 {
+            return Self::identity();
+        }
+
+        Self::new(
+            self.x / length,
+            self.y / length,
+            self.z / length,
+            self.w / length,
+        )
+    }
+
+    /// Spherically interpolates between this rotation at `t = 0` and `other` at `t = 1`, along the shortest path.
+    pub fn slerp(&self, other: Quaternion, t: f32) -> Quaternion {
+        let mut other = other;
+        let mut cos = self.dot(other);
+
+        // q and -q describe the same rotation, pick the one which is closer to take the short way around.
+        if cos < 0.0 {
+            other = Self::new(-other.x, -other.y, -other.z, -other.w);
+            cos = -cos;
+        }
+
+        // the rotations are almost the same, where the sine below approaches zero, so interpolate linearly instead.
+        let (from, to) = if cos > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let angle = cos.acos();
+            let sin = angle.sin();
+
+            (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+        };
+
+        Self::new(
+            self.x * from + other.x * to,
+            self.y * from + other.y * to,
+            self.z * from + other.z * to,
+            self.w * from + other.w * to,
+        )
+        .normalize()
+    }
+
+    /// Converts this rotation into a rotation matrix, laid out the same way as [Matrix4::rotate].
+    pub fn to_matrix4(&self) -> Matrix4 {
+        let Self { x, y, z, w } = *self;
+
+        Matrix4::from([
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y + w * z),
+                2.0 * (x * z - w * y),
+                0.0,
+            ],
+            [
+                2.0 * (x * y - w * z),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z + w * x),
+                0.0,
+            ],
+            [
+                2.0 * (x * z + w * y),
+                2.0 * (y * z - w * x),
+                1.0 - 2.0 * (x * x + y * y),
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// Composes two rotations, the result applies `rhs` first and `self` second.
+impl Mul for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Quaternion::new(
+            self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+            self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+        )
+    }
+}
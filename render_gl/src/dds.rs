@@ -0,0 +1,129 @@
+use glium::{
+    texture::{
+        CompressedFormat, CompressedMipmapsOption, CompressedTexture2d, TextureCreationError,
+    },
+    Display,
+};
+
+const MAGIC: &[u8; 4] = b"DDS ";
+const HEADER_SIZE: usize = 128;
+const DX10_HEADER_SIZE: usize = 20;
+
+const DXGI_FORMAT_BC1_UNORM: u32 = 71;
+const DXGI_FORMAT_BC3_UNORM: u32 = 77;
+const DXGI_FORMAT_BC5_UNORM: u32 = 83;
+
+/// The block compression formats which can be read from a DDS file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DdsFormat {
+    /// BC1 (DXT1), 8 bytes per 4x4 block.
+    Bc1,
+    /// BC3 (DXT5), 16 bytes per 4x4 block.
+    Bc3,
+    /// BC5 (ATI2), two channels with 16 bytes per 4x4 block. Commonly used for normal maps.
+    Bc5,
+}
+
+impl DdsFormat {
+    fn from_four_cc(four_cc: &[u8]) -> Option<Self> {
+        match four_cc {
+            b"DXT1" => Some(Self::Bc1),
+            b"DXT5" => Some(Self::Bc3),
+            b"ATI2" | b"BC5U" => Some(Self::Bc5),
+            _ => None,
+        }
+    }
+
+    fn from_dxgi(format: u32) -> Option<Self> {
+        match format {
+            DXGI_FORMAT_BC1_UNORM => Some(Self::Bc1),
+            DXGI_FORMAT_BC3_UNORM => Some(Self::Bc3),
+            DXGI_FORMAT_BC5_UNORM => Some(Self::Bc5),
+            _ => None,
+        }
+    }
+
+    /// The size of a single 4x4 block in bytes.
+    pub fn block_size(&self) -> usize {
+        match self {
+            Self::Bc1 => 8,
+            Self::Bc3 | Self::Bc5 => 16,
+        }
+    }
+
+    pub fn compressed_format(&self) -> CompressedFormat {
+        match self {
+            Self::Bc1 => CompressedFormat::S3tcDxt1Alpha,
+            Self::Bc3 => CompressedFormat::S3tcDxt5Alpha,
+            Self::Bc5 => CompressedFormat::RgtcFormatUU,
+        }
+    }
+}
+
+/// The top level image of a block compressed DDS file.
+///
+/// Only the top mip level is kept, any mipmaps stored in the file are ignored.
+#[derive(Debug, Clone)]
+pub struct DdsImage {
+    pub format: DdsFormat,
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl DdsImage {
+    /// Parses the header of a DDS file and extracts the compressed data of the top mip level.
+    ///
+    /// Returns `None` if the file is truncated, isn't a DDS file, or uses a format other than BC1, BC3 or BC5.
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < HEADER_SIZE || &bytes[0..4] != MAGIC {
+            return None;
+        }
+
+        let read = |offset: usize| {
+            u32::from_le_bytes([
+                bytes[offset],
+                bytes[offset + 1],
+                bytes[offset + 2],
+                bytes[offset + 3],
+            ])
+        };
+
+        let (height, width) = (read(12), read(16));
+        let four_cc = &bytes[84..88];
+
+        let (format, offset) = if four_cc == b"DX10" {
+            if bytes.len() < HEADER_SIZE + DX10_HEADER_SIZE {
+                return None;
+            }
+
+            let format = DdsFormat::from_dxgi(read(HEADER_SIZE))?;
+            (format, HEADER_SIZE + DX10_HEADER_SIZE)
+        } else {
+            (DdsFormat::from_four_cc(four_cc)?, HEADER_SIZE)
+        };
+
+        let blocks = |size: u32| (size.max(1) as usize).div_ceil(4);
+        let length = blocks(width) * blocks(height) * format.block_size();
+        let data = bytes.get(offset..offset + length)?.to_vec();
+
+        Some(Self {
+            format,
+            width,
+            height,
+            data,
+        })
+    }
+
+    /// Uploads the compressed data, without decompressing it on the CPU.
+    pub fn upload(&self, display: &Display) -> Result<CompressedTexture2d, TextureCreationError> {
+        CompressedTexture2d::with_compressed_data(
+            display,
+            &self.data,
+            self.width,
+            self.height,
+            self.format.compressed_format(),
+            CompressedMipmapsOption::NoMipmap,
+        )
+    }
+}
@@ -0,0 +1,323 @@
+use std::{any::TypeId, rc::Rc};
+
+use ecs::{
+    entity::{EntityManager, EntityQueryTable},
+    system::System,
+};
+use ecs_macro::EntityComponent;
+use glium::{glutin::event::VirtualKeyCode, index::PrimitiveType, Display, Program, VertexBuffer};
+
+use crate::{
+    bounds::{BoundingSphere, Culled},
+    buffer::IndexBufferCreator,
+    container::Vec3,
+    draw::vertex::{ColoredVertex, ToBuffer, Vertex},
+    error::RenderError,
+    input::Input,
+};
+
+pub const LINE_VERTEX_SHADER: &str = r#"
+#version 140
+
+in vec3 position;
+in vec3 color;
+
+out vec3 v_color;
+
+uniform mat4 matrix;
+uniform mat4 view;
+uniform mat4 perspective;
+
+void main() {
+    v_color = color;
+    gl_Position = perspective * view * matrix * vec4(position, 1.0);
+}
+"#;
+
+pub const LINE_FRAGMENT_SHADER: &str = r#"
+#version 140
+
+in vec3 v_color;
+out vec4 color;
+
+void main() {
+    color = vec4(v_color, 1.0);
+}
+"#;
+
+/// Builds a line list visualizing the normal of every vertex.
+///
+/// Every normal is drawn as a segment of the given `length`, starting at the vertex position. The segments are colored
+/// by their direction, so flipped normals stand out.
+///
+/// # Returns
+///
+/// Two vertices for every source vertex, to be drawn as `PrimitiveType::LinesList`.
+pub fn normals_mesh(vertices: &[Vertex], length: f32) -> Vec<ColoredVertex> {
+    let mut lines = Vec::with_capacity(vertices.len() * 2);
+
+    for vertex in vertices {
+        let [x, y, z] = vertex.position;
+        let [nx, ny, nz] = vertex.normal;
+        let color = [nx * 0.5 + 0.5, ny * 0.5 + 0.5, nz * 0.5 + 0.5];
+
+        lines.push(ColoredVertex {
+            position: [x, y, z],
+            color,
+        });
+        lines.push(ColoredVertex {
+            position: [x + nx * length, y + ny * length, z + nz * length],
+            color,
+        });
+    }
+
+    lines
+}
+
+/// The color of bounding volumes of entities which passed the frustum cull.
+pub const VISIBLE_BOUNDS_COLOR: [f32; 3] = [0.0, 1.0, 0.0];
+/// The color of bounding volumes of entities which were culled.
+pub const CULLED_BOUNDS_COLOR: [f32; 3] = [1.0, 0.0, 0.0];
+
+/// The amount of segments every circle of a wireframe sphere is made of.
+const SPHERE_SEGMENTS: usize = 16;
+
+/// Builds a wireframe of a [BoundingSphere], made of one circle around each axis.
+///
+/// # Returns
+///
+/// Two vertices for every segment, to be drawn as `PrimitiveType::LinesList`.
+pub fn sphere_lines(sphere: &BoundingSphere, color: [f32; 3]) -> Vec<ColoredVertex> {
+    let mut lines = Vec::with_capacity(SPHERE_SEGMENTS * 6);
+    let center = sphere.center;
+
+    let point = |axis: usize, step: usize| {
+        let angle = step as f32 / SPHERE_SEGMENTS as f32 * std::f32::consts::TAU;
+        let (sin, cos) = (angle.sin() * sphere.radius, angle.cos() * sphere.radius);
+
+        let offset = match axis {
+            0 => [0.0, cos, sin],
+            1 => [cos, 0.0, sin],
+            _ => [cos, sin, 0.0],
+        };
+
+        ColoredVertex {
+            position: [
+                center[0] + offset[0],
+                center[1] + offset[1],
+                center[2] + offset[2],
+            ],
+            color,
+        }
+    };
+
+    for axis in 0..3 {
+        for step in 0..SPHERE_SEGMENTS {
+            lines.push(point(axis, step));
+            lines.push(point(axis, step + 1));
+        }
+    }
+
+    lines
+}
+
+/// An indexed line list connecting the joints of a skeleton, built by [skeleton].
+#[derive(Debug, Clone)]
+pub struct Skeleton {
+    /// One vertex for every joint.
+    pub vertices: Vec<ColoredVertex>,
+    /// Two indices for every bone, to be drawn as `PrimitiveType::LinesList`.
+    pub indices: Vec<u32>,
+}
+
+impl Skeleton {
+    /// Uploads the joints, and pushes the bones as index buffer onto `indices`, where it can be retrieved with
+    /// [IndexBufferCreator::get_index_buffer_u32].
+    pub fn upload(
+        &self,
+        display: &Display,
+        indices: &mut IndexBufferCreator,
+    ) -> Result<VertexBuffer<ColoredVertex>, RenderError> {
+        indices.create_index_buffer_u32(display, &self.indices, PrimitiveType::LinesList)?;
+        Ok(ColoredVertex::to_buffer(display, &self.vertices)?)
+    }
+}
+
+/// Builds an indexed line list visualizing a skeleton, with a line for every bone between its two joints.
+///
+/// Bones referring to a joint which doesn't exist are skipped with a warning.
+pub fn skeleton(joints: &[Vec3], bones: &[(usize, usize)], color: [f32; 3]) -> Skeleton {
+    let vertices = joints
+        .iter()
+        .map(|joint| ColoredVertex {
+            position: joint.inner(),
+            color,
+        })
+        .collect();
+
+    let mut indices = Vec::with_capacity(bones.len() * 2);
+
+    for (from, to) in bones {
+        if *from >= joints.len() || *to >= joints.len() {
+            eprintln!(
+                "Skipping bone ({}, {}) of a skeleton with {} joints",
+                from,
+                to,
+                joints.len()
+            );
+            continue;
+        }
+
+        indices.push(*from as u32);
+        indices.push(*to as u32);
+    }
+
+    Skeleton { vertices, indices }
+}
+
+/// Debug lines belonging to an entity, drawn on top of the entity's `Mesh` with the entity's `MeshUniform`.
+#[derive(EntityComponent)]
+pub struct DebugLines {
+    pub vertex_buffer: VertexBuffer<ColoredVertex>,
+    pub program: Program,
+    pub enabled: bool,
+}
+
+impl DebugLines {
+    /// Creates new debug lines from a line list, such as the one built by [normals_mesh].
+    pub fn new(display: &Display, lines: &[ColoredVertex]) -> Result<Self, RenderError> {
+        let vertex_buffer = ColoredVertex::to_buffer(display, lines)?;
+        let program =
+            Program::from_source(display, LINE_VERTEX_SHADER, LINE_FRAGMENT_SHADER, None)?;
+
+        Ok(Self {
+            vertex_buffer,
+            program,
+            enabled: true,
+        })
+    }
+}
+
+/// Toggles all [DebugLines] whenever `key` is pressed.
+pub struct DebugToggleSystem {
+    pub key: VirtualKeyCode,
+}
+
+impl DebugToggleSystem {
+    pub fn new(key: VirtualKeyCode) -> Self {
+        Self { key }
+    }
+}
+
+impl<T> System<T> for DebugToggleSystem {
+    fn update(
+        &mut self,
+        manager: &mut EntityManager,
+        table: &mut EntityQueryTable,
+        _: &T,
+    ) -> Option<()> {
+        let input = *table.query_single::<Input>(manager)?.first()?;
+
+        if !manager
+            .query_entity::<Input>(input)
+            .0?
+            .just_pressed(self.key)
+        {
+            return None;
+        }
+
+        for lines in manager
+            .borrow_manager_mut::<DebugLines>()?
+            .components
+            .iter_mut()
+        {
+            lines.enabled = !lines.enabled;
+        }
+
+        None
+    }
+
+    fn runs_while_paused(&self) -> bool {
+        true
+    }
+}
+
+/// The bounding volumes queued for drawing during the current frame.
+///
+/// The lines of every entity are in the local space of its `Mesh`, and are drawn with the entity's `MeshUniform`.
+#[derive(EntityComponent, Default)]
+pub struct BoundsDebug {
+    pub enabled: bool,
+    pub queued: Vec<(usize, Vec<ColoredVertex>)>,
+    /// The line program, compiled by the render system once the bounds are drawn for the first time.
+    pub program: Option<Rc<Program>>,
+}
+
+impl BoundsDebug {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Toggles [BoundsDebug] whenever `key` is pressed, and queues the [BoundingSphere] of every entity while it's
+/// enabled. Spheres of entities marked as [Culled] are colored differently, see [CULLED_BOUNDS_COLOR].
+pub struct BoundsDebugSystem {
+    pub key: VirtualKeyCode,
+}
+
+impl BoundsDebugSystem {
+    pub fn new(key: VirtualKeyCode) -> Self {
+        Self { key }
+    }
+}
+
+impl<T> System<T> for BoundsDebugSystem {
+    fn update(
+        &mut self,
+        manager: &mut EntityManager,
+        table: &mut EntityQueryTable,
+        _: &T,
+    ) -> Option<()> {
+        let debug = *table.query_first_single::<BoundsDebug>(manager)?;
+
+        let toggled = match table.query_first_single::<Input>(manager).copied() {
+            Some(input) => manager
+                .query_entity::<Input>(input)
+                .0?
+                .just_pressed(self.key),
+            None => false,
+        };
+
+        let state = manager.query_entity::<BoundsDebug>(debug).0?;
+
+        if toggled {
+            state.enabled = !state.enabled;
+        }
+
+        state.queued.clear();
+
+        if !state.enabled {
+            return None;
+        }
+
+        let mut queued = vec![];
+
+        for entity in table.query_single::<BoundingSphere>(manager)?.clone() {
+            let color = match manager.has_type(TypeId::of::<Culled>(), entity) {
+                true => CULLED_BOUNDS_COLOR,
+                false => VISIBLE_BOUNDS_COLOR,
+            };
+
+            let sphere = manager.query_entity::<BoundingSphere>(entity).0?;
+            queued.push((entity, sphere_lines(sphere, color)));
+        }
+
+        manager.query_entity::<BoundsDebug>(debug).0?.queued = queued;
+
+        None
+    }
+
+    fn runs_while_paused(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,180 @@
+use ecs::entity::EntityManager;
+use ecs_macro::EntityComponent;
+use glium::{Rect, Surface};
+
+use crate::{container::Vec3, draw::instanced::MAX_INSTANCES_PER_BATCH};
+
+/// A red, green, blue and alpha color, as the buffers are cleared with.
+type Rgba = (f32, f32, f32, f32);
+
+/// The buffers which are cleared at the start of every frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClearFlags {
+    /// Clears both the color and the depth buffer.
+    ColorAndDepth,
+    /// Clears only the color buffer.
+    Color,
+    /// Clears only the depth buffer.
+    Depth,
+    /// Clears nothing, which is useful for additive passes drawing on top of the previous frame.
+    None,
+}
+
+impl ClearFlags {
+    /// Returns the values the color and depth buffers should be cleared to, or `None` if the buffer should be left as is.
+    pub fn values(&self, color: Rgba, depth: f32) -> (Option<Rgba>, Option<f32>) {
+        match self {
+            ClearFlags::ColorAndDepth => (Some(color), Some(depth)),
+            ClearFlags::Color => (Some(color), None),
+            ClearFlags::Depth => (None, Some(depth)),
+            ClearFlags::None => (None, None),
+        }
+    }
+
+    /// Clears the buffers of the `surface` selected by these flags, limited to `rect` if given.
+    pub fn clear<S>(
+        &self,
+        surface: &mut S,
+        rect: Option<&Rect>,
+        color: (f32, f32, f32, f32),
+        depth: f32,
+    ) where
+        S: Surface,
+    {
+        let (color, depth) = self.values(color, depth);
+
+        if color.is_none() && depth.is_none() {
+            return;
+        }
+
+        surface.clear(rect, color, false, depth, None);
+    }
+}
+
+/// The color the frame is cleared to, stored as resource. Without this resource, frames are cleared to black.
+#[derive(Debug, Clone, Copy)]
+pub struct ClearColor(pub Vec3);
+
+impl ClearColor {
+    pub fn new(color: impl Into<Vec3>) -> Self {
+        Self(color.into())
+    }
+
+    /// Returns the color as opaque RGBA.
+    pub fn rgba(&self) -> (f32, f32, f32, f32) {
+        (self.0[0], self.0[1], self.0[2], 1.0)
+    }
+}
+
+/// Returns the color the frame is cleared to, taken from the [ClearColor] resource or black if there is none.
+pub fn clear_color(manager: &EntityManager) -> (f32, f32, f32, f32) {
+    manager
+        .resource::<ClearColor>()
+        .map(ClearColor::rgba)
+        .unwrap_or((0.0, 0.0, 0.0, 1.0))
+}
+
+/// Computes the largest rect with the given aspect ratio (width / height) which fits into a surface of the given
+/// `dimensions`, centered on the surface.
+///
+/// If the surface is wider than the aspect ratio, bars are left on the sides (pillarboxing), otherwise above and
+/// below (letterboxing).
+pub fn letterbox(dimensions: (u32, u32), aspect_ratio: f32) -> Rect {
+    let (width, height) = dimensions;
+
+    if width == 0 || height == 0 {
+        return Rect {
+            left: 0,
+            bottom: 0,
+            width,
+            height,
+        };
+    }
+
+    let surface_ratio = width as f32 / height as f32;
+
+    if surface_ratio > aspect_ratio {
+        let inner = (height as f32 * aspect_ratio).round() as u32;
+
+        Rect {
+            left: (width - inner) / 2,
+            bottom: 0,
+            width: inner,
+            height,
+        }
+    } else {
+        let inner = (width as f32 / aspect_ratio).round() as u32;
+
+        Rect {
+            left: 0,
+            bottom: (height - inner) / 2,
+            width,
+            height: inner,
+        }
+    }
+}
+
+/// Marks a mesh as transparent.
+///
+/// Transparent meshes are drawn after all opaque meshes, ordered back to front by their distance to the camera, so
+/// they're blended over everything behind them.
+#[derive(EntityComponent, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transparent;
+
+/// Configures the behavior of the `GlRenderSystem`.
+///
+/// The configuration is read from the first entity holding a `RenderConfig` component, if there is no such entity,
+/// the default configuration is used.
+#[derive(EntityComponent, Debug, Clone)]
+pub struct RenderConfig {
+    pub clear_flags: ClearFlags,
+    /// Whether non-instanced meshes should be drawn front to back, ordered by their distance to the camera.
+    ///
+    /// This reduces overdraw for opaque geometry, at the cost of sorting the meshes every frame.
+    pub sort_opaque: bool,
+    /// The aspect ratio (width / height) the scene is locked to.
+    ///
+    /// If the window has a different aspect ratio, the scene is drawn into a centered viewport of this aspect ratio
+    /// and the remaining margins are cleared to black, see [letterbox].
+    pub aspect_ratio: Option<f32>,
+    /// The maximum amount of instances of a mesh drawn with a single draw call, more instances are split into
+    /// several draw calls. Defaults to [MAX_INSTANCES_PER_BATCH].
+    pub max_instances_per_batch: usize,
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        Self {
+            clear_flags: ClearFlags::ColorAndDepth,
+            sort_opaque: false,
+            aspect_ratio: None,
+            max_instances_per_batch: MAX_INSTANCES_PER_BATCH,
+        }
+    }
+}
+
+impl RenderConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear_flags(mut self, clear_flags: ClearFlags) -> Self {
+        self.clear_flags = clear_flags;
+        self
+    }
+
+    pub fn sort_opaque(mut self, sort_opaque: bool) -> Self {
+        self.sort_opaque = sort_opaque;
+        self
+    }
+
+    pub fn aspect_ratio(mut self, aspect_ratio: f32) -> Self {
+        self.aspect_ratio = Some(aspect_ratio);
+        self
+    }
+
+    pub fn max_instances_per_batch(mut self, max_instances_per_batch: usize) -> Self {
+        self.max_instances_per_batch = max_instances_per_batch;
+        self
+    }
+}
@@ -5,17 +5,49 @@ use ecs_macro::EntityComponent;
 #[derive(EntityComponent)]
 pub struct TimeDelta {
     last_time: Instant,
+    /// The time of the first [TimeDelta::update_time_delta], from which the total elapsed time is counted.
+    start: Option<Instant>,
+    frames: u64,
+    /// The simulated time which hasn't been consumed by fixed steps yet, see [TimeDelta::accumulate].
+    accumulator: Duration,
 }
 
 impl TimeDelta {
     pub fn new() -> Self {
         Self {
             last_time: Instant::now(),
+            start: None,
+            frames: 0,
+            accumulator: Duration::ZERO,
         }
     }
 
+    /// Starts a new frame, which resets the delta and advances the total elapsed time and the frame count.
     pub fn update_time_delta(&mut self) {
-        self.last_time = Instant::now();
+        let now = Instant::now();
+
+        self.start.get_or_insert(now);
+        self.last_time = now;
+        self.frames += 1;
+    }
+
+    /// Returns the time from the first to the latest [TimeDelta::update_time_delta], so the first frame reports zero
+    /// rather than the time since the `TimeDelta` was created.
+    pub fn total_elapsed(&self) -> Duration {
+        self.start
+            .map(|start| self.last_time.duration_since(start))
+            .unwrap_or_default()
+    }
+
+    /// Returns the total elapsed time in seconds, e.g. to feed the `u_time` uniform of time based shader effects, see
+    /// `MeshUniform::set_time`.
+    pub fn total_elapsed_secs(&self) -> f32 {
+        self.total_elapsed().as_secs_f32()
+    }
+
+    /// Returns the number of frames started by [TimeDelta::update_time_delta].
+    pub fn frame_count(&self) -> u64 {
+        self.frames
     }
 
     pub fn get_time_delta(&mut self) -> Duration {
@@ -40,4 +72,37 @@ impl TimeDelta {
     pub fn get_time_delta_sec(&mut self) -> f32 {
         self.get_time_delta().as_secs_f32()
     }
+
+    /// Adds the time since the last [TimeDelta::update_time_delta] to the accumulator and consumes it in steps of
+    /// `fixed_dt`, so a simulation can run the same deterministic ticks regardless of the frame rate.
+    ///
+    /// Should be called once per frame, before the delta is reset by [TimeDelta::update_time_delta].
+    ///
+    /// # Returns
+    ///
+    /// The number of fixed steps to run this frame, and the leftover time carried over to the next frame.
+    pub fn accumulate(&mut self, fixed_dt: Duration) -> (u32, Duration) {
+        let frame_time = self.get_time_delta();
+        self.accumulate_frame(frame_time, fixed_dt)
+    }
+
+    /// Same as [TimeDelta::accumulate], but with an explicitly given `frame_time` instead of the measured delta.
+    ///
+    /// A zero `fixed_dt` never produces any steps, the time keeps accumulating instead.
+    pub fn accumulate_frame(
+        &mut self,
+        frame_time: Duration,
+        fixed_dt: Duration,
+    ) -> (u32, Duration) {
+        self.accumulator += frame_time;
+
+        if fixed_dt.is_zero() {
+            return (0, self.accumulator);
+        }
+
+        let steps = (self.accumulator.as_nanos() / fixed_dt.as_nanos()) as u32;
+        self.accumulator -= fixed_dt * steps;
+
+        (steps, self.accumulator)
+    }
 }
@@ -0,0 +1,167 @@
+use std::collections::{HashMap, HashSet};
+
+use ecs::{
+    component::Component,
+    entity::{EntityManager, EntityQueryTable},
+    system::System,
+};
+use ecs_macro::EntityComponent;
+
+use crate::container::{multiply, Matrix4};
+
+use super::transform::Transform;
+
+/// Attaches an entity to the entity with the given id, so its `Transform` is relative to the parent's world matrix.
+#[derive(EntityComponent, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parent(pub usize);
+
+/// The world matrix of an entity with a [Parent], computed by [TransformHierarchySystem].
+///
+/// While rendering, it takes the place of the entity's local `Transform`.
+#[derive(EntityComponent, Debug, Clone, Copy)]
+pub struct WorldTransform(pub Matrix4);
+
+/// Computes the [WorldTransform] of every entity with a [Parent] and a `Transform`, by multiplying its local
+/// transform by the world matrix of its parent, all the way up to the root of the chain.
+///
+/// A parent without a `Transform` (e.g. because it was removed) detaches the child, which is then drawn as a root
+/// again. Entities whose chain of parents forms a cycle are skipped, and keep their local transform.
+///
+/// Should be registered after the systems moving transforms, and before the render systems.
+pub struct TransformHierarchySystem;
+
+impl<T> System<T> for TransformHierarchySystem {
+    fn update(
+        &mut self,
+        manager: &mut EntityManager,
+        _: &mut EntityQueryTable,
+        _: &T,
+    ) -> Option<()> {
+        let parents = components(manager, |parent: &Parent| parent.0);
+
+        if parents.is_empty() {
+            return None;
+        }
+
+        let locals = components(manager, |transform: &Transform| transform.matrix);
+
+        let (worlds, detached) = world_matrices(&parents, &locals);
+
+        for entity in parents.keys() {
+            let Some(world) = worlds.get(entity) else {
+                manager.remove_component::<WorldTransform>(*entity);
+                continue;
+            };
+
+            match manager.query_entity::<WorldTransform>(*entity).0 {
+                Some(current) => current.0 = *world,
+                None => {
+                    manager.entity_with(*entity, WorldTransform(*world));
+                }
+            }
+        }
+
+        for entity in detached {
+            manager.remove_component::<Parent>(entity);
+        }
+
+        None
+    }
+
+    fn runs_while_paused(&self) -> bool {
+        true
+    }
+}
+
+/// Maps the components of type `C` by their entity.
+fn components<C, V>(manager: &EntityManager, value: impl Fn(&C) -> V) -> HashMap<usize, V>
+where
+    C: Component + 'static,
+{
+    match manager.borrow_manager::<C>() {
+        Some(components) => components
+            .entities
+            .iter()
+            .copied()
+            .zip(components.components.iter().map(value))
+            .collect(),
+        None => HashMap::new(),
+    }
+}
+
+/// Computes the world matrices of the children in `parents` (child to parent), given the local matrices of all
+/// entities with a `Transform`.
+///
+/// Every chain is walked up to its root once, and the matrices along it are computed top down, so each entity is
+/// visited once regardless of the order of the maps.
+///
+/// # Returns
+///
+/// The world matrices of all children which are part of a valid chain, and the children whose parent has no local
+/// matrix, which are detached. Children in or below a cycle are part of neither.
+pub fn world_matrices(
+    parents: &HashMap<usize, usize>,
+    locals: &HashMap<usize, Matrix4>,
+) -> (HashMap<usize, Matrix4>, Vec<usize>) {
+    let mut worlds = HashMap::<usize, Matrix4>::new();
+    let mut cyclic = HashSet::<usize>::new();
+    let mut detached = vec![];
+
+    for &entity in parents.keys() {
+        // walks up until an entity with a known world matrix, or a root, is reached.
+        let mut chain = vec![];
+        let mut current = entity;
+        let mut base = None;
+
+        loop {
+            if let Some(world) = worlds.get(&current) {
+                base = Some(*world);
+                break;
+            }
+
+            if cyclic.contains(&current) || chain.contains(&current) {
+                cyclic.extend(chain.iter().copied());
+                chain.clear();
+                break;
+            }
+
+            let Some(local) = locals.get(&current) else {
+                // a child without a transform of its own has no world matrix either.
+                chain.clear();
+                break;
+            };
+
+            chain.push(current);
+
+            match parents.get(&current) {
+                Some(parent) if locals.contains_key(parent) => current = *parent,
+                Some(_) => {
+                    detached.push(current);
+                    base = Some(*local);
+                    chain.pop();
+                    worlds.insert(current, *local);
+                    break;
+                }
+                None => {
+                    base = Some(*local);
+                    chain.pop();
+                    break;
+                }
+            }
+        }
+
+        let Some(mut world) = base else {
+            continue;
+        };
+
+        for child in chain.into_iter().rev() {
+            world = multiply(locals[&child], world);
+
+            if parents.contains_key(&child) {
+                worlds.insert(child, world);
+            }
+        }
+    }
+
+    (worlds, detached)
+}
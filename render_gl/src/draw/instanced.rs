@@ -0,0 +1,171 @@
+use std::ops::Range;
+
+use ecs::world::World;
+use ecs_macro::EntityComponent;
+use glium::{implement_vertex, VertexBuffer};
+
+use crate::container::{Quaternion, Vec3};
+
+/// A single instance of a `Mesh`, drawn at its own world position, with its own rotation and scale.
+///
+/// Every `Instanced` component refers to the entity holding the `Mesh` it's an instance of. The render system
+/// gathers all instances of a mesh into a per-instance vertex buffer, which exposes the `world_position`, `rotation`
+/// (a quaternion, `xyzw`) and `scale` attributes to the vertex shader.
+#[derive(EntityComponent, Debug, Clone, Copy, PartialEq)]
+pub struct Instanced {
+    pub mesh: u32,
+    pub world_position: [f32; 3],
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+}
+
+implement_vertex!(Instanced, world_position, rotation, scale);
+
+impl Instanced {
+    /// Creates an instance at the given position, which is neither rotated nor scaled.
+    ///
+    /// See [InstanceSpawner::with_instances] for spawning many rotated and scaled instances at once.
+    pub fn create(mesh: u32, world_position: (f32, f32, f32)) -> Self {
+        Self {
+            mesh,
+            world_position: [world_position.0, world_position.1, world_position.2],
+            rotation: Quaternion::identity().inner(),
+            scale: [1.0, 1.0, 1.0],
+        }
+    }
+
+    pub fn rotation(mut self, rotation: Quaternion) -> Self {
+        self.rotation = rotation.inner();
+        self
+    }
+
+    pub fn scale(mut self, scale: impl Into<Vec3>) -> Self {
+        self.scale = scale.into().inner();
+        self
+    }
+}
+
+/// The default maximum amount of instances drawn with a single draw call, see `RenderConfig::max_instances_per_batch`.
+pub const MAX_INSTANCES_PER_BATCH: usize = 65536;
+
+/// Splits `count` instances into consecutive batches of at most `max_per_batch` instances, which are uploaded to
+/// their own buffers and drawn with their own draw calls.
+///
+/// A maximum of zero is treated as one, so every batch holds at least one instance.
+pub fn instance_batches(count: usize, max_per_batch: usize) -> impl Iterator<Item = Range<usize>> {
+    let max_per_batch = max_per_batch.max(1);
+
+    (0..count)
+        .step_by(max_per_batch)
+        .map(move |start| start..count.min(start + max_per_batch))
+}
+
+/// How the instance buffer of a mesh, which is kept across frames, has to be updated to hold the current instances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstanceUpload {
+    /// The instances didn't change, so the buffer can be drawn as is.
+    Reuse,
+    /// The amount of instances didn't change, so the buffer can be overwritten in place.
+    Write,
+    /// There's no buffer yet, or the amount of instances changed, so a new buffer has to be created.
+    Create,
+}
+
+impl InstanceUpload {
+    /// Decides how the `buffer` of a mesh, if it has one, is updated to hold its instances, split into `batches` of
+    /// the given lengths.
+    ///
+    /// The instances are only uploaded again if they `changed` since the buffer was written (see
+    /// [InstanceBuffer::changed]), or if they were culled with a different `frustum`.
+    pub fn of(
+        buffer: Option<&InstanceBuffer>,
+        changed: u64,
+        frustum: Option<[[f32; 4]; 4]>,
+        batches: &[usize],
+    ) -> Self {
+        let Some(buffer) = buffer else {
+            return InstanceUpload::Create;
+        };
+
+        // the buffers can only be reused or overwritten if they're still split into the same batches.
+        let lengths = buffer.buffers.iter().map(|buffer| buffer.len());
+
+        if !lengths.eq(batches.iter().copied()) {
+            return InstanceUpload::Create;
+        }
+
+        match buffer.changed == changed && buffer.frustum == frustum {
+            true => InstanceUpload::Reuse,
+            false => InstanceUpload::Write,
+        }
+    }
+}
+
+/// The per-instance vertex buffers of a mesh.
+///
+/// This is attached to the entity holding the `Mesh` by the render system, and kept up to date with its instances.
+/// The instances are split into batches by [instance_batches], so very large amounts of instances don't exceed the
+/// buffer limits of the GPU, and every buffer is drawn with its own draw call.
+///
+/// Changes to the instances are tracked through the frame they were last updated at, so instances which are
+/// modified in place have to be marked with `EntityManager::mark_changed::<Instanced>()` to be uploaded again.
+#[derive(EntityComponent)]
+pub struct InstanceBuffer {
+    pub buffers: Vec<VertexBuffer<Instanced>>,
+    /// The last frame the [Instanced] components or the bounding spheres they're culled with changed at, when the
+    /// buffers were written, see `EntityManager::get_updated_frame`.
+    pub changed: u64,
+    /// The view projection matrix of the frustum the instances were culled with, if they were culled.
+    pub frustum: Option<[[f32; 4]; 4]>,
+    /// The frame the buffers were last written at.
+    pub uploaded: u64,
+}
+
+/// The render layer of a `Mesh` with instances, which determines the order instanced groups are drawn in.
+///
+/// Groups on lower layers are drawn first, meshes without a `RenderLayer` are on layer `0`.
+#[derive(EntityComponent, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RenderLayer(pub i32);
+
+/// Extension trait for spawning many [Instanced] components at once.
+pub trait InstanceSpawner {
+    /// Spawns a new entity with an [Instanced] component of the `parent` mesh for every given position, rotation
+    /// and scale.
+    ///
+    /// All components are inserted in a single pass, which is considerably faster than calling `World::with`
+    /// for every instance.
+    ///
+    /// # Returns
+    ///
+    /// The ids of the spawned entities, in the same order as the instances.
+    fn with_instances(
+        &mut self,
+        parent: usize,
+        instances: &[(Vec3, Quaternion, Vec3)],
+    ) -> Vec<usize>;
+}
+
+impl<T> InstanceSpawner for World<T> {
+    fn with_instances(
+        &mut self,
+        parent: usize,
+        instances: &[(Vec3, Quaternion, Vec3)],
+    ) -> Vec<usize> {
+        let mut entities = Vec::with_capacity(instances.len());
+        let mut components = Vec::with_capacity(instances.len());
+
+        for (position, rotation, scale) in instances {
+            let entity = self.entity();
+            let instance =
+                Instanced::create(parent as u32, (position[0], position[1], position[2]))
+                    .rotation(*rotation)
+                    .scale(*scale);
+
+            entities.push(entity);
+            components.push((entity, instance));
+        }
+
+        self.entity_manager.entity_with_many(components);
+        entities
+    }
+}
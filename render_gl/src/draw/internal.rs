@@ -1,16 +1,47 @@
-use ecs::system::System;
-use glium::{uniforms::EmptyUniforms, Display, Surface};
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
-use crate::{camera::Camera, mesh::Mesh, uniform::MeshUniform};
+use ecs::{
+    system::System,
+    world::{SystemType, World},
+};
+use glium::{
+    backend::Facade,
+    index::{NoIndices, PrimitiveType},
+    vertex::MultiVerticesSource,
+    Display, DrawParameters, Frame, Program, Rect, Surface, VertexBuffer,
+};
 
-use super::transform::{DrawParametersComponent, Transform};
+use crate::{
+    bounds::{BoundingSphere, Culled, Frustum},
+    camera::{active_perspective, camera_view},
+    container::{multiply, Matrix4, Vec3},
+    debug::{BoundsDebug, DebugLines, LINE_FRAGMENT_SHADER, LINE_VERTEX_SHADER},
+    error::RenderError,
+    headless::HeadlessContext,
+    mesh::{ColoredMesh, Mesh, Winding},
+    sprite::{draw_sprites, SpriteRenderSystem},
+    uniform::MeshUniform,
+};
+
+use super::{
+    config::{clear_color, letterbox, ClearFlags, RenderConfig, Transparent},
+    hierarchy::WorldTransform,
+    instanced::{instance_batches, InstanceBuffer, InstanceUpload, Instanced, RenderLayer},
+    target::RenderTargetSystem,
+    transform::{DrawParametersComponent, Transform},
+    vertex::{ColoredVertex, ToBuffer},
+};
 
 pub struct GlRenderSystem;
-pub struct InternalTransformSystem;
 
 impl System<Display> for GlRenderSystem {
-    /// Renders the mesh components of all entities that have both a `Mesh` and a `Transform` component.
-    /// If an entity has a `Mesh` component but no `Transform` component, the default identity matrix is used.
+    /// Renders the mesh components of all entities that have a `Mesh` component.
+    /// The model matrix is taken from the entity's `Transform`, falling back to the matrix of its `MeshUniform`, and
+    /// finally to the identity matrix.
     ///
     /// # Parameters
     ///
@@ -28,83 +59,696 @@ impl System<Display> for GlRenderSystem {
         table: &mut ecs::entity::EntityQueryTable,
         display: &Display,
     ) -> Option<()> {
-        let view = {
-            let entity = table
-                .query_single::<Camera>(manager)
-                .expect("No camera is initialized!")
-                .first()
-                .expect("No camera is initialized!");
+        render_frame(manager, table, display, Display::draw)
+    }
+
+    fn runs_while_paused(&self) -> bool {
+        true
+    }
+}
+
+impl System<HeadlessContext> for GlRenderSystem {
+    /// Renders the scene into the default framebuffer of a [HeadlessContext], see the `Display` variant.
+    fn update(
+        &mut self,
+        manager: &mut ecs::entity::EntityManager,
+        table: &mut ecs::entity::EntityQueryTable,
+        context: &HeadlessContext,
+    ) -> Option<()> {
+        render_frame(manager, table, context, HeadlessContext::draw)
+    }
+
+    fn runs_while_paused(&self) -> bool {
+        true
+    }
+}
+
+/// Draws the scene into the frame started by `draw`, shared by the window and the headless [GlRenderSystem].
+fn render_frame<F: Facade>(
+    manager: &mut ecs::entity::EntityManager,
+    table: &mut ecs::entity::EntityQueryTable,
+    display: &F,
+    draw: impl FnOnce(&F) -> Frame,
+) -> Option<()> {
+    // a scene without meshes is still cleared, and its sprites are still drawn.
+    let scene = prepare_scene(manager, table, display);
+    let config = match &scene {
+        Some(scene) => scene.config.clone(),
+        None => render_config(manager, table),
+    };
+    let bounds = bounds_lines(manager, table, display);
+
+    // all entities are drawn into a single frame, which is cleared once up front and presented once at the end.
+    let mut target = draw(display);
+
+    let viewport = config
+        .aspect_ratio
+        .map(|ratio| letterbox(target.get_dimensions(), ratio));
+
+    if viewport.is_some() {
+        // the margins around the viewport are never drawn to, so they're cleared to black.
+        ClearFlags::Color.clear(&mut target, None, (0.0, 0.0, 0.0, 1.0), 1.0);
+    }
+
+    config
+        .clear_flags
+        .clear(&mut target, viewport.as_ref(), clear_color(manager), 1.0);
 
-            let entity = *entity;
+    if let Some(scene) = scene {
+        draw_entities(
+            manager,
+            &mut target,
+            &scene.entities,
+            scene.view,
+            viewport,
+            bounds.as_ref(),
+        );
+        draw_colored_meshes(
+            manager,
+            &mut target,
+            &scene.colored_entities,
+            scene.view,
+            viewport,
+        );
+    }
+
+    // sprites are drawn on top of the whole screen, regardless of the viewport of the scene.
+    draw_sprites(manager, table, &mut target);
+
+    report(target.finish());
+
+    None
+}
+
+/// Reads the [RenderConfig] from the first entity holding one, or falls back to the default configuration.
+fn render_config(
+    manager: &mut ecs::entity::EntityManager,
+    table: &mut ecs::entity::EntityQueryTable,
+) -> RenderConfig {
+    table
+        .query_single::<RenderConfig>(manager)
+        .and_then(|entities| entities.first().copied())
+        .and_then(|entity| manager.query_entity::<RenderConfig>(entity).0.cloned())
+        .unwrap_or_default()
+}
+
+/// The state shared by all systems drawing the scene, see [prepare_scene].
+pub(crate) struct Scene {
+    pub config: RenderConfig,
+    pub view: Matrix4,
+    /// The mesh entities in the order they're drawn.
+    pub entities: Vec<usize>,
+    /// The entities with a [ColoredMesh], which are drawn after the meshes.
+    pub colored_entities: Vec<usize>,
+}
+
+/// Reads the camera and the [RenderConfig], brings the instance buffers up to date and orders the mesh entities for
+/// drawing.
+///
+/// Non-instanced meshes come first, sorted front to back if configured, followed by the instanced groups ordered by
+/// their render layer. Returns `None` if neither meshes nor colored meshes have been registered, in which case no
+/// camera is needed, or if there is no camera to draw them from, which is reported as [RenderError::MissingCamera].
+pub(crate) fn prepare_scene(
+    manager: &mut ecs::entity::EntityManager,
+    table: &mut ecs::entity::EntityQueryTable,
+    display: &impl Facade,
+) -> Option<Scene> {
+    let meshes = table.query_single::<Mesh>(manager).cloned();
+    let colored_entities = table.query_single::<ColoredMesh>(manager).cloned();
+
+    if meshes.is_none() && colored_entities.is_none() {
+        return None;
+    }
+
+    let Some((view, camera_position)) = camera_view(manager, table) else {
+        eprintln!("{}", RenderError::MissingCamera);
+        return None;
+    };
+    let config = render_config(manager, table);
+
+    let mut instances = HashMap::<usize, Vec<Instanced>>::new();
+
+    if let Some(manager) = manager.borrow_manager::<Instanced>() {
+        for instance in &manager.components {
+            instances
+                .entry(instance.mesh as usize)
+                .or_default()
+                .push(*instance);
+        }
+    }
+
+    // meshes whose instances are all culled are neither drawn instanced nor as a single mesh.
+    let instanced_meshes = instances.keys().copied().collect::<HashSet<_>>();
+    let view_projection =
+        active_perspective(manager, table).map(|perspective| multiply(view, perspective.matrix()));
+    let frustum = view_projection.map(Frustum::from_matrix);
+
+    if let Some(frustum) = &frustum {
+        cull_instances(manager, frustum, &mut instances);
+    }
 
-            let view = manager.query_entity::<Camera>(entity).0;
-            let view = view.expect("No camera is initialized!");
+    update_instance_buffers(
+        manager,
+        display,
+        &instances,
+        view_projection.map(|matrix| matrix.inner()),
+        config.max_instances_per_batch,
+    );
 
-            view.view_matrix()
+    let (instanced, entities): (Vec<usize>, Vec<usize>) = meshes
+        .unwrap_or_default()
+        .into_iter()
+        .partition(|entity| instanced_meshes.contains(entity));
+
+    let entities = match &frustum {
+        Some(frustum) => cull_meshes(manager, frustum, entities),
+        None => entities,
+    };
+
+    let (transparent, mut opaque): (Vec<usize>, Vec<usize>) = entities
+        .into_iter()
+        .partition(|entity| manager.query_entity::<Transparent>(*entity).0.is_some());
+
+    if config.sort_opaque {
+        let mut positioned = positions(manager, &opaque);
+        sort_front_to_back(&mut positioned, camera_position);
+        opaque = positioned.into_iter().map(|entry| entry.0).collect();
+    }
+
+    // instanced groups are drawn after the non-instanced meshes, ordered by their render layer.
+    let mut groups = vec![];
+
+    for entity in instanced
+        .into_iter()
+        .filter(|entity| instances.contains_key(entity))
+    {
+        let layer = manager.query_entity::<RenderLayer>(entity).0;
+        groups.push((entity, layer.map(|layer| layer.0).unwrap_or_default()));
+    }
+
+    sort_instanced_groups(&mut groups);
+
+    // transparent meshes come last, so they're blended over all opaque geometry.
+    let mut transparent = positions(manager, &transparent);
+    sort_back_to_front(&mut transparent, camera_position);
+
+    let mut entities = opaque;
+    entities.extend(groups.into_iter().map(|group| group.0));
+    entities.extend(transparent.into_iter().map(|entry| entry.0));
+
+    Some(Scene {
+        config,
+        view,
+        entities,
+        colored_entities: colored_entities.unwrap_or_default(),
+    })
+}
+
+/// Draws the `entities` into the `target`, which has to be cleared already.
+///
+/// An entity which can't be drawn is skipped and the error is logged, so the remaining entities are still drawn.
+pub(crate) fn draw_entities<S>(
+    manager: &mut ecs::entity::EntityManager,
+    target: &mut S,
+    entities: &[usize],
+    view: Matrix4,
+    viewport: Option<Rect>,
+    bounds: Option<&BoundsLines>,
+) where
+    S: Surface,
+{
+    for entity in entities {
+        let world = manager
+            .query_entity::<WorldTransform>(*entity)
+            .0
+            .map(|world| world.0);
+        let Some((mesh, uniform, draw_parameters, transform, debug_lines, instance_buffer)) = manager
+            .join_entity_six::<
+                Mesh,
+                Option<MeshUniform>,
+                Option<DrawParametersComponent>,
+                Option<Transform>,
+                Option<DebugLines>,
+                Option<InstanceBuffer>,
+            >(*entity)
+        else {
+            continue;
         };
 
-        for entity in table.query_single::<Mesh>(manager)? {
-            let mut target = display.draw();
-
-            let entries =
-                manager.query_entity_three::<Mesh, MeshUniform, DrawParametersComponent>(*entity);
-            let (mesh, uniform, draw_parameters) = (entries.0?, entries.1, entries.2);
-
-            let draw_parameters = match draw_parameters {
-                Some(value) => value.0.clone(),
-                None => Default::default(),
-            };
-
-            target.clear_color_and_depth((0.0, 0.0, 1.0, 1.0), 1.0);
-
-            match uniform {
-                Some(uniform) => {
-                    let uniform = uniform.view_matrix(view);
-
-                    target
-                        .draw(
-                            &mesh.vertex_buffer,
-                            mesh.index_buffer.clone(),
-                            &mesh.program,
-                            uniform,
-                            &draw_parameters,
-                        )
-                        .unwrap();
-                }
-                None => {
-                    target
-                        .draw(
-                            &mesh.vertex_buffer,
-                            mesh.index_buffer.clone(),
-                            &mesh.program,
-                            &EmptyUniforms,
-                            &draw_parameters,
-                        )
-                        .unwrap();
+        let draw_parameters = mesh_draw_parameters(draw_parameters, mesh.winding, viewport);
+
+        let mut fallback = None;
+        let uniform = resolve_uniform(uniform, &mut fallback, transform);
+
+        // the world matrix of a child in a hierarchy takes the place of its local transform.
+        if let Some(world) = world {
+            uniform.matrix(world);
+        }
+
+        default_matrix(uniform);
+        let uniform = uniform.view_matrix(view);
+
+        match instance_buffer {
+            Some(instance_buffer) => {
+                // every batch of instances is drawn with its own draw call.
+                for buffer in &instance_buffer.buffers {
+                    let Ok(per_instance) = buffer.per_instance() else {
+                        eprintln!("Instancing is not supported, skipping entity {}", entity);
+                        break;
+                    };
+
+                    report(draw_mesh(
+                        target,
+                        mesh,
+                        (&mesh.vertex_buffer, per_instance),
+                        uniform,
+                        &draw_parameters,
+                    ));
                 }
             }
+            None => {
+                let vertices = &mesh.vertex_buffer;
 
-            target.finish().unwrap();
+                report(draw_mesh(target, mesh, vertices, uniform, &draw_parameters));
+            }
+        }
+
+        if let Some(lines) = debug_lines.filter(|lines| lines.enabled) {
+            report(target.draw(
+                &lines.vertex_buffer,
+                NoIndices(PrimitiveType::LinesList),
+                &lines.program,
+                uniform,
+                &draw_parameters,
+            ));
         }
 
-        None
+        if let Some((program, lines)) = bounds {
+            if let Some(lines) = lines.get(entity) {
+                report(target.draw(
+                    lines,
+                    NoIndices(PrimitiveType::LinesList),
+                    program,
+                    uniform,
+                    &draw_parameters,
+                ));
+            }
+        }
     }
 }
 
-impl System<Display> for InternalTransformSystem {
-    fn update(
-        &mut self,
-        manager: &mut ecs::entity::EntityManager,
-        table: &mut ecs::entity::EntityQueryTable,
-        _: &Display,
-    ) -> Option<()> {
-        for entity in table.query::<(Transform, MeshUniform)>(manager)? {
-            let entry = manager.query_entity_two::<Transform, MeshUniform>(entity);
-            let (transform, mesh) = (entry.0?, entry.1?);
+/// Draws the entities with a [ColoredMesh] into the `target`, like [draw_entities] draws the meshes.
+pub(crate) fn draw_colored_meshes<S>(
+    manager: &mut ecs::entity::EntityManager,
+    target: &mut S,
+    entities: &[usize],
+    view: Matrix4,
+    viewport: Option<Rect>,
+) where
+    S: Surface,
+{
+    for entity in entities {
+        let world = manager
+            .query_entity::<WorldTransform>(*entity)
+            .0
+            .map(|world| world.0);
+        let Some((mesh, uniform, draw_parameters, transform)) = manager.join_entity_four::<
+            ColoredMesh,
+            Option<MeshUniform>,
+            Option<DrawParametersComponent>,
+            Option<Transform>,
+        >(*entity) else {
+            continue;
+        };
+
+        let draw_parameters = mesh_draw_parameters(draw_parameters, mesh.winding, viewport);
+
+        let mut fallback = None;
+        let uniform = resolve_uniform(uniform, &mut fallback, transform);
+
+        if let Some(world) = world {
+            uniform.matrix(world);
+        }
+
+        default_matrix(uniform);
+        let uniform = uniform.view_matrix(view);
+
+        report(target.draw(
+            &mesh.vertex_buffer,
+            mesh.index_buffer.source(),
+            &mesh.program,
+            uniform,
+            &draw_parameters,
+        ));
+    }
+}
+
+/// Returns the draw parameters of an entity, adjusted to the winding of its mesh and the viewport of the scene.
+fn mesh_draw_parameters(
+    draw_parameters: Option<&mut DrawParametersComponent>,
+    winding: Winding,
+    viewport: Option<Rect>,
+) -> DrawParameters<'static> {
+    let mut draw_parameters = match draw_parameters {
+        Some(value) => value.0.clone(),
+        None => Default::default(),
+    };
 
-            mesh.transform(transform);
+    draw_parameters.backface_culling = winding.culling(draw_parameters.backface_culling);
+    draw_parameters.viewport = viewport.or(draw_parameters.viewport);
+
+    draw_parameters
+}
+
+/// Drops the instances whose mesh has a [BoundingSphere] and which lie outside of the `frustum`, see
+/// [BoundingSphere::around_instance]. Meshes without a bounding sphere keep all their instances, and meshes without
+/// any visible instances are removed.
+pub(crate) fn cull_instances(
+    manager: &mut ecs::entity::EntityManager,
+    frustum: &Frustum,
+    instances: &mut HashMap<usize, Vec<Instanced>>,
+) {
+    instances.retain(|mesh, instances| {
+        if let Some(sphere) = manager.query_entity::<BoundingSphere>(*mesh).0 {
+            instances.retain(|instance| {
+                let bounds = sphere.around_instance(instance);
+                frustum.contains_sphere(bounds.center, bounds.radius)
+            });
         }
 
-        None
+        !instances.is_empty()
+    });
+}
+
+/// Marks the meshes with a [BoundingSphere] outside of the `frustum` as [Culled], and returns the remaining ones.
+///
+/// Meshes which are visible again lose their marker.
+pub(crate) fn cull_meshes(
+    manager: &mut ecs::entity::EntityManager,
+    frustum: &Frustum,
+    entities: Vec<usize>,
+) -> Vec<usize> {
+    let mut visible = vec![];
+
+    for entity in entities {
+        let sphere = manager.query_entity::<BoundingSphere>(entity).0.copied();
+        let bounds = match (sphere, model_matrix(manager, entity)) {
+            (Some(sphere), Some(matrix)) => Some(sphere.transformed(&matrix)),
+            (sphere, None) => sphere,
+            (None, _) => None,
+        };
+
+        match bounds {
+            Some(bounds) if !frustum.contains_sphere(bounds.center, bounds.radius) => {
+                manager.entity_with(entity, Culled);
+            }
+            _ => {
+                manager.remove_component::<Culled>(entity);
+                visible.push(entity);
+            }
+        }
+    }
+
+    visible
+}
+
+/// Brings the [InstanceBuffer] of every mesh up to date with its instances, split into batches of at most
+/// `max_per_batch` instances.
+///
+/// The buffers are kept on the mesh entities across frames, so a static set of instances is only uploaded once, see
+/// [InstanceUpload::of]. `frustum` is the view projection matrix the instances were culled with. Meshes which lost all
+/// their instances lose their buffers as well.
+fn update_instance_buffers(
+    manager: &mut ecs::entity::EntityManager,
+    display: &impl Facade,
+    instances: &HashMap<usize, Vec<Instanced>>,
+    frustum: Option<[[f32; 4]; 4]>,
+    max_per_batch: usize,
+) {
+    let stale = manager
+        .query_entity_ids::<InstanceBuffer>()
+        .map(|entities| {
+            entities
+                .iter()
+                .filter(|entity| !instances.contains_key(entity))
+                .copied()
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    for entity in stale {
+        manager.remove_component::<InstanceBuffer>(entity);
+    }
+
+    // culling depends on the bounding spheres of the meshes as well, so their changes count as changes to the instances.
+    let changed = manager
+        .get_updated_frame::<Instanced>()
+        .max(manager.get_updated_frame::<BoundingSphere>());
+    let frame = manager.frame();
+
+    for (mesh, instances) in instances {
+        let batches = instance_batches(instances.len(), max_per_batch).collect::<Vec<_>>();
+        let lengths = batches.iter().map(|batch| batch.len()).collect::<Vec<_>>();
+        let cached = manager.query_entity::<InstanceBuffer>(*mesh).0;
+
+        match (
+            InstanceUpload::of(cached.as_deref(), changed, frustum, &lengths),
+            cached,
+        ) {
+            (InstanceUpload::Reuse, _) => {}
+            (InstanceUpload::Write, Some(cached)) => {
+                for (buffer, batch) in cached.buffers.iter().zip(batches) {
+                    buffer.write(&instances[batch]);
+                }
+
+                cached.changed = changed;
+                cached.frustum = frustum;
+                cached.uploaded = frame;
+            }
+            (_, cached) => match batches
+                .into_iter()
+                .map(|batch| VertexBuffer::dynamic(display, &instances[batch]))
+                .collect::<Result<Vec<_>, _>>()
+            {
+                Ok(buffers) => {
+                    let created = InstanceBuffer {
+                        buffers,
+                        changed,
+                        frustum,
+                        uploaded: frame,
+                    };
+
+                    match cached {
+                        Some(cached) => *cached = created,
+                        None => {
+                            manager.entity_with(*mesh, created);
+                        }
+                    }
+                }
+                Err(error) => eprintln!("{}", RenderError::from(error)),
+            },
+        }
+    }
+}
+
+/// The line program and the uploaded bounding volumes of every entity, see [bounds_lines].
+pub(crate) type BoundsLines = (Rc<Program>, HashMap<usize, VertexBuffer<ColoredVertex>>);
+
+/// Uploads the bounding volumes queued in [BoundsDebug], if the bounds are enabled.
+///
+/// The line program is compiled the first time the bounds are drawn, and kept in the component afterwards.
+pub(crate) fn bounds_lines(
+    manager: &mut ecs::entity::EntityManager,
+    table: &mut ecs::entity::EntityQueryTable,
+    display: &impl Facade,
+) -> Option<BoundsLines> {
+    let entity = *table.query_first_single::<BoundsDebug>(manager)?;
+    let debug = manager.query_entity::<BoundsDebug>(entity).0?;
+
+    if !debug.enabled || debug.queued.is_empty() {
+        return None;
+    }
+
+    if debug.program.is_none() {
+        let program =
+            Program::from_source(display, LINE_VERTEX_SHADER, LINE_FRAGMENT_SHADER, None).ok()?;
+        debug.program = Some(Rc::new(program));
+    }
+
+    let mut lines = HashMap::new();
+
+    for (entity, queued) in &debug.queued {
+        lines.insert(*entity, ColoredVertex::to_buffer(display, queued).ok()?);
+    }
+
+    Some((debug.program.clone()?, lines))
+}
+
+/// Returns the model matrix of an entity, taken from its `WorldTransform`, its `Transform` or else its `MeshUniform`.
+pub(crate) fn model_matrix(
+    manager: &mut ecs::entity::EntityManager,
+    entity: usize,
+) -> Option<Matrix4> {
+    if let Some(world) = manager.query_entity::<WorldTransform>(entity).0 {
+        return Some(world.0);
+    }
+
+    match manager.query_entity_two::<Transform, MeshUniform>(entity) {
+        (Some(transform), _) => Some(transform.matrix),
+        (None, Some(uniform)) => uniform.ref_matrix().copied(),
+        (None, None) => None,
+    }
+}
+
+/// Pairs the entities with the translation of their model matrix, see [model_matrix]. Entities without a model matrix
+/// are placed at the origin.
+pub(crate) fn positions(
+    manager: &mut ecs::entity::EntityManager,
+    entities: &[usize],
+) -> Vec<(usize, Vec3)> {
+    entities
+        .iter()
+        .map(|entity| {
+            let position = model_matrix(manager, *entity).map(|matrix| matrix.translation());
+            (*entity, position.unwrap_or(Vec3::new(0.0, 0.0, 0.0)))
+        })
+        .collect()
+}
+
+/// The squared distance between two points, which orders the same as the distance itself.
+fn distance_squared(position: &Vec3, camera: Vec3) -> f32 {
+    let (x, y, z) = (
+        position[0] - camera[0],
+        position[1] - camera[1],
+        position[2] - camera[2],
+    );
+
+    x * x + y * y + z * z
+}
+
+/// Sorts the entities by their distance to the camera, nearest first.
+///
+/// Drawing opaque geometry front to back lets the depth test discard occluded fragments early, which reduces the
+/// amount of fragment shader invocations.
+pub(crate) fn sort_front_to_back(entities: &mut [(usize, Vec3)], camera: Vec3) {
+    entities
+        .sort_by(|a, b| distance_squared(&a.1, camera).total_cmp(&distance_squared(&b.1, camera)));
+}
+
+/// Sorts the entities by their distance to the camera, farthest first.
+///
+/// Blended geometry has to be drawn back to front, so every transparent mesh is composited over the meshes behind it.
+pub(crate) fn sort_back_to_front(entities: &mut [(usize, Vec3)], camera: Vec3) {
+    entities
+        .sort_by(|a, b| distance_squared(&b.1, camera).total_cmp(&distance_squared(&a.1, camera)));
+}
+
+/// Sorts instanced groups by their render layer, lowest first. Groups on the same layer are ordered by the entity
+/// of their mesh, so the draw order is deterministic.
+pub(crate) fn sort_instanced_groups(groups: &mut [(usize, i32)]) {
+    groups.sort_by_key(|(entity, layer)| (*layer, *entity));
+}
+
+/// Picks the uniform an entity is drawn with.
+///
+/// Entities without a `MeshUniform` are drawn with an empty uniform, which is only created in `fallback` for them. The
+/// `Transform` of an entity is authoritative: if the entity has one, the model matrix of the uniform is always taken
+/// from it. The matrix of a `MeshUniform` is only used for entities without a `Transform`.
+pub(crate) fn resolve_uniform<'a>(
+    uniform: Option<&'a mut MeshUniform>,
+    fallback: &'a mut Option<MeshUniform>,
+    transform: Option<&mut Transform>,
+) -> &'a mut MeshUniform {
+    let uniform = match uniform {
+        Some(uniform) => uniform,
+        None => fallback.insert(MeshUniform::empty()),
+    };
+
+    if let Some(transform) = transform {
+        uniform.transform(transform);
+    }
+
+    uniform
+}
+
+/// Whether the missing model matrix warning of [default_matrix] has been logged already.
+static MISSING_MATRIX_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Falls back to the identity matrix if the uniform has no model matrix, so the `matrix` uniform is always emitted.
+///
+/// A missing matrix usually means the uniform was created with [MeshUniform::empty] and the entity has no `Transform`,
+/// which is logged once.
+pub(crate) fn default_matrix(uniform: &mut MeshUniform) {
+    if uniform.ref_matrix().is_some() {
+        return;
+    }
+
+    if !MISSING_MATRIX_WARNED.swap(true, Ordering::Relaxed) {
+        eprintln!("A mesh without a model matrix is drawn, falling back to the identity matrix");
+    }
+
+    uniform.matrix(Matrix4::identity());
+}
+
+/// Draws a single mesh into the `target`, using the given vertex source.
+fn draw_mesh<'a, S, V>(
+    target: &mut S,
+    mesh: &Mesh,
+    vertices: V,
+    uniform: &MeshUniform,
+    draw_parameters: &DrawParameters,
+) -> Result<(), RenderError>
+where
+    S: Surface,
+    V: MultiVerticesSource<'a>,
+{
+    target.draw(
+        vertices,
+        mesh.index_buffer.source(),
+        &mesh.program,
+        uniform,
+        draw_parameters,
+    )?;
+
+    Ok(())
+}
+
+/// Logs a failed draw or swap instead of aborting the frame, so a single broken mesh doesn't take down the engine.
+pub(crate) fn report<E>(result: Result<(), E>)
+where
+    E: Into<RenderError>,
+{
+    if let Err(error) = result {
+        eprintln!("{}", error.into());
+    }
+}
+
+/// The priority the render systems are registered with by [RenderSystems::add_render_systems], see
+/// `World::with_system_ordered`.
+pub const RENDER_PRIORITY: i32 = 1000;
+
+/// Extension trait for attaching the internal render systems to a [World].
+pub trait RenderSystems {
+    /// Registers [RenderTargetSystem], [SpriteRenderSystem] and [GlRenderSystem] as loop systems with
+    /// [RENDER_PRIORITY], so the render targets are drawn and the sprites batched before the frame.
+    ///
+    /// Systems which mutate a `Transform` run before the render systems as long as their priority is lower, so their
+    /// changes are drawn in the same frame regardless of the registration order.
+    fn add_render_systems(&mut self) -> &mut Self;
+}
+
+impl RenderSystems for World<Display> {
+    fn add_render_systems(&mut self) -> &mut Self {
+        self.with_system_ordered(SystemType::Loop, RENDER_PRIORITY, RenderTargetSystem)
+            .with_system_ordered(SystemType::Loop, RENDER_PRIORITY, SpriteRenderSystem::new())
+            .with_system_ordered(SystemType::Loop, RENDER_PRIORITY, GlRenderSystem)
+    }
+}
+
+impl RenderSystems for World<HeadlessContext> {
+    fn add_render_systems(&mut self) -> &mut Self {
+        self.with_system_ordered(SystemType::Loop, RENDER_PRIORITY, RenderTargetSystem)
+            .with_system_ordered(SystemType::Loop, RENDER_PRIORITY, SpriteRenderSystem::new())
+            .with_system_ordered(SystemType::Loop, RENDER_PRIORITY, GlRenderSystem)
     }
 }
@@ -1,4 +1,8 @@
+pub mod config;
 pub mod delta;
+pub mod hierarchy;
+pub mod instanced;
 pub mod internal;
+pub mod target;
 pub mod transform;
 pub mod vertex;
@@ -0,0 +1,158 @@
+use std::rc::Rc;
+
+use ecs::{
+    entity::{EntityManager, EntityQueryTable},
+    system::System,
+};
+use ecs_macro::EntityComponent;
+use glium::{
+    backend::{Context, Facade},
+    framebuffer::SimpleFrameBuffer,
+    texture::DepthTexture2d,
+    Surface, Texture2d,
+};
+
+use crate::error::RenderError;
+
+use super::internal::{bounds_lines, draw_colored_meshes, draw_entities, prepare_scene};
+
+/// A texture the scene is rendered into instead of the screen, e.g. for shadow maps or post-processing.
+///
+/// Entities holding a `RenderTarget` are drawn into by [RenderTargetSystem] every frame. The textures are reference
+/// counted, so cloning a target shares them.
+#[derive(EntityComponent, Clone)]
+pub struct RenderTarget {
+    context: Rc<Context>,
+    color: Rc<Texture2d>,
+    depth: Option<Rc<DepthTexture2d>>,
+    /// The color the target is cleared to before the scene is drawn into it.
+    pub clear_color: (f32, f32, f32, f32),
+}
+
+impl RenderTarget {
+    /// Creates a target with an empty color texture of the given `dimensions`, see [target_dimensions].
+    pub fn new(display: &impl Facade, dimensions: (u32, u32)) -> Result<Self, RenderError> {
+        let (width, height) = target_dimensions(dimensions);
+
+        Ok(Self {
+            context: display.get_context().clone(),
+            color: Rc::new(Texture2d::empty(display, width, height)?),
+            depth: None,
+            clear_color: (0.0, 0.0, 0.0, 1.0),
+        })
+    }
+
+    /// Adds a depth attachment of the same size as the color texture, which is required for depth testing.
+    pub fn with_depth(mut self, display: &impl Facade) -> Result<Self, RenderError> {
+        let (width, height) = self.dimensions();
+        self.depth = Some(Rc::new(DepthTexture2d::empty(display, width, height)?));
+        Ok(self)
+    }
+
+    pub fn clear_color(mut self, color: (f32, f32, f32, f32)) -> Self {
+        self.clear_color = color;
+        self
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.color.dimensions()
+    }
+
+    /// The texture holding the rendered colors.
+    pub fn texture(&self) -> &Texture2d {
+        &self.color
+    }
+
+    /// The texture holding the rendered depth, if the target has a depth attachment.
+    pub fn depth_texture(&self) -> Option<&DepthTexture2d> {
+        self.depth.as_deref()
+    }
+
+    /// Draws into the target through a framebuffer, which is handed to `draw`.
+    ///
+    /// Returns an error if the textures can't be attached to a framebuffer.
+    pub fn draw_to<F, R>(&mut self, draw: F) -> Result<R, RenderError>
+    where
+        F: FnOnce(&mut SimpleFrameBuffer) -> R,
+    {
+        let mut framebuffer = match &self.depth {
+            Some(depth) => {
+                SimpleFrameBuffer::with_depth_buffer(&self.context, &*self.color, &**depth)?
+            }
+            None => SimpleFrameBuffer::new(&self.context, &*self.color)?,
+        };
+
+        Ok(draw(&mut framebuffer))
+    }
+
+    /// Reads the color of the pixel at `x`, `y`, counted from the bottom left corner.
+    pub fn read_pixel(&self, x: u32, y: u32) -> Option<(u8, u8, u8, u8)> {
+        let rows: Vec<Vec<(u8, u8, u8, u8)>> = self.color.read();
+        pixel_at(&rows, x, y)
+    }
+}
+
+/// Clamps the dimensions of a target to at least one pixel, as a texture can't be empty.
+pub fn target_dimensions(dimensions: (u32, u32)) -> (u32, u32) {
+    (dimensions.0.max(1), dimensions.1.max(1))
+}
+
+/// Returns the pixel at `x`, `y` of an image read back from the GPU, whose rows start at the bottom.
+pub(crate) fn pixel_at<P: Copy>(rows: &[Vec<P>], x: u32, y: u32) -> Option<P> {
+    rows.get(y as usize)?.get(x as usize).copied()
+}
+
+/// Draws the scene into every [RenderTarget], the same way `GlRenderSystem` draws it to the screen.
+///
+/// Should be registered before `GlRenderSystem`, so the targets are up to date when they're sampled on the screen.
+pub struct RenderTargetSystem;
+
+impl<F: Facade> System<F> for RenderTargetSystem {
+    fn update(
+        &mut self,
+        manager: &mut EntityManager,
+        table: &mut EntityQueryTable,
+        display: &F,
+    ) -> Option<()> {
+        let targets = table.query_single::<RenderTarget>(manager)?.clone();
+        let scene = prepare_scene(manager, table, display)?;
+        let bounds = bounds_lines(manager, table, display);
+
+        for entity in targets {
+            let Some(mut target) = manager.query_entity::<RenderTarget>(entity).0.cloned() else {
+                continue;
+            };
+
+            let color = target.clear_color;
+            let depth = target.depth.is_some().then_some(1.0);
+            let result = target.draw_to(|framebuffer| {
+                framebuffer.clear(None, Some(color), false, depth, None);
+                draw_entities(
+                    manager,
+                    framebuffer,
+                    &scene.entities,
+                    scene.view,
+                    None,
+                    bounds.as_ref(),
+                );
+                draw_colored_meshes(
+                    manager,
+                    framebuffer,
+                    &scene.colored_entities,
+                    scene.view,
+                    None,
+                );
+            });
+
+            if let Err(error) = result {
+                eprintln!("{}", error);
+            }
+        }
+
+        None
+    }
+
+    fn runs_while_paused(&self) -> bool {
+        true
+    }
+}
@@ -1,11 +1,67 @@
-use crate::container::Matrix4;
+use crate::container::{Matrix4, Quaternion};
 
 use ecs_macro::EntityComponent;
-use glium::DrawParameters;
+use glium::{
+    draw_parameters::DepthTest, Blend, BlendingFunction, Depth, DrawParameters,
+    LinearBlendingFactor,
+};
 
 #[derive(EntityComponent)]
 pub struct DrawParametersComponent(pub DrawParameters<'static>);
 
+impl DrawParametersComponent {
+    /// Draws opaque geometry, which is depth tested and writes to the depth buffer like the meshes of the demos.
+    pub fn opaque() -> Self {
+        Self(DrawParameters {
+            depth: depth(true),
+            ..Default::default()
+        })
+    }
+
+    /// Blends the geometry over the frame by its alpha, see `Blend::alpha_blending`.
+    ///
+    /// The geometry is still depth tested, but doesn't write to the depth buffer, so it doesn't hide transparent
+    /// geometry drawn after it. Entities using this should also be marked `Transparent`, so they're drawn back to front.
+    pub fn alpha_blend() -> Self {
+        Self(DrawParameters {
+            depth: depth(false),
+            blend: Blend::alpha_blending(),
+            ..Default::default()
+        })
+    }
+
+    /// Adds the color of the geometry, weighted by its alpha, to the frame, as used for glows and particles.
+    ///
+    /// The alpha of the frame is kept, and like [DrawParametersComponent::alpha_blend] the geometry doesn't write to
+    /// the depth buffer.
+    pub fn additive() -> Self {
+        Self(DrawParameters {
+            depth: depth(false),
+            blend: Blend {
+                color: BlendingFunction::Addition {
+                    source: LinearBlendingFactor::SourceAlpha,
+                    destination: LinearBlendingFactor::One,
+                },
+                alpha: BlendingFunction::Addition {
+                    source: LinearBlendingFactor::Zero,
+                    destination: LinearBlendingFactor::One,
+                },
+                constant_value: (0.0, 0.0, 0.0, 0.0),
+            },
+            ..Default::default()
+        })
+    }
+}
+
+/// Returns the depth test used by the presets of [DrawParametersComponent].
+fn depth(write: bool) -> Depth {
+    Depth {
+        test: DepthTest::IfLess,
+        write,
+        ..Default::default()
+    }
+}
+
 #[derive(EntityComponent)]
 pub struct Transform {
     pub matrix: Matrix4,
@@ -19,18 +75,46 @@ impl From<[[f32; 4]; 4]> for Transform {
     }
 }
 
+/// Builds a transform which only rotates.
+impl From<Quaternion> for Transform {
+    fn from(value: Quaternion) -> Self {
+        Self {
+            matrix: value.to_matrix4(),
+        }
+    }
+}
+
 impl Transform {
+    /// Creates a transform which doesn't move, rotate or scale, the same as [Transform::identity].
     pub fn new() -> Self {
+        Self::identity()
+    }
+
+    pub fn identity() -> Self {
         Self {
-            matrix: Matrix4::from([
-                [1.0, 0.0, 0.0, 0.0],
-                [0.0, 1.0, 0.0, 0.0],
-                [0.0, 0.0, 1.0, 0.0],
-                [0.0, 0.0, 0.0, 1.0],
-            ]),
+            matrix: Matrix4::identity(),
         }
     }
 
+    /// Translates the transform. The translation is stored in the last column of the matrix (`matrix[3]`), where
+    /// [Matrix4::translation] reads it from, see [Matrix4::translate].
+    pub fn translate(&mut self, x: f32, y: f32, z: f32) -> &mut Self {
+        self.matrix.translate(x, y, z);
+        self
+    }
+
+    /// Rotates the transform by `angle` radians around `axis`, see [Matrix4::rotate].
+    pub fn rotate(&mut self, angle: f32, axis: (f32, f32, f32)) -> &mut Self {
+        self.matrix.rotate(angle, axis);
+        self
+    }
+
+    /// Scales the transform along each axis, see [Matrix4::scale].
+    pub fn scale(&mut self, x: f32, y: f32, z: f32) -> &mut Self {
+        self.matrix.scale(x, y, z);
+        self
+    }
+
     pub fn ref_matrix(&mut self) -> &mut Matrix4 {
         &mut self.matrix
     }
@@ -39,3 +123,9 @@ impl Transform {
         self.matrix.inner()
     }
 }
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
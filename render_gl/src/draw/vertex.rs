@@ -1,8 +1,14 @@
-use glium::{implement_vertex, vertex::BufferCreationError, Display, VertexBuffer};
+use std::{error::Error, fmt};
+
+use glium::{
+    backend::Facade, implement_vertex, vertex::BufferCreationError, Display, VertexBuffer,
+};
+
+use crate::{container::Vec3, error::RenderError, mesh::check_indices};
 
 pub trait ToBuffer: Sized + Copy {
     fn to_buffer(
-        display: &Display,
+        display: &impl Facade,
         shape: &[Self],
     ) -> Result<VertexBuffer<Self>, BufferCreationError>;
 }
@@ -14,68 +20,266 @@ pub struct Vertex {
     pub normal: [f32; 3],
 }
 
+/// The error returned when building the vertices of a mesh from separate attribute slices.
+#[derive(Debug)]
+pub enum VertexBuildError {
+    /// An attribute slice doesn't have one entry for every vertex.
+    LengthMismatch {
+        /// The name of the attribute, e.g. `"normals"`.
+        attribute: &'static str,
+        vertices: usize,
+        actual: usize,
+    },
+    /// The vertex buffer couldn't be created.
+    Buffer(BufferCreationError),
+}
+
+impl fmt::Display for VertexBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VertexBuildError::LengthMismatch {
+                attribute,
+                vertices,
+                actual,
+            } => write!(
+                f,
+                "expected {} {} for {} vertices, got {}",
+                vertices, attribute, vertices, actual
+            ),
+            VertexBuildError::Buffer(error) => {
+                write!(f, "failed to create vertex buffer: {}", error)
+            }
+        }
+    }
+}
+
+impl Error for VertexBuildError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            VertexBuildError::LengthMismatch { .. } => None,
+            VertexBuildError::Buffer(error) => Some(error),
+        }
+    }
+}
+
+impl From<BufferCreationError> for VertexBuildError {
+    fn from(error: BufferCreationError) -> Self {
+        VertexBuildError::Buffer(error)
+    }
+}
+
+impl From<VertexBuildError> for RenderError {
+    fn from(error: VertexBuildError) -> Self {
+        match error {
+            VertexBuildError::LengthMismatch {
+                vertices, actual, ..
+            } => RenderError::VertexCountMismatch {
+                expected: vertices,
+                actual,
+            },
+            VertexBuildError::Buffer(error) => RenderError::VertexBuffer(error),
+        }
+    }
+}
+
+/// Returns an error if the `attribute` slice of length `actual` doesn't have an entry for each of the `vertices`.
+fn check_length(
+    attribute: &'static str,
+    vertices: usize,
+    actual: usize,
+) -> Result<(), VertexBuildError> {
+    if vertices != actual {
+        return Err(VertexBuildError::LengthMismatch {
+            attribute,
+            vertices,
+            actual,
+        });
+    }
+
+    Ok(())
+}
+
 impl Vertex {
-    pub fn from_vertices(
-        display: &Display,
+    /// Zips the positions and normals into vertices without texture coordinates.
+    pub fn build_vertices(
         vertices: &[(f32, f32, f32)],
         normals: &[(f32, f32, f32)],
-    ) -> VertexBuffer<Vertex> {
-        if vertices.len() != normals.len() {
-            // todo: proper error handling
-            panic!("Vertices and normals should be the same length!");
-        };
+    ) -> Result<Vec<Vertex>, VertexBuildError> {
+        check_length("normals", vertices.len(), normals.len())?;
 
-        let mut vertex_vec = Vec::<Vertex>::new();
-
-        for i in 0..vertices.len() {
-            let position = vertices[i];
-            let normal = normals[i];
-
-            let vertex = Vertex {
+        let vertices = vertices
+            .iter()
+            .zip(normals)
+            .map(|(position, normal)| Vertex {
                 position: [position.0, position.1, position.2],
                 normal: [normal.0, normal.1, normal.2],
                 tex_pos: [0.0, 0.0],
-            };
+            })
+            .collect();
+
+        Ok(vertices)
+    }
+
+    /// Zips the positions, normals and texture coordinates into vertices.
+    pub fn build_vertices_with_tex(
+        vertices: &[(f32, f32, f32)],
+        normals: &[(f32, f32, f32)],
+        tex_pos: &[(f32, f32)],
+    ) -> Result<Vec<Vertex>, VertexBuildError> {
+        check_length("texture coordinates", vertices.len(), tex_pos.len())?;
 
-            vertex_vec.push(vertex);
+        let mut built = Self::build_vertices(vertices, normals)?;
+
+        for (vertex, tex_pos) in built.iter_mut().zip(tex_pos) {
+            vertex.tex_pos = [tex_pos.0, tex_pos.1];
         }
 
-        Self::to_buffer(display, &vertex_vec).unwrap()
+        Ok(built)
     }
 
+    /// Uploads the vertices built by [Vertex::build_vertices].
+    pub fn from_vertices(
+        display: &Display,
+        vertices: &[(f32, f32, f32)],
+        normals: &[(f32, f32, f32)],
+    ) -> Result<VertexBuffer<Vertex>, VertexBuildError> {
+        let vertices = Self::build_vertices(vertices, normals)?;
+        Ok(Self::to_buffer(display, &vertices)?)
+    }
+
+    /// Uploads the vertices built by [Vertex::build_vertices_with_tex].
     pub fn from_vertices_with_tex(
         display: &Display,
         vertices: &[(f32, f32, f32)],
         normals: &[(f32, f32, f32)],
         tex_pos: &[(f32, f32)],
-    ) -> VertexBuffer<Vertex> {
-        if (vertices.len() != normals.len()) || (vertices.len() != tex_pos.len()) {
-            // todo: proper error handling
-            panic!("Vertices, texture position and normals should be the same length!");
-        };
+    ) -> Result<VertexBuffer<Vertex>, VertexBuildError> {
+        let vertices = Self::build_vertices_with_tex(vertices, normals, tex_pos)?;
+        Ok(Self::to_buffer(display, &vertices)?)
+    }
 
-        let mut vertex_vec = Vec::<Vertex>::new();
+    /// Creates the vertices of indexed triangles without normals, which are computed by [compute_normals].
+    pub fn from_positions_indexed(
+        display: &Display,
+        positions: &[(f32, f32, f32)],
+        indices: &[u32],
+    ) -> Result<VertexBuffer<Vertex>, RenderError> {
+        let normals = compute_normals(positions, indices)?;
 
-        for i in 0..vertices.len() {
-            let texture_position = tex_pos[i];
-            let position = vertices[i];
-            let normal = normals[i];
+        Ok(Self::from_vertices(display, positions, &normals)?)
+    }
+}
 
-            let vertex = Vertex {
-                position: [position.0, position.1, position.2],
-                normal: [normal.0, normal.1, normal.2],
-                tex_pos: [texture_position.0, texture_position.1],
-            };
+/// Computes a normal for every position, by averaging the face normals of the triangles (three indices each) which
+/// share it. Larger triangles weigh more, as their face normals aren't normalized before averaging.
+///
+/// Positions which aren't part of any triangle get a zero normal.
+pub fn compute_normals(
+    positions: &[(f32, f32, f32)],
+    indices: &[u32],
+) -> Result<Vec<(f32, f32, f32)>, RenderError> {
+    check_indices(indices, positions.len())?;
 
-            vertex_vec.push(vertex);
-        }
+    let positions = positions
+        .iter()
+        .map(|position| Vec3::new(position.0, position.1, position.2))
+        .collect::<Vec<_>>();
+    let mut normals = vec![Vec3::new(0.0, 0.0, 0.0); positions.len()];
 
-        Self::to_buffer(display, &vertex_vec).unwrap()
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| index as usize);
+        let face = (positions[b] - positions[a]).cross(positions[c] - positions[a]);
+
+        for index in [a, b, c] {
+            normals[index] = normals[index] + face;
+        }
     }
+
+    Ok(normals
+        .into_iter()
+        .map(|normal| {
+            let normal = normal.normalize();
+            (normal[0], normal[1], normal[2])
+        })
+        .collect())
 }
 
 implement_vertex!(Vertex, position, tex_pos, normal);
 
+/// A [Vertex] with a tangent, which together with the normal spans the tangent space normal maps are expressed in.
+///
+/// The bitangent isn't stored, shaders can reconstruct it as `cross(normal, tangent)`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TangentVertex {
+    pub position: [f32; 3],
+    pub tex_pos: [f32; 2],
+    pub normal: [f32; 3],
+    pub tangent: [f32; 3],
+}
+
+implement_vertex!(TangentVertex, position, tex_pos, normal, tangent);
+
+/// Computes the tangents of indexed triangles (three indices each) from their positions and texture coordinates.
+///
+/// The tangents of all triangles sharing a vertex are accumulated, and the sum is made orthogonal to the vertex's
+/// normal and normalized. Vertices which aren't part of any triangle, or whose texture coordinates are degenerate,
+/// get a zero tangent.
+pub fn compute_tangents(
+    vertices: &[Vertex],
+    indices: &[u32],
+) -> Result<Vec<TangentVertex>, RenderError> {
+    check_indices(indices, vertices.len())?;
+
+    let mut tangents = vec![Vec3::new(0.0, 0.0, 0.0); vertices.len()];
+
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| index as usize);
+        let [pa, pb, pc] = [a, b, c].map(|index| Vec3::from(vertices[index].position));
+        let [ta, tb, tc] = [a, b, c].map(|index| vertices[index].tex_pos);
+
+        let (edge1, edge2) = (pb - pa, pc - pa);
+        let (du1, dv1) = (tb[0] - ta[0], tb[1] - ta[1]);
+        let (du2, dv2) = (tc[0] - ta[0], tc[1] - ta[1]);
+
+        let determinant = du1 * dv2 - du2 * dv1;
+
+        if determinant == 0.0 {
+            continue;
+        }
+
+        let tangent = (edge1 * dv2 - edge2 * dv1) * (1.0 / determinant);
+
+        for index in [a, b, c] {
+            tangents[index] = tangents[index] + tangent;
+        }
+    }
+
+    Ok(vertices
+        .iter()
+        .zip(tangents)
+        .map(|(vertex, tangent)| {
+            let normal = Vec3::from(vertex.normal);
+            let tangent = (tangent - normal * normal.dot(tangent)).normalize();
+
+            TangentVertex {
+                position: vertex.position,
+                tex_pos: vertex.tex_pos,
+                normal: vertex.normal,
+                tangent: tangent.inner(),
+            }
+        })
+        .collect())
+}
+
+/// A vertex carrying a color instead of texture coordinates, used for debug geometry such as lines.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ColoredVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 3],
+}
+
+implement_vertex!(ColoredVertex, position, color);
+
 #[macro_export]
 macro_rules! vertex {
     ([$x:expr, $y:expr, $z:expr], [$xt:expr, $yt:expr]) => {
@@ -110,7 +314,25 @@ macro_rules! vertex {
 
 impl ToBuffer for Vertex {
     fn to_buffer(
-        display: &glium::Display,
+        display: &impl Facade,
+        shape: &[Self],
+    ) -> Result<VertexBuffer<Self>, BufferCreationError> {
+        VertexBuffer::new(display, shape)
+    }
+}
+
+impl ToBuffer for ColoredVertex {
+    fn to_buffer(
+        display: &impl Facade,
+        shape: &[Self],
+    ) -> Result<VertexBuffer<Self>, BufferCreationError> {
+        VertexBuffer::new(display, shape)
+    }
+}
+
+impl ToBuffer for TangentVertex {
+    fn to_buffer(
+        display: &impl Facade,
         shape: &[Self],
     ) -> Result<VertexBuffer<Self>, BufferCreationError> {
         VertexBuffer::new(display, shape)
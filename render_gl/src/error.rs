@@ -0,0 +1,207 @@
+use std::{error::Error, fmt, io};
+
+use glium::{
+    backend::glutin::DisplayCreationError, framebuffer::ValidationError, index,
+    texture::TextureCreationError, vertex::BufferCreationError, DrawError, ProgramCreationError,
+    ReadError, SwapBuffersError,
+};
+use image::ImageError;
+
+use crate::{obj::ObjError, text::FontError};
+
+/// The error returned by the fallible parts of the public API, wrapping the error of the underlying operation.
+#[derive(Debug)]
+pub enum RenderError {
+    /// An OpenGL context couldn't be created.
+    ContextCreation(DisplayCreationError),
+    /// The shader program of a mesh failed to compile or link.
+    MeshCreation(ProgramCreationError),
+    /// A shader source file couldn't be read.
+    ShaderFile(io::Error),
+    /// A vertex buffer couldn't be created.
+    VertexBuffer(BufferCreationError),
+    /// An index buffer couldn't be created.
+    IndexBuffer(index::BufferCreationError),
+    /// The source of a Wavefront `.obj` model is malformed.
+    Obj(ObjError),
+    /// The metrics table of a bitmap font is malformed.
+    Font(FontError),
+    /// The bytes of a texture couldn't be decoded.
+    TextureLoad(ImageError),
+    /// A decoded texture couldn't be uploaded to the GPU.
+    TextureCreation(TextureCreationError),
+    /// The faces of a cubemap aren't squares of the same size.
+    CubemapFaceSize {
+        face: usize,
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+    /// A texture couldn't be attached to a framebuffer.
+    Framebuffer(ValidationError),
+    /// The bytes of a texture aren't a DDS file in one of the supported formats.
+    InvalidDds,
+    /// Drawing a mesh failed.
+    Draw(DrawError),
+    /// The pixels of a framebuffer couldn't be read back.
+    ReadPixels(ReadError),
+    /// A captured image couldn't be encoded or written.
+    SaveImage(ImageError),
+    /// The finished frame couldn't be presented.
+    SwapBuffers(SwapBuffersError),
+    /// The per-vertex attributes don't have one entry for every vertex.
+    VertexCountMismatch { expected: usize, actual: usize },
+    /// An index refers to a vertex which doesn't exist.
+    IndexOutOfRange { index: u32, vertices: usize },
+    /// There are meshes to draw, but no camera to view them from.
+    MissingCamera,
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::ContextCreation(error) => {
+                write!(f, "failed to create OpenGL context: {}", error)
+            }
+            RenderError::MeshCreation(error) => write!(f, "failed to create mesh: {}", error),
+            RenderError::ShaderFile(error) => write!(f, "failed to read shader: {}", error),
+            RenderError::VertexBuffer(error) => {
+                write!(f, "failed to create vertex buffer: {}", error)
+            }
+            RenderError::IndexBuffer(error) => {
+                write!(f, "failed to create index buffer: {}", error)
+            }
+            RenderError::Obj(error) => write!(f, "failed to parse obj model: {}", error),
+            RenderError::Font(error) => write!(f, "failed to parse font metrics: {}", error),
+            RenderError::TextureLoad(error) => write!(f, "failed to load texture: {}", error),
+            RenderError::TextureCreation(error) => {
+                write!(f, "failed to create texture: {}", error)
+            }
+            RenderError::CubemapFaceSize {
+                face,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "cubemap face {} is {}x{}, expected {}x{}",
+                face, actual.0, actual.1, expected.0, expected.1
+            ),
+            RenderError::Framebuffer(error) => {
+                write!(f, "failed to create framebuffer: {}", error)
+            }
+            RenderError::InvalidDds => write!(f, "invalid or unsupported DDS file"),
+            RenderError::Draw(error) => write!(f, "failed to draw: {}", error),
+            RenderError::ReadPixels(error) => write!(f, "failed to read pixels: {}", error),
+            RenderError::SaveImage(error) => write!(f, "failed to save image: {}", error),
+            RenderError::SwapBuffers(error) => write!(f, "failed to swap buffers: {}", error),
+            RenderError::VertexCountMismatch { expected, actual } => write!(
+                f,
+                "expected an attribute for each of the {} vertices, got {}",
+                expected, actual
+            ),
+            RenderError::IndexOutOfRange { index, vertices } => write!(
+                f,
+                "index {} is out of range for a mesh with {} vertices",
+                index, vertices
+            ),
+            RenderError::MissingCamera => write!(f, "no camera is initialized to draw the meshes"),
+        }
+    }
+}
+
+impl Error for RenderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RenderError::ContextCreation(error) => Some(error),
+            RenderError::MeshCreation(error) => Some(error),
+            RenderError::ShaderFile(error) => Some(error),
+            RenderError::VertexBuffer(error) => Some(error),
+            RenderError::IndexBuffer(error) => Some(error),
+            RenderError::Obj(error) => Some(error),
+            RenderError::Font(error) => Some(error),
+            RenderError::TextureLoad(error) => Some(error),
+            RenderError::TextureCreation(error) => Some(error),
+            RenderError::Framebuffer(error) => Some(error),
+            RenderError::Draw(error) => Some(error),
+            RenderError::ReadPixels(error) => Some(error),
+            RenderError::SaveImage(error) => Some(error),
+            RenderError::SwapBuffers(error) => Some(error),
+            RenderError::InvalidDds
+            | RenderError::CubemapFaceSize { .. }
+            | RenderError::VertexCountMismatch { .. }
+            | RenderError::IndexOutOfRange { .. }
+            | RenderError::MissingCamera => None,
+        }
+    }
+}
+
+impl From<DisplayCreationError> for RenderError {
+    fn from(error: DisplayCreationError) -> Self {
+        RenderError::ContextCreation(error)
+    }
+}
+
+impl From<ProgramCreationError> for RenderError {
+    fn from(error: ProgramCreationError) -> Self {
+        RenderError::MeshCreation(error)
+    }
+}
+
+impl From<BufferCreationError> for RenderError {
+    fn from(error: BufferCreationError) -> Self {
+        RenderError::VertexBuffer(error)
+    }
+}
+
+impl From<index::BufferCreationError> for RenderError {
+    fn from(error: index::BufferCreationError) -> Self {
+        RenderError::IndexBuffer(error)
+    }
+}
+
+impl From<ObjError> for RenderError {
+    fn from(error: ObjError) -> Self {
+        RenderError::Obj(error)
+    }
+}
+
+impl From<FontError> for RenderError {
+    fn from(error: FontError) -> Self {
+        RenderError::Font(error)
+    }
+}
+
+impl From<ImageError> for RenderError {
+    fn from(error: ImageError) -> Self {
+        RenderError::TextureLoad(error)
+    }
+}
+
+impl From<TextureCreationError> for RenderError {
+    fn from(error: TextureCreationError) -> Self {
+        RenderError::TextureCreation(error)
+    }
+}
+
+impl From<ValidationError> for RenderError {
+    fn from(error: ValidationError) -> Self {
+        RenderError::Framebuffer(error)
+    }
+}
+
+impl From<DrawError> for RenderError {
+    fn from(error: DrawError) -> Self {
+        RenderError::Draw(error)
+    }
+}
+
+impl From<ReadError> for RenderError {
+    fn from(error: ReadError) -> Self {
+        RenderError::ReadPixels(error)
+    }
+}
+
+impl From<SwapBuffersError> for RenderError {
+    fn from(error: SwapBuffersError) -> Self {
+        RenderError::SwapBuffers(error)
+    }
+}
@@ -0,0 +1,112 @@
+use glium::{
+    backend::Facade,
+    index::{NoIndices, PrimitiveType},
+    uniform, Program, Surface,
+};
+
+use crate::{
+    container::{Matrix4, Vec3},
+    debug::{LINE_FRAGMENT_SHADER, LINE_VERTEX_SHADER},
+    draw::vertex::{ColoredVertex, ToBuffer},
+    error::RenderError,
+};
+
+/// The colors of the x, y and z axis drawn by [draw_axes].
+pub const AXIS_COLORS: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+/// Returns the two vertices of a line segment from `from` to `to`, to be drawn as `PrimitiveType::LinesList`.
+pub fn line_vertices(from: Vec3, to: Vec3, color: [f32; 3]) -> [ColoredVertex; 2] {
+    [
+        ColoredVertex {
+            position: from.inner(),
+            color,
+        },
+        ColoredVertex {
+            position: to.inner(),
+            color,
+        },
+    ]
+}
+
+/// Returns three segments of the given `length` along the positive x, y and z axis starting at `origin`, colored by
+/// [AXIS_COLORS].
+pub fn axes_vertices(origin: Vec3, length: f32) -> [ColoredVertex; 6] {
+    let axis = |index: usize| {
+        let mut end = origin.inner();
+        end[index] += length;
+
+        line_vertices(origin, end.into(), AXIS_COLORS[index])
+    };
+
+    let ([x0, x1], [y0, y1], [z0, z1]) = (axis(0), axis(1), axis(2));
+
+    [x0, x1, y0, y1, z0, z1]
+}
+
+/// Draws a line list into `frame` with the given view and projection matrices, on top of whatever has been drawn.
+///
+/// The vertex buffer and the program are created for every call, so this is meant for debugging rather than for
+/// drawing many lines every frame. No `Mesh` or entity is needed.
+pub fn draw_lines<S: Surface>(
+    display: &impl Facade,
+    frame: &mut S,
+    lines: &[ColoredVertex],
+    view: &Matrix4,
+    projection: &Matrix4,
+) -> Result<(), RenderError> {
+    if lines.is_empty() {
+        return Ok(());
+    }
+
+    let buffer = ColoredVertex::to_buffer(display, lines)?;
+    let program = Program::from_source(display, LINE_VERTEX_SHADER, LINE_FRAGMENT_SHADER, None)?;
+
+    let uniforms = uniform! {
+        matrix: Matrix4::identity().inner(),
+        view: view.inner(),
+        perspective: projection.inner(),
+    };
+
+    frame.draw(
+        &buffer,
+        NoIndices(PrimitiveType::LinesList),
+        &program,
+        &uniforms,
+        &Default::default(),
+    )?;
+
+    Ok(())
+}
+
+/// Draws a single line segment, see [draw_lines].
+pub fn draw_line<S: Surface>(
+    display: &impl Facade,
+    frame: &mut S,
+    from: impl Into<Vec3>,
+    to: impl Into<Vec3>,
+    color: [f32; 3],
+    view: &Matrix4,
+    projection: &Matrix4,
+) -> Result<(), RenderError> {
+    let lines = line_vertices(from.into(), to.into(), color);
+
+    draw_lines(display, frame, &lines, view, projection)
+}
+
+/// Draws the x, y and z axis at `origin`, see [axes_vertices] and [draw_lines].
+pub fn draw_axes<S: Surface>(
+    display: &impl Facade,
+    frame: &mut S,
+    origin: impl Into<Vec3>,
+    length: f32,
+    view: &Matrix4,
+    projection: &Matrix4,
+) -> Result<(), RenderError> {
+    draw_lines(
+        display,
+        frame,
+        &axes_vertices(origin.into(), length),
+        view,
+        projection,
+    )
+}
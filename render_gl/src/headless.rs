@@ -0,0 +1,101 @@
+use std::rc::Rc;
+
+use glium::{
+    backend::{glutin::DisplayCreationError, Context, Facade},
+    glutin::{
+        dpi::PhysicalSize,
+        event_loop::{EventLoop, EventLoopBuilder},
+    },
+    Frame, HeadlessRenderer,
+};
+
+use crate::{draw::target::target_dimensions, error::RenderError, window::WindowConfig};
+
+/// An OpenGL context without a window, for rendering in tests and on CI machines.
+///
+/// It takes the place of a `Display`: meshes and render targets are created from it, and a
+/// `World<HeadlessContext>` with the render systems is driven by `World::update_once` instead of an event loop. The
+/// last frame is read back with `read_front_buffer`, or the scene is rendered into a `RenderTarget`.
+pub struct HeadlessContext {
+    renderer: HeadlessRenderer,
+    // the context is created for the event loop, so the loop is kept alive as long as the context.
+    _event_loop: EventLoop<()>,
+}
+
+impl HeadlessContext {
+    /// Creates a context whose default framebuffer has the given `dimensions`, see [target_dimensions].
+    pub fn new(dimensions: (u32, u32)) -> Result<Self, RenderError> {
+        Self::with_config(dimensions, &WindowConfig::default())
+    }
+
+    /// Creates a context with the depth buffer and multisampling requested by `config`, the other options only
+    /// apply to windows.
+    pub fn with_config(dimensions: (u32, u32), config: &WindowConfig) -> Result<Self, RenderError> {
+        let event_loop = build_event_loop();
+        let (width, height) = target_dimensions(dimensions);
+
+        let context = config
+            .context_builder()
+            .build_headless(&event_loop, PhysicalSize::new(width, height))
+            .map_err(DisplayCreationError::from)?;
+        let renderer = HeadlessRenderer::new(context).map_err(DisplayCreationError::from)?;
+
+        Ok(Self {
+            renderer,
+            _event_loop: event_loop,
+        })
+    }
+
+    /// Starts drawing a frame into the default framebuffer, the same way `Display::draw` does.
+    pub fn draw(&self) -> Frame {
+        self.renderer.draw()
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.renderer.get_framebuffer_dimensions()
+    }
+}
+
+impl Facade for HeadlessContext {
+    fn get_context(&self) -> &Rc<Context> {
+        self.renderer.get_context()
+    }
+}
+
+/// Builds the event loop the context is created for.
+///
+/// Tests don't run on the main thread, so the loop is allowed to be created on any thread where the platform
+/// supports it.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn build_event_loop() -> EventLoop<()> {
+    use glium::glutin::platform::unix::EventLoopBuilderExtUnix;
+
+    EventLoopBuilder::new().with_any_thread(true).build()
+}
+
+/// Builds the event loop the context is created for, see the Unix variant of this function.
+#[cfg(target_os = "windows")]
+fn build_event_loop() -> EventLoop<()> {
+    use glium::glutin::platform::windows::EventLoopBuilderExtWindows;
+
+    EventLoopBuilder::new().with_any_thread(true).build()
+}
+
+/// Builds the event loop the context is created for, see the Unix variant of this function.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "windows"
+)))]
+fn build_event_loop() -> EventLoop<()> {
+    EventLoopBuilder::new().build()
+}
@@ -0,0 +1,118 @@
+use std::collections::HashSet;
+
+use ecs::entity::EntityManager;
+use ecs_macro::EntityComponent;
+use glium::glutin::event::{
+    ElementState, KeyboardInput, ModifiersState, VirtualKeyCode, WindowEvent,
+};
+
+/// `Input` keeps track of the keyboard state, so systems can react to key presses without having to handle window
+/// events themselves.
+///
+/// The state is updated through [Input::handle_event], and [Input::end_frame] has to be called once every frame
+/// after the systems have run, to reset the keys which were pressed or released during that frame.
+///
+/// `Input` can be used as component or as resource, [update_input] and [end_input_frame] keep both up to date from
+/// the event loop.
+#[derive(EntityComponent, Debug, Clone, Default)]
+pub struct Input {
+    pressed: HashSet<VirtualKeyCode>,
+    just_pressed: HashSet<VirtualKeyCode>,
+    just_released: HashSet<VirtualKeyCode>,
+    modifiers: ModifiersState,
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates the keyboard state from a window event. Events which aren't keyboard related are ignored.
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state,
+                        virtual_keycode: Some(key),
+                        ..
+                    },
+                ..
+            } => match state {
+                ElementState::Pressed => self.press(*key),
+                ElementState::Released => self.release(*key),
+            },
+            WindowEvent::ModifiersChanged(modifiers) => self.modifiers = *modifiers,
+            _ => (),
+        }
+    }
+
+    pub fn press(&mut self, key: VirtualKeyCode) {
+        // held keys generate repeated press events, which shouldn't count as new presses.
+        if self.pressed.insert(key) {
+            self.just_pressed.insert(key);
+        }
+    }
+
+    pub fn release(&mut self, key: VirtualKeyCode) {
+        if self.pressed.remove(&key) {
+            self.just_released.insert(key);
+        }
+    }
+
+    /// Returns whether the key is currently held down.
+    pub fn is_pressed(&self, key: VirtualKeyCode) -> bool {
+        self.pressed.contains(&key)
+    }
+
+    /// Returns whether the key was pressed during the current frame.
+    pub fn just_pressed(&self, key: VirtualKeyCode) -> bool {
+        self.just_pressed.contains(&key)
+    }
+
+    /// Returns whether the key was released during the current frame.
+    pub fn just_released(&self, key: VirtualKeyCode) -> bool {
+        self.just_released.contains(&key)
+    }
+
+    /// Returns the state of the modifier keys, see [ModifiersState::shift], [ModifiersState::ctrl],
+    /// [ModifiersState::alt] and [ModifiersState::logo].
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    pub fn end_frame(&mut self) {
+        self.just_pressed.clear();
+        self.just_released.clear();
+    }
+}
+
+/// Passes a window event to the `Input` resource and all `Input` components, see [Input::handle_event].
+///
+/// Should be called for every window event, before the systems of the frame run.
+pub fn update_input(manager: &mut EntityManager, event: &WindowEvent) {
+    if let Some(input) = manager.resource_mut::<Input>() {
+        input.handle_event(event);
+    }
+
+    if let Some(inputs) = manager.borrow_manager_mut::<Input>() {
+        for input in &mut inputs.components {
+            input.handle_event(event);
+        }
+    }
+}
+
+/// Ends the frame of the `Input` resource and all `Input` components, see [Input::end_frame].
+///
+/// Should be called once the systems of the frame have run.
+pub fn end_input_frame(manager: &mut EntityManager) {
+    if let Some(input) = manager.resource_mut::<Input>() {
+        input.end_frame();
+    }
+
+    if let Some(inputs) = manager.borrow_manager_mut::<Input>() {
+        for input in &mut inputs.components {
+            input.end_frame();
+        }
+    }
+}
@@ -1,11 +1,2952 @@
+pub mod asset;
+pub mod bounds;
 #[macro_use]
 pub mod buffer;
 pub mod camera;
 pub mod container;
+pub mod dds;
+pub mod debug;
 pub mod draw;
+pub mod error;
+pub mod gizmo;
+pub mod headless;
+pub mod input;
+pub mod light;
 pub mod mesh;
+pub mod obj;
+pub mod picking;
+pub mod program;
+pub mod screenshot;
+pub mod shader;
+pub mod sprite;
+pub mod text;
 pub mod uniform;
 pub mod window;
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use std::{
+        collections::HashMap,
+        env,
+        error::Error,
+        fs::{self, File},
+        io::{self, Cursor},
+        rc::Rc,
+        thread,
+        time::{Duration, Instant, SystemTime},
+    };
+
+    use ecs::world::{SystemType, World};
+    use glium::{
+        draw_parameters::{BackfaceCullingMode, DepthTest},
+        glutin::{
+            dpi::PhysicalSize,
+            event::{
+                DeviceId, ElementState, KeyboardInput, ModifiersState, MouseButton, VirtualKeyCode,
+                WindowEvent,
+            },
+        },
+        index::{NoIndices, PrimitiveType},
+        program::ShaderType,
+        texture::{ClientFormat, RawImage2d, Texture2d, TextureCreationError},
+        uniforms::{
+            MagnifySamplerFilter, MinifySamplerFilter, SamplerWrapFunction, UniformValue, Uniforms,
+        },
+        vertex::BufferCreationError,
+        Blend, BlendingFunction, DrawError, LinearBlendingFactor, ProgramCreationError, Rect,
+        Surface, SwapBuffersError,
+    };
+    use image::{ImageError, ImageFormat, RgbaImage};
+
+    use crate::{
+        asset::{AssetLoader, AssetLoaderSystem, AssetState},
+        bounds::{Aabb, BoundingSphere, Culled, Frustum},
+        buffer::IndexBufferCreator,
+        camera::{
+            camera_view, fly_offset, ActiveCamera, Camera, CameraCycleSystem, CameraEvent,
+            CameraMatrices, CameraMatricesSystem, FlyCameraSystem, MouseCamera, MAX_PITCH,
+        },
+        container::{multiply, project_point, Matrix4, Quaternion, Vec2, Vec3, Vec4},
+        dds::{DdsFormat, DdsImage},
+        debug::{
+            normals_mesh, skeleton, BoundsDebug, BoundsDebugSystem, CULLED_BOUNDS_COLOR,
+            VISIBLE_BOUNDS_COLOR,
+        },
+        draw::{
+            config::{clear_color, letterbox, ClearColor, ClearFlags, RenderConfig, Transparent},
+            delta::TimeDelta,
+            hierarchy::{world_matrices, Parent, TransformHierarchySystem, WorldTransform},
+            instanced::{
+                instance_batches, InstanceBuffer, InstanceSpawner, Instanced,
+                MAX_INSTANCES_PER_BATCH,
+            },
+            internal::{
+                cull_instances, default_matrix, positions, resolve_uniform, sort_back_to_front,
+                sort_front_to_back, sort_instanced_groups, RenderSystems,
+            },
+            target::{target_dimensions, RenderTarget},
+            transform::{DrawParametersComponent, Transform},
+            vertex::{compute_normals, compute_tangents, ColoredVertex, Vertex, VertexBuildError},
+        },
+        error::RenderError,
+        gizmo::{axes_vertices, line_vertices, AXIS_COLORS},
+        headless::HeadlessContext,
+        input::{end_input_frame, update_input, Input},
+        light::{gather_lights, DirectionalLight, LightSystem, LightType, PointLight, SpotLight},
+        mesh::{
+            check_indices, fullscreen_quad_vertices, ColoredMesh, Mesh, TextureType, Winding,
+            FULLSCREEN_VERTEX_SHADER,
+        },
+        obj::ObjModel,
+        picking::{pick, ray_intersects_aabb, PickRequest, Picked, PickingSystem},
+        program::ProgramCache,
+        screenshot::{flip_rows, read_front_buffer},
+        shader::ShaderFiles,
+        sprite::{batch_vertices, sprite_vertices, Sprite, SpriteBatch},
+        text::{layout_text, FontMetrics},
+        uniform::{
+            decode_cubemap_faces, mip_levels,
+            perspective::{resize, resize_event, Orthographic, Perspective},
+            sampler::SamplerConfig,
+            MeshUniform, MAX_LIGHTS,
+        },
+        window::{load_icon, WindowConfig},
+    };
+
+    #[test]
+    #[ignore = "requires an OpenGL driver, run with `cargo test -- --ignored`"]
+    fn transform_moves_mesh() {
+        // squeeze the quad into the left half of the frame, before it is moved by the model matrix.
+        const MATRIX_VERTEX_SHADER: &str = r#"
+            #version 140
+
+            in vec3 position;
+
+            uniform mat4 matrix;
+
+            void main() {
+                gl_Position = matrix * vec4(position.x * 0.5 - 0.5, position.y, 0.0, 1.0);
+            }
+        "#;
+
+        let (context, mut world) = headless_world((4, 4));
+
+        let entity = world.entity();
+        let half = Mesh::new(
+            &context,
+            &fullscreen_quad_vertices(),
+            NoIndices(PrimitiveType::TriangleStrip).into(),
+            MATRIX_VERTEX_SHADER,
+            RED_FRAGMENT_SHADER,
+            Winding::CounterClockwise,
+        )
+        .unwrap();
+        world
+            .with(entity, half)
+            .with::<Transform>(entity, Transform::new());
+
+        world.add_render_systems();
+
+        // the entity has nothing but its transform, which is moved into the right half after the first frame.
+        for (offset, red, black) in [(0.0, 0, 3), (1.0, 3, 0)] {
+            let transform = world.entity_manager.query_entity::<Transform>(entity).0;
+            transform.unwrap().ref_matrix()[3][0] = offset;
+
+            world.update_once(&context);
+
+            let image = read_front_buffer(&context).unwrap();
+            assert_eq!(image.get_pixel(red, 2).0, [255, 0, 0, 255]);
+            assert_eq!(image.get_pixel(black, 2).0, [0, 0, 0, 255]);
+        }
+    }
+
+    #[test]
+    fn async_texture_load() {
+        let name = format!("skyward_async_texture_load_{}.png", std::process::id());
+        let path = env::temp_dir().join(name);
+        RgbaImage::new(4, 2).save(&path).unwrap();
+
+        let mut loader = AssetLoader::new();
+        let handle = loader.load_texture_async(&path);
+        let started = Instant::now();
+
+        loop {
+            loader.receive();
+
+            match loader.state(handle) {
+                Some(AssetState::Pending) => {
+                    assert!(started.elapsed() < Duration::from_secs(10), "decoding timed out");
+                    thread::sleep(Duration::from_millis(5));
+                }
+                Some(AssetState::Decoded(image)) => {
+                    assert_eq!((image.width, image.height), (4, 2));
+                    break;
+                }
+                _ => panic!("texture failed to decode"),
+            }
+        }
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL driver, run with `cargo test -- --ignored`"]
+    fn async_texture_upload() {
+        let name = format!("skyward_async_texture_upload_{}.png", std::process::id());
+        let path = env::temp_dir().join(name);
+        RgbaImage::new(4, 2).save(&path).unwrap();
+
+        let context = HeadlessContext::new((1, 1)).unwrap();
+        let mut world = World::<HeadlessContext>::new();
+        world.with_system(SystemType::Loop, AssetLoaderSystem);
+
+        let mut loader = AssetLoader::new();
+        let handle = loader.load_texture_async(&path);
+        let entity = world.entity();
+        world.with(entity, loader);
+
+        let started = Instant::now();
+
+        // the system uploads the texture on this thread, once it has been decoded in the background.
+        let texture = loop {
+            world.update_once(&context);
+
+            let (loader,) = world.entity_manager.query_entity::<AssetLoader>(entity);
+            match loader.unwrap().take_texture(handle) {
+                Some(texture) => break texture,
+                None => {
+                    assert!(
+                        started.elapsed() < Duration::from_secs(10),
+                        "loading timed out"
+                    );
+                    thread::sleep(Duration::from_millis(5));
+                }
+            }
+        };
+
+        let TextureType::Texture2d(texture) = texture else {
+            panic!("the image wasn't uploaded as a 2d texture");
+        };
+        assert_eq!(texture.dimensions(), (4, 2));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn bulk_instances() {
+        let mut world = World::<()>::new();
+        let mesh = world.entity_at(3);
+
+        let instances = (0..1000)
+            .map(|i| {
+                let position = Vec3::new(i as f32, 0.0, 0.0);
+                (position, Quaternion::identity(), Vec3::new(1.0, 1.0, 1.0))
+            })
+            .collect::<Vec<_>>();
+
+        world.entity_manager.tick_frame();
+
+        let entities = world.with_instances(mesh, &instances);
+        let manager = &mut world.entity_manager;
+
+        assert_eq!(entities.len(), 1000);
+        assert!(!entities.contains(&mesh));
+        assert_eq!(manager.borrow_manager::<Instanced>().unwrap().components.len(), 1000);
+        assert_eq!(manager.get_updated_frame::<Instanced>(), manager.frame());
+
+        let instance = manager.query_entity::<Instanced>(entities[37]).0.unwrap();
+        assert_eq!(instance.mesh, mesh as u32);
+        assert_eq!(instance.world_position, [37.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn project_origin_to_center() {
+        let identity = Matrix4::from([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+
+        let viewport = Vec2::new(800.0, 600.0);
+        let projected = project_point(
+            Vec3::new(0.0, 0.0, 0.0),
+            &identity,
+            &identity,
+            &identity,
+            viewport,
+        )
+        .unwrap();
+
+        assert_eq!(projected.inner(), [400.0, 300.0]);
+
+        let mut behind = identity;
+        behind[3][3] = -1.0;
+
+        assert!(project_point(
+            Vec3::new(0.0, 0.0, 0.0),
+            &identity,
+            &identity,
+            &behind,
+            viewport
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn clear_flags() {
+        let color = (0.1, 0.2, 0.3, 1.0);
+
+        assert_eq!(
+            ClearFlags::ColorAndDepth.values(color, 1.0),
+            (Some(color), Some(1.0))
+        );
+        assert_eq!(ClearFlags::Color.values(color, 1.0), (Some(color), None));
+        assert_eq!(ClearFlags::Depth.values(color, 1.0), (None, Some(1.0)));
+        assert_eq!(ClearFlags::None.values(color, 1.0), (None, None));
+    }
+
+    #[test]
+    fn transform_only_entity() {
+        let mut transform = Transform::new();
+        transform.ref_matrix().translate(0.0, 0.0, 2.0);
+
+        let expected = transform.inner();
+
+        let mut fallback = None;
+        let uniform = resolve_uniform(None, &mut fallback, Some(&mut transform));
+
+        assert_eq!(uniform.ref_matrix().unwrap().inner(), expected);
+
+        // the transform overrides the matrix of the uniform, which is only used without a transform.
+        let mut uniform = MeshUniform::new(Matrix4::new());
+        let mut fallback = None;
+        let resolved = resolve_uniform(Some(&mut uniform), &mut fallback, Some(&mut transform));
+
+        assert_eq!(resolved.ref_matrix().unwrap().inner(), expected);
+
+        let mut uniform = MeshUniform::new(Matrix4::new());
+        let resolved = resolve_uniform(Some(&mut uniform), &mut fallback, None);
+
+        assert_eq!(
+            resolved.ref_matrix().unwrap().inner(),
+            Matrix4::new().inner()
+        );
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL driver, run with `cargo test -- --ignored`"]
+    fn transient_buffers_are_recycled() {
+        let (context, mut world) = headless_world((4, 4));
+
+        let green = RawImage2d::from_raw_rgba(vec![0u8, 255, 0, 255], (1, 1));
+        let texture = Rc::new(Texture2d::new(&context, green).unwrap());
+
+        world.add_render_systems();
+
+        // one more sprite, six more vertices, is batched in every frame.
+        for _ in 0..100 {
+            let sprite = world.entity();
+            world.with(
+                sprite,
+                Sprite::new(texture.clone(), [2.0, 2.0]).size([2.0, 2.0]),
+            );
+
+            world.update_once(&context);
+
+            let image = read_front_buffer(&context).unwrap();
+            assert_eq!(image.get_pixel(2, 2).0, [0, 255, 0, 255]);
+        }
+
+        let batch = *world
+            .entity_query_table
+            .query_first_single::<SpriteBatch>(&mut world.entity_manager)
+            .unwrap();
+        let (batch,) = world.entity_manager.query_entity::<SpriteBatch>(batch);
+
+        // a buffer of each of the 64, 128, 256, 512 and 1024 size classes, for up to 600 vertices.
+        assert_eq!(batch.unwrap().pool().allocations(), 5);
+    }
+
+    #[test]
+    fn cycle_cameras() {
+        let mut world = World::<()>::new();
+        world.with_system(SystemType::Loop, CameraCycleSystem::new(VirtualKeyCode::C));
+
+        let cameras = (0..3)
+            .map(|_| {
+                let entity = world.entity();
+                world.with::<Camera>(
+                    entity,
+                    Camera::new([0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+                );
+                entity
+            })
+            .collect::<Vec<_>>();
+
+        let holder = world.entity();
+
+        world
+            .with::<ActiveCamera>(holder, ActiveCamera(cameras[0]))
+            .with::<Input>(holder, Input::new());
+
+        for expected in [cameras[1], cameras[2], cameras[0]] {
+            let manager = &mut world.entity_manager;
+            manager
+                .query_entity::<Input>(holder)
+                .0
+                .unwrap()
+                .press(VirtualKeyCode::C);
+
+            world.update(SystemType::Loop, &());
+
+            let manager = &mut world.entity_manager;
+            let input = manager.query_entity::<Input>(holder).0.unwrap();
+
+            input.release(VirtualKeyCode::C);
+            input.end_frame();
+
+            let active = manager.query_entity::<ActiveCamera>(holder).0.unwrap();
+            assert_eq!(active.0, expected);
+        }
+
+        // the active camera shouldn't change without a key press
+        world.update(SystemType::Loop, &());
+
+        let active = world.entity_manager.query_entity::<ActiveCamera>(holder).0;
+        assert_eq!(active.unwrap().0, cameras[0]);
+    }
+
+    #[test]
+    fn cycle_cameras_input_resource() {
+        let mut world = World::<()>::new();
+        world.with_system(SystemType::Loop, CameraCycleSystem::new(VirtualKeyCode::C));
+        world.insert_resource(Input::new());
+
+        let cameras = [world.entity(), world.entity()];
+
+        for camera in cameras {
+            world.with::<Camera>(
+                camera,
+                Camera::new([0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+            );
+        }
+
+        let holder = world.entity();
+        world.with::<ActiveCamera>(holder, ActiveCamera(cameras[0]));
+
+        // the key is pressed through the resource, the way the demo feeds its input.
+        world
+            .resource_mut::<Input>()
+            .unwrap()
+            .press(VirtualKeyCode::C);
+        world.update(SystemType::Loop, &());
+
+        let active = world.entity_manager.query_entity::<ActiveCamera>(holder).0;
+        assert_eq!(active.unwrap().0, cameras[1]);
+
+        let input = world.resource_mut::<Input>().unwrap();
+        input.release(VirtualKeyCode::C);
+        input.end_frame();
+        world.update(SystemType::Loop, &());
+
+        let active = world.entity_manager.query_entity::<ActiveCamera>(holder).0;
+        assert_eq!(active.unwrap().0, cameras[1]);
+    }
+
+    #[test]
+    fn normal_lines() {
+        let vertices = [
+            crate::vertex!([0.0, 0.0, 0.0], [0.0, 0.0], [0.0, 0.0, 1.0]),
+            crate::vertex!([1.0, 0.0, 0.0], [1.0, 0.0], [0.0, 1.0, 0.0]),
+            crate::vertex!([0.0, 1.0, 0.0], [0.0, 1.0], [-1.0, 0.0, 0.0]),
+        ];
+
+        let lines = normals_mesh(&vertices, 0.5);
+
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[0].position, [0.0, 0.0, 0.0]);
+        assert_eq!(lines[1].position, [0.0, 0.0, 0.5]);
+        assert_eq!(lines[3].position, [1.0, 0.5, 0.0]);
+        assert_eq!(lines[5].position, [-0.5, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn window_icon() {
+        let mut bytes = vec![];
+
+        RgbaImage::new(16, 16)
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+
+        assert!(load_icon(&bytes).is_some());
+        assert!(load_icon(&[0, 1, 2, 3]).is_none());
+    }
+
+    #[test]
+    fn opaque_front_to_back() {
+        let mut entities = vec![
+            (0, Vec3::new(0.0, 0.0, 10.0)),
+            (1, Vec3::new(0.0, 0.0, 2.0)),
+            (2, Vec3::new(0.0, 0.0, 5.0)),
+        ];
+
+        sort_front_to_back(&mut entities, Vec3::new(0.0, 0.0, 0.0));
+
+        let order = entities.iter().map(|entry| entry.0).collect::<Vec<_>>();
+        assert_eq!(order, vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn translated_meshes_front_to_back() {
+        let mut world = World::<()>::new();
+
+        let meshes = [10.0, 2.0, 5.0].map(|z| {
+            let mut transform = Transform::new();
+            transform.translate(0.0, 0.0, z);
+
+            let mesh = world.entity();
+            world.with(mesh, transform);
+            mesh
+        });
+
+        let mut entities = positions(&mut world.entity_manager, &meshes);
+        assert_eq!(entities[0].1.inner(), [0.0, 0.0, 10.0]);
+
+        sort_front_to_back(&mut entities, Vec3::new(0.0, 0.0, 0.0));
+
+        let order = entities.iter().map(|entry| entry.0).collect::<Vec<_>>();
+        assert_eq!(order, vec![meshes[1], meshes[2], meshes[0]]);
+    }
+
+    #[test]
+    fn instanced_group_order() {
+        let mut groups = vec![(4, 1), (7, 0), (2, 1)];
+        sort_instanced_groups(&mut groups);
+
+        assert_eq!(groups, vec![(7, 0), (2, 1), (4, 1)]);
+    }
+
+    #[test]
+    fn modifier_keys() {
+        let mut input = Input::new();
+
+        input.handle_event(&WindowEvent::ModifiersChanged(
+            ModifiersState::SHIFT | ModifiersState::CTRL,
+        ));
+
+        let modifiers = input.modifiers();
+        assert!(modifiers.shift() && modifiers.ctrl());
+        assert!(!modifiers.alt() && !modifiers.logo());
+
+        input.handle_event(&WindowEvent::ModifiersChanged(ModifiersState::empty()));
+        assert!(!input.modifiers().shift() && !input.modifiers().ctrl());
+    }
+
+    #[test]
+    fn fullscreen_quad() {
+        let vertices = fullscreen_quad_vertices();
+
+        assert_eq!(vertices.len(), 4);
+
+        for vertex in vertices {
+            let [x, y, _] = vertex.position;
+            assert_eq!(vertex.tex_pos, [(x + 1.0) / 2.0, (y + 1.0) / 2.0]);
+        }
+    }
+
+    #[test]
+    fn tiny_bc1_dds() {
+        let bytes = include_bytes!("../fixtures/tiny_bc1.dds");
+        let image = DdsImage::parse(bytes).unwrap();
+
+        assert_eq!(image.format, DdsFormat::Bc1);
+        assert_eq!((image.width, image.height), (4, 4));
+        assert_eq!(image.data.len(), 8);
+
+        // truncated files are rejected instead of reading past the end.
+        assert!(DdsImage::parse(&bytes[..bytes.len() - 1]).is_none());
+    }
+
+    #[test]
+    fn bounds_debug() {
+        let mut world = World::<()>::new();
+        world.with_system(SystemType::Loop, BoundsDebugSystem::new(VirtualKeyCode::B));
+
+        let holder = world.entity();
+
+        world
+            .with::<BoundsDebug>(holder, BoundsDebug::new())
+            .with::<Input>(holder, Input::new());
+
+        let visible = world.entity();
+        let culled = world.entity();
+        let unbounded = world.entity();
+
+        world
+            .with::<BoundingSphere>(visible, BoundingSphere::new([0.0, 0.0, 0.0], 1.0))
+            .with::<BoundingSphere>(culled, BoundingSphere::new([5.0, 0.0, 0.0], 2.0))
+            .with::<Culled>(culled, Culled)
+            .with::<Transform>(unbounded, Transform::new());
+
+        world.update(SystemType::Loop, &());
+
+        let manager = &mut world.entity_manager;
+        assert!(manager
+            .query_entity::<BoundsDebug>(holder)
+            .0
+            .unwrap()
+            .queued
+            .is_empty());
+
+        manager
+            .query_entity::<Input>(holder)
+            .0
+            .unwrap()
+            .press(VirtualKeyCode::B);
+
+        world.update(SystemType::Loop, &());
+
+        let manager = &mut world.entity_manager;
+        let debug = manager.query_entity::<BoundsDebug>(holder).0.unwrap();
+
+        assert!(debug.enabled);
+        assert_eq!(debug.queued.len(), 2);
+
+        for (entity, lines) in &debug.queued {
+            // three circles, each made of line segments with two vertices.
+            assert_eq!(lines.len() % 6, 0);
+            assert!(!lines.is_empty());
+
+            let color = match *entity == culled {
+                true => CULLED_BOUNDS_COLOR,
+                false => VISIBLE_BOUNDS_COLOR,
+            };
+
+            assert!(lines.iter().all(|vertex| vertex.color == color));
+        }
+
+        let (_, lines) = debug.queued.iter().find(|entry| entry.0 == culled).unwrap();
+        assert_eq!(lines[0].position, [5.0, 2.0, 0.0]);
+    }
+
+    #[test]
+    fn letterbox_rect() {
+        // a 16:9 target in a 4:3 window leaves bars above and below.
+        let rect = letterbox((800, 600), 16.0 / 9.0);
+
+        assert_eq!((rect.left, rect.bottom), (0, 75));
+        assert_eq!((rect.width, rect.height), (800, 450));
+
+        // a 4:3 target in a 16:9 window leaves bars on the sides.
+        let rect = letterbox((1920, 1080), 4.0 / 3.0);
+
+        assert_eq!((rect.left, rect.bottom), (240, 0));
+        assert_eq!((rect.width, rect.height), (1440, 1080));
+
+        assert_eq!(letterbox((0, 0), 16.0 / 9.0).width, 0);
+    }
+
+    #[test]
+    fn camera_resource() {
+        let mut world = World::<()>::new();
+        let manager = &mut world.entity_manager;
+        let table = &mut world.entity_query_table;
+
+        assert!(camera_view(manager, table).is_none());
+
+        let entity = manager.entity();
+        manager.entity_with::<Camera>(
+            entity,
+            Camera::new([1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+        );
+
+        let (_, position) = camera_view(manager, table).unwrap();
+        assert_eq!(position.inner(), [1.0, 0.0, 0.0]);
+
+        // the resource takes precedence over the camera entity.
+        world.insert_resource(Camera::new(
+            [0.0, 2.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 1.0, 0.0],
+        ));
+
+        let manager = &mut world.entity_manager;
+        let table = &mut world.entity_query_table;
+
+        let (view, position) = camera_view(manager, table).unwrap();
+        let expected = world.resource::<Camera>().unwrap().view_matrix();
+
+        assert_eq!(position.inner(), [0.0, 2.0, 0.0]);
+        assert_eq!(view.inner(), expected.inner());
+
+        world
+            .resource_mut::<Camera>()
+            .unwrap()
+            .position([0.0, 3.0, 0.0]);
+
+        assert_eq!(world.resource::<Camera>().unwrap().ref_position()[1], 3.0);
+    }
+
+    #[test]
+    fn skeleton_lines() {
+        let joints = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+        ];
+
+        // the last bone refers to a joint which doesn't exist, and is skipped.
+        let skeleton = skeleton(&joints, &[(0, 1), (1, 2), (2, 3)], [1.0, 1.0, 1.0]);
+
+        assert_eq!(skeleton.vertices.len(), 3);
+        assert_eq!(skeleton.vertices[2].position, [0.0, 2.0, 0.0]);
+        assert_eq!(skeleton.indices, vec![0, 1, 1, 2]);
+    }
+
+    #[test]
+    fn empty_uniform_uses_identity() {
+        let mut fallback = None;
+        let uniform = resolve_uniform(None, &mut fallback, None);
+
+        default_matrix(uniform);
+
+        let mut matrix = None;
+
+        uniform.visit_values(|name, value| {
+            if let ("matrix", UniformValue::Mat4(value)) = (name, value) {
+                matrix = Some(value);
+            }
+        });
+
+        assert_eq!(
+            matrix,
+            Some([
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ])
+        );
+
+        // an existing matrix is left untouched.
+        let mut uniform = MeshUniform::new(Matrix4::new());
+        default_matrix(&mut uniform);
+
+        assert_eq!(
+            uniform.ref_matrix().unwrap().inner(),
+            Matrix4::new().inner()
+        );
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL driver, run with `cargo test -- --ignored`"]
+    fn winding_culling() {
+        // a triangle covering the whole screen, authored counter-clockwise.
+        let vertices = [
+            crate::vertex!([-1.0, -1.0, 0.0], [0.0, 0.0]),
+            crate::vertex!([3.0, -1.0, 0.0], [2.0, 0.0]),
+            crate::vertex!([-1.0, 3.0, 0.0], [0.0, 2.0]),
+        ];
+
+        // renders the triangle with the given indices as a mesh of the given winding, and reads back the center.
+        let render = |indices: &[u32], winding: Winding| {
+            let (context, mut world) = headless_world((4, 4));
+
+            let mesh = world.entity();
+            let triangle = Mesh::indexed(
+                &context,
+                &vertices,
+                indices,
+                PrimitiveType::TrianglesList,
+                FULLSCREEN_VERTEX_SHADER,
+                RED_FRAGMENT_SHADER,
+                winding,
+            )
+            .unwrap();
+            let culling = DrawParametersComponent(glium::DrawParameters {
+                backface_culling: BackfaceCullingMode::CullClockwise,
+                ..Default::default()
+            });
+            world.with(mesh, triangle).with(mesh, culling);
+
+            world.add_render_systems();
+            world.update_once(&context);
+
+            read_front_buffer(&context).unwrap().get_pixel(2, 2).0
+        };
+
+        let (red, clear) = ([255, 0, 0, 255], [0, 0, 0, 255]);
+
+        // the front face survives, while the back face of the same mesh is culled.
+        let mut indices = [0u32, 1, 2];
+        assert_eq!(render(&indices, Winding::CounterClockwise), red);
+        assert_eq!(render(&indices, Winding::Clockwise), clear);
+
+        // once flipped at load time, the triangle is wound clockwise.
+        Winding::flip_indices(&mut indices);
+        assert_eq!(indices, [0, 2, 1]);
+        assert_eq!(render(&indices, Winding::Clockwise), red);
+        assert_eq!(render(&indices, Winding::CounterClockwise), clear);
+    }
+
+    #[test]
+    fn camera_matrices() {
+        let mut world = World::<()>::new();
+        world.with_system(SystemType::Loop, CameraMatricesSystem);
+
+        let entity = world.entity();
+        let camera = Camera::new([1.0, 2.0, -3.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]);
+
+        let perspective = Perspective::from_dimensions(800.0, 600.0, 3.0, 1024.0, 0.1);
+
+        world
+            .with::<Camera>(entity, camera.clone())
+            .insert_resource(perspective);
+
+        world.update(SystemType::Loop, &());
+
+        let matrices = world.resource::<CameraMatrices>().unwrap();
+        let (view, projection) = (camera.view_matrix(), perspective.matrix());
+
+        assert_eq!(matrices.view.inner(), view.inner());
+        assert_eq!(matrices.projection.inner(), projection.inner());
+        assert_eq!(matrices.position.inner(), [1.0, 2.0, -3.0]);
+
+        // transforming by the combined matrix equals applying the view, then the projection.
+        let point = Vec4::new(0.5, -1.0, 4.0, 1.0);
+        let expected = projection.transform_vec4(view.transform_vec4(point));
+        let actual = matrices.view_projection.transform_vec4(point);
+
+        for index in 0..4 {
+            assert!((expected[index] - actual[index]).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn tuple_bundle() {
+        let mut world = World::<()>::new();
+        let entity = world.spawn_bundle((Transform::new(), MeshUniform::empty()));
+
+        let (transform, uniform) = world
+            .entity_manager
+            .query_entity_two::<Transform, MeshUniform>(entity);
+
+        assert!(transform.is_some());
+        assert!(uniform.is_some());
+
+        let queried = world
+            .entity_query_table
+            .query::<(Transform, MeshUniform)>(&mut world.entity_manager);
+
+        assert_eq!(queried, Some(vec![entity]));
+    }
+
+    #[test]
+    fn render_error_display() {
+        let errors: Vec<(RenderError, &str)> = vec![
+            (
+                ProgramCreationError::CompilationError("bad".into(), ShaderType::Vertex).into(),
+                "failed to create mesh: Compilation error in vertex shader: bad",
+            ),
+            (
+                BufferCreationError::FormatNotSupported.into(),
+                "failed to create vertex buffer: ",
+            ),
+            (
+                ImageError::IoError(io::Error::new(io::ErrorKind::NotFound, "missing")).into(),
+                "failed to load texture: missing",
+            ),
+            (
+                TextureCreationError::FormatNotSupported.into(),
+                "failed to create texture: ",
+            ),
+            (RenderError::InvalidDds, "invalid or unsupported DDS file"),
+            (DrawError::NoDepthBuffer.into(), "failed to draw: "),
+            (
+                SwapBuffersError::ContextLost.into(),
+                "failed to swap buffers: the OpenGL context has been lost and needs to be recreated",
+            ),
+            (
+                RenderError::VertexCountMismatch {
+                    expected: 4,
+                    actual: 3,
+                },
+                "expected an attribute for each of the 4 vertices, got 3",
+            ),
+            (
+                glium::index::BufferCreationError::IndexTypeNotSupported.into(),
+                "failed to create index buffer: ",
+            ),
+        ];
+
+        for (error, message) in &errors {
+            assert!(
+                error.to_string().starts_with(message),
+                "{:?} displayed as {}",
+                error,
+                error
+            );
+        }
+
+        assert!(matches!(errors[0].0, RenderError::MeshCreation(_)));
+        assert!(matches!(errors[1].0, RenderError::VertexBuffer(_)));
+        assert!(matches!(errors[2].0, RenderError::TextureLoad(_)));
+        assert!(matches!(errors[3].0, RenderError::TextureCreation(_)));
+        assert!(matches!(errors[5].0, RenderError::Draw(_)));
+        assert!(matches!(errors[6].0, RenderError::SwapBuffers(_)));
+        assert!(matches!(errors[8].0, RenderError::IndexBuffer(_)));
+
+        // wrapped errors are exposed as source, the ones raised by the engine itself have none.
+        assert!(errors[0].0.source().is_some());
+        assert!(errors[4].0.source().is_none());
+        assert!(errors[7].0.source().is_none());
+    }
+
+    #[test]
+    fn matrix_inverse() {
+        let identity = [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+
+        let mut matrix = Matrix4::from(identity);
+        matrix.scale(2.0, 0.5, 3.0);
+        matrix.rotate(0.7, (0.0, 0.6, 0.8));
+        matrix[3][0] = 4.0;
+        matrix[3][1] = -2.0;
+        matrix[3][2] = 7.5;
+
+        let inverse = matrix.inverse().unwrap();
+
+        for product in [multiply(matrix, inverse), multiply(inverse, matrix)] {
+            for i in 0..4 {
+                for j in 0..4 {
+                    assert!((product[i][j] - identity[i][j]).abs() < 1e-5);
+                }
+            }
+        }
+
+        let transposed = matrix.transpose();
+
+        for i in 0..4 {
+            for j in 0..4 {
+                assert_eq!(transposed[i][j], matrix[j][i]);
+            }
+        }
+
+        assert!(Matrix4::new().inverse().is_none());
+
+        // the view matrix maps the camera's position to the origin, and its direction onto the z axis.
+        let camera = Camera::new([0.0, 0.0, -3.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]);
+        let view = camera.view_matrix();
+        let origin = view.transform_vec4(Vec4::new(0.0, 0.0, -3.0, 1.0));
+        let ahead = view.transform_vec4(Vec4::new(0.0, 0.0, 0.0, 1.0));
+
+        assert!(origin
+            .inner()
+            .iter()
+            .take(3)
+            .all(|value| value.abs() < 1e-5));
+        assert!((ahead[2] - 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn vector_arithmetic() {
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        let y = Vec3::new(0.0, 1.0, 0.0);
+
+        assert_eq!(x.cross(y).inner(), [0.0, 0.0, 1.0]);
+        assert_eq!(x.dot(y), 0.0);
+        assert_eq!((x - y).inner(), [1.0, -1.0, 0.0]);
+        assert_eq!((x + y * 2.0).inner(), [1.0, 2.0, 0.0]);
+
+        let normalized = Vec3::new(3.0, -4.0, 12.0).normalize();
+
+        assert!((normalized.length() - 1.0).abs() < 1e-6);
+        assert_eq!(
+            Vec3::new(0.0, 0.0, 0.0).normalize().inner(),
+            [0.0, 0.0, 0.0]
+        );
+
+        let vec = Vec4::new(1.0, 2.0, 3.0, 4.0);
+
+        assert_eq!((vec - vec * 0.5).inner(), [0.5, 1.0, 1.5, 2.0]);
+        assert_eq!(vec.dot(vec), 30.0);
+        assert!((vec.normalize().length() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn quaternion_rotation() {
+        let identity = [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+
+        assert_eq!(Quaternion::identity().to_matrix4().inner(), identity);
+
+        let close = |a: [f32; 4], b: [f32; 4]| (0..4).all(|i| (a[i] - b[i]).abs() < 1e-5);
+
+        // matches the matrix built by `Matrix4::rotate`.
+        let rotation = Quaternion::from_axis_angle([0.0, 0.6, 0.8], 1.2);
+        let mut matrix = Matrix4::from(identity);
+        matrix.rotate(1.2, (0.0, 0.6, 0.8));
+
+        for column in 0..4 {
+            assert!(close(
+                rotation.to_matrix4()[column].inner(),
+                matrix[column].inner()
+            ));
+        }
+
+        let from = Quaternion::identity();
+        let to = Quaternion::from_axis_angle([0.0, 0.0, 1.0], std::f32::consts::FRAC_PI_2);
+
+        assert!(close(from.slerp(to, 0.0).inner(), from.inner()));
+        assert!(close(from.slerp(to, 1.0).inner(), to.inner()));
+
+        let half = Quaternion::from_axis_angle([0.0, 0.0, 1.0], std::f32::consts::FRAC_PI_4);
+
+        assert!(close(from.slerp(to, 0.5).inner(), half.inner()));
+        assert!(close((half * half).inner(), to.inner()));
+
+        let transform = Transform::from(to);
+        let rotated = transform
+            .matrix
+            .transform_vec4(Vec4::new(1.0, 0.0, 0.0, 1.0));
+
+        assert!(close(rotated.inner(), [0.0, 1.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn orthographic_projection() {
+        let orthographic = Orthographic::new(-4.0, 12.0, 2.0, 6.0, 0.5, 100.0);
+        let matrix = orthographic.matrix();
+
+        let min = matrix.transform_vec4(Vec4::new(-4.0, 2.0, 0.5, 1.0));
+        let max = matrix.transform_vec4(Vec4::new(12.0, 6.0, 100.0, 1.0));
+        let center = matrix.transform_vec4(Vec4::new(4.0, 4.0, 50.25, 1.0));
+
+        for (actual, expected) in [
+            (min.inner(), [-1.0, -1.0, -1.0, 1.0]),
+            (max.inner(), [1.0, 1.0, 1.0, 1.0]),
+            (center.inner(), [0.0, 0.0, 0.0, 1.0]),
+        ] {
+            assert!((0..4).all(|i| (actual[i] - expected[i]).abs() < 1e-5));
+        }
+
+        let uniform = MeshUniform::empty().orthographic(orthographic);
+        let mut projection = None;
+
+        uniform.visit_values(|name, value| {
+            if let ("perspective", UniformValue::Mat4(matrix)) = (name, value) {
+                projection = Some(matrix);
+            }
+        });
+
+        assert_eq!(projection, Some(matrix.inner()));
+    }
+
+    #[test]
+    fn resize_updates_perspective() {
+        let mut world = World::<()>::new();
+        let perspective = Perspective::from_dimensions(800.0, 600.0, 3.0, 1024.0, 0.1);
+        let entity = world.entity();
+
+        world
+            .with(entity, perspective)
+            .with(entity, MeshUniform::empty().perspective(perspective))
+            .insert_resource(perspective);
+
+        resize(&mut world.entity_manager, (600, 600));
+
+        let square = |matrix: [[f32; 4]; 4]| (matrix[0][0] - matrix[1][1]).abs() < 1e-6;
+
+        let (component, uniform) = world
+            .entity_manager
+            .query_entity_two::<Perspective, MeshUniform>(entity);
+        let mut projection = None;
+
+        uniform.unwrap().visit_values(|name, value| {
+            if let ("perspective", UniformValue::Mat4(matrix)) = (name, value) {
+                projection = Some(matrix);
+            }
+        });
+
+        assert!(square(component.unwrap().inner()));
+        assert!(square(projection.unwrap()));
+        assert!(square(world.resource::<Perspective>().unwrap().inner()));
+
+        // a minimized window reports a height of zero.
+        let mut minimized = perspective;
+        minimized.update_dimensions(800.0, 0.0);
+
+        assert!(minimized
+            .inner()
+            .iter()
+            .flatten()
+            .all(|value| value.is_finite()));
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL driver, run with `cargo test -- --ignored`"]
+    fn static_instances_upload_once() {
+        let (context, mut world) = headless_world((4, 4));
+
+        let mesh = world.entity();
+        let quad = Mesh::fullscreen_quad(&context, RED_FRAGMENT_SHADER).unwrap();
+        world.with(mesh, quad);
+
+        let instances = (0..100)
+            .map(|index| {
+                (
+                    Vec3::new(index as f32, 0.0, 0.0),
+                    Quaternion::identity(),
+                    Vec3::new(1.0, 1.0, 1.0),
+                )
+            })
+            .collect::<Vec<_>>();
+        world.with_instances(mesh, &instances);
+        world.add_render_systems();
+
+        // renders a frame, and returns the frame the instances were last uploaded at, and how many there are.
+        let update = |world: &mut World<HeadlessContext>| {
+            world.update_once(&context);
+
+            let (buffer,) = world.entity_manager.query_entity::<InstanceBuffer>(mesh);
+            let buffer = buffer.unwrap();
+            (
+                buffer.uploaded,
+                buffer
+                    .buffers
+                    .iter()
+                    .map(|buffer| buffer.len())
+                    .sum::<usize>(),
+            )
+        };
+
+        let mut uploads = (0..5).map(|_| update(&mut world)).collect::<Vec<_>>();
+        uploads.dedup();
+        assert_eq!(uploads.len(), 1);
+
+        // instances changed in place are only uploaded again once they're marked as changed.
+        let (first, _) = uploads[0];
+        let (_, instance) = world.entity_manager.iter::<Instanced>().next().unwrap();
+        instance.world_position[1] = 2.0;
+        assert_eq!(update(&mut world), (first, 100));
+
+        world.entity_manager.mark_changed::<Instanced>();
+        let (written, count) = update(&mut world);
+        assert!(written > first);
+        assert_eq!(count, 100);
+
+        // the next frame reuses the buffer again, until another instance is added.
+        assert_eq!(update(&mut world), (written, 100));
+
+        world.with_instances(mesh, &instances[..1]);
+        let (created, count) = update(&mut world);
+        assert!(created > written);
+        assert_eq!(count, 101);
+    }
+
+    #[test]
+    fn instance_rotation_and_scale() {
+        let turn = Quaternion::from_axis_angle([0.0, 1.0, 0.0], 1.0);
+        let instances = [
+            Instanced::create(1, (0.0, 0.0, 0.0)),
+            Instanced::create(1, (1.0, 0.0, 0.0)).rotation(turn),
+            Instanced::create(1, (2.0, 0.0, 0.0)).scale([2.0, 0.5, 1.0]),
+            Instanced::create(1, (3.0, 0.0, 0.0))
+                .rotation(turn)
+                .scale([3.0, 3.0, 3.0]),
+        ];
+
+        assert_eq!(instances[0].rotation, [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(instances[0].scale, [1.0, 1.0, 1.0]);
+        assert_eq!(instances[1].rotation, turn.inner());
+        assert_eq!(instances[2].scale, [2.0, 0.5, 1.0]);
+        assert_eq!(instances[3].rotation, turn.inner());
+        assert_eq!(instances[3].scale, [3.0, 3.0, 3.0]);
+
+        // instances spawned in bulk keep their own rotation and scale as well.
+        let mut world = World::<()>::new();
+        let mesh = world.entity();
+        let spawned = world.with_instances(
+            mesh,
+            &[
+                (Vec3::new(0.0, 0.0, 0.0), turn, Vec3::new(2.0, 0.5, 1.0)),
+                (
+                    Vec3::new(1.0, 0.0, 0.0),
+                    Quaternion::identity(),
+                    Vec3::new(1.0, 1.0, 1.0),
+                ),
+            ],
+        );
+
+        let manager = &mut world.entity_manager;
+        let rotated = *manager.query_entity::<Instanced>(spawned[0]).0.unwrap();
+        let plain = *manager.query_entity::<Instanced>(spawned[1]).0.unwrap();
+
+        assert_eq!(rotated.rotation, turn.inner());
+        assert_eq!(rotated.scale, [2.0, 0.5, 1.0]);
+        assert_eq!(plain.rotation, [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(plain.scale, [1.0, 1.0, 1.0]);
+
+        // the per-instance buffer exposes every field but the mesh to the vertex shader.
+        let bindings = <Instanced as glium::Vertex>::build_bindings();
+        let names = bindings
+            .iter()
+            .map(|binding| binding.0.as_ref())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, ["world_position", "rotation", "scale"]);
+    }
+
+    #[test]
+    fn obj_cube() {
+        let cube = "
+            # a unit cube with a normal per side
+            o cube
+            v 0 0 0
+            v 1 0 0
+            v 1 1 0
+            v 0 1 0
+            v 0 0 1
+            v 1 0 1
+            v 1 1 1
+            v 0 1 1
+            vt 0 0
+            vt 1 0
+            vt 1 1
+            vt 0 1
+            vn 0 0 -1
+            vn 0 0 1
+            vn 0 -1 0
+            vn 0 1 0
+            vn -1 0 0
+            vn 1 0 0
+            f 1/1/1 4/4/1 3/3/1 2/2/1
+            f 5/1/2 6/2/2 7/3/2 8/4/2
+            f 1/1/3 2/2/3 6/3/3 5/4/3
+            f 4/1/4 8/4/4 7/3/4 3/2/4
+            f 1/1/5 5/2/5 8/3/5 4/4/5
+            f 2/1/6 3/4/6 7/3/6 6/2/6
+        ";
+
+        let model = ObjModel::parse(cube).unwrap();
+
+        // every corner of a side has its own normal, so the 8 positions become 24 vertices.
+        assert_eq!(model.vertices.len(), 24);
+        assert_eq!(model.indices.len(), 36);
+        assert_eq!(model.vertices[0].normal, [0.0, 0.0, -1.0]);
+        assert_eq!(model.vertices[2].tex_pos, [1.0, 1.0]);
+
+        // faces without texture coordinates, referring to the elements relative to the end.
+        let relative = "
+            v 0 0 0
+            v 1 0 0
+            v 0 1 0
+            vn 0 0 1
+            f -3//-1 -2//-1 -1//-1
+        ";
+
+        let model = ObjModel::parse(relative).unwrap();
+
+        assert_eq!(model.indices, [0, 1, 2]);
+        assert_eq!(model.vertices[1].position, [1.0, 0.0, 0.0]);
+        assert_eq!(model.vertices[1].tex_pos, [0.0, 0.0]);
+        assert_eq!(model.vertices[1].normal, [0.0, 0.0, 1.0]);
+
+        let error = ObjModel::parse("v 0 0 0\nf 1 2 3").unwrap_err();
+
+        assert_eq!(error.line, 2);
+        assert_eq!(
+            RenderError::from(error).to_string(),
+            "failed to parse obj model: line 2: index 2 is out of range, 1 elements are defined"
+        );
+    }
+
+    #[test]
+    fn indexed_quad() {
+        let quad = [
+            crate::vertex!([-1.0, -1.0, 0.0], [0.0, 0.0]),
+            crate::vertex!([1.0, -1.0, 0.0], [1.0, 0.0]),
+            crate::vertex!([1.0, 1.0, 0.0], [1.0, 1.0]),
+            crate::vertex!([-1.0, 1.0, 0.0], [0.0, 1.0]),
+        ];
+        let indices = [0, 1, 2, 0, 2, 3];
+
+        assert!(check_indices(&indices, quad.len()).is_ok());
+
+        let error = check_indices(&[0, 1, 4], quad.len()).unwrap_err();
+
+        assert!(matches!(
+            error,
+            RenderError::IndexOutOfRange {
+                index: 4,
+                vertices: 4
+            }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "index 4 is out of range for a mesh with 4 vertices"
+        );
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL driver, run with `cargo test -- --ignored`"]
+    fn indexed_quad_render() {
+        let (context, mut world) = headless_world((4, 4));
+
+        let quad = [
+            crate::vertex!([-1.0, -1.0, 0.0], [0.0, 0.0]),
+            crate::vertex!([1.0, -1.0, 0.0], [1.0, 0.0]),
+            crate::vertex!([1.0, 1.0, 0.0], [1.0, 1.0]),
+            crate::vertex!([-1.0, 1.0, 0.0], [0.0, 1.0]),
+        ];
+        let mesh = Mesh::indexed(
+            &context,
+            &quad,
+            &[0, 1, 2, 0, 2, 3],
+            PrimitiveType::TrianglesList,
+            FULLSCREEN_VERTEX_SHADER,
+            RED_FRAGMENT_SHADER,
+            Winding::CounterClockwise,
+        )
+        .unwrap();
+
+        // with back faces culled, the quad only covers the frame if both triangles are wound like the quad.
+        let culling = DrawParametersComponent(glium::DrawParameters {
+            backface_culling: BackfaceCullingMode::CullClockwise,
+            ..Default::default()
+        });
+        let entity = world.entity();
+        world.with(entity, mesh).with(entity, culling);
+
+        world.add_render_systems();
+        world.update_once(&context);
+
+        let image = read_front_buffer(&context).unwrap();
+        assert!(image.pixels().all(|pixel| pixel.0 == [255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn computed_normals() {
+        // a plane of two counter-clockwise triangles, and a vertex which isn't part of any triangle.
+        let positions = [
+            (0.0, 0.0, 0.0),
+            (1.0, 0.0, 0.0),
+            (1.0, 1.0, 0.0),
+            (0.0, 1.0, 0.0),
+            (5.0, 5.0, 5.0),
+        ];
+        let indices = [0, 1, 2, 0, 2, 3];
+
+        let normals = compute_normals(&positions, &indices).unwrap();
+
+        assert_eq!(normals.len(), positions.len());
+
+        for normal in &normals[..4] {
+            assert_eq!(*normal, (0.0, 0.0, 1.0));
+        }
+
+        assert_eq!(normals[4], (0.0, 0.0, 0.0));
+        assert!(compute_normals(&positions, &[0, 1, 7]).is_err());
+    }
+
+    #[test]
+    fn computed_tangents() {
+        let quad = [
+            crate::vertex!([0.0, 0.0, 0.0], [0.0, 0.0], [0.0, 0.0, 1.0]),
+            crate::vertex!([2.0, 0.0, 0.0], [1.0, 0.0], [0.0, 0.0, 1.0]),
+            crate::vertex!([2.0, 2.0, 0.0], [1.0, 1.0], [0.0, 0.0, 1.0]),
+            crate::vertex!([0.0, 2.0, 0.0], [0.0, 1.0], [0.0, 0.0, 1.0]),
+        ];
+        let indices = [0, 1, 2, 0, 2, 3];
+
+        let vertices = compute_tangents(&quad, &indices).unwrap();
+
+        for vertex in &vertices {
+            let tangent = Vec3::from(vertex.tangent);
+
+            // the tangent follows the u axis of the texture, and lies in the plane of the quad.
+            assert!(tangent.dot(Vec3::from(vertex.normal)).abs() < 1e-6);
+            assert!((tangent.length() - 1.0).abs() < 1e-6);
+            assert_eq!(vertex.tangent, [1.0, 0.0, 0.0]);
+        }
+
+        assert!(compute_tangents(&quad, &[0, 1, 4]).is_err());
+    }
+
+    #[test]
+    fn texture_sampler() {
+        let uniform = MeshUniform::empty()
+            .sampler(SamplerConfig::pixelated())
+            .normal_sampler(SamplerConfig::new().wrap(SamplerWrapFunction::Repeat));
+
+        let textures = uniform.textures();
+        let (name, _, behavior) = textures[0];
+
+        assert_eq!(name, "tex");
+        assert_eq!(behavior.minify_filter, MinifySamplerFilter::Nearest);
+        assert_eq!(behavior.magnify_filter, MagnifySamplerFilter::Nearest);
+        assert_eq!(
+            behavior.wrap_function,
+            (
+                SamplerWrapFunction::Clamp,
+                SamplerWrapFunction::Clamp,
+                SamplerWrapFunction::Clamp
+            )
+        );
+
+        // untouched textures keep the default sampling.
+        assert_eq!(textures[1].2, SamplerConfig::default().behavior());
+        assert_eq!(textures[2].2.wrap_function.0, SamplerWrapFunction::Repeat);
+        assert_eq!(
+            textures[2].2.minify_filter,
+            MinifySamplerFilter::LinearMipmapLinear
+        );
+    }
+
+    #[test]
+    fn mipmap_levels() {
+        assert_eq!(mip_levels((1, 1)), 1);
+        assert_eq!(mip_levels((256, 256)), 9);
+        assert_eq!(mip_levels((1024, 16)), 11);
+
+        // non-power-of-two sizes round every level down.
+        assert_eq!(mip_levels((300, 200)), 9);
+        assert!(mip_levels((3, 5)) > 1);
+
+        let trilinear = SamplerConfig::trilinear().behavior();
+        assert_eq!(
+            trilinear.minify_filter,
+            MinifySamplerFilter::LinearMipmapLinear
+        );
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL driver, run with `cargo test -- --ignored`"]
+    fn uploaded_mipmap_levels() {
+        let context = HeadlessContext::new((1, 1)).unwrap();
+
+        let mut png = Cursor::new(Vec::new());
+        RgbaImage::new(300, 200)
+            .write_to(&mut png, ImageFormat::Png)
+            .unwrap();
+
+        let uniform = MeshUniform::empty()
+            .with_img_2d_texture_mipmapped(ImageFormat::Png, &context, png.get_ref())
+            .unwrap();
+
+        // the driver allocated the whole chain of levels for the uploaded texture.
+        let (_, texture, _) = &uniform.textures()[0];
+        let Some(TextureType::Texture2d(texture)) = texture else {
+            panic!("the image wasn't uploaded as a 2d texture");
+        };
+
+        assert!(texture.get_mipmap_levels() > 1);
+        assert_eq!(texture.get_mipmap_levels(), mip_levels((300, 200)));
+    }
+
+    #[test]
+    fn cubemap_faces() {
+        let png = |width, height| {
+            let mut bytes = Cursor::new(Vec::new());
+            RgbaImage::new(width, height)
+                .write_to(&mut bytes, ImageFormat::Png)
+                .unwrap();
+            bytes.into_inner()
+        };
+
+        let face = png(1, 1);
+        let faces = decode_cubemap_faces(ImageFormat::Png, [&face[..]; 6]).unwrap();
+        assert_eq!(faces.len(), 6);
+        assert!(faces.iter().all(|face| (face.width, face.height) == (1, 1)));
+
+        let larger = png(2, 2);
+        let mut mismatched = [&face[..]; 6];
+        mismatched[4] = &larger;
+        assert!(matches!(
+            decode_cubemap_faces(ImageFormat::Png, mismatched),
+            Err(RenderError::CubemapFaceSize {
+                face: 4,
+                expected: (1, 1),
+                actual: (2, 2),
+            })
+        ));
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL driver, run with `cargo test -- --ignored`"]
+    fn cubemap_sampler() {
+        let context = HeadlessContext::new((1, 1)).unwrap();
+
+        let mut face = Cursor::new(Vec::new());
+        RgbaImage::new(2, 2)
+            .write_to(&mut face, ImageFormat::Png)
+            .unwrap();
+        let face = face.into_inner();
+
+        let uniform = MeshUniform::empty()
+            .with_cubemap_from_faces(&context, [&face[..]; 6], ImageFormat::Png)
+            .unwrap();
+
+        // the cubemap is handed to the shaders as `cubemap_tex`, clamped so the seams between the faces don't bleed.
+        let mut sampler = None;
+
+        uniform.visit_values(|name, value| {
+            if let ("cubemap_tex", UniformValue::Cubemap(cubemap, behavior)) = (name, value) {
+                assert_eq!(cubemap.dimensions(), 2);
+                sampler = behavior;
+            }
+        });
+
+        let clamp = SamplerWrapFunction::Clamp;
+        assert_eq!(sampler.unwrap().wrap_function, (clamp, clamp, clamp));
+    }
+
+    #[test]
+    fn multiple_lights() {
+        let uniform = MeshUniform::empty().lights(vec![
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ]);
+
+        let mut count = None;
+        let mut lights = Vec::new();
+        let mut single = None;
+
+        uniform.visit_values(|name, value| match (name, value) {
+            ("u_light_count", UniformValue::SignedInt(value)) => count = Some(value),
+            ("u_light", UniformValue::Vec3(value)) => single = Some(value),
+            (name, UniformValue::Vec3(value)) if name.starts_with("u_lights[") => {
+                lights.push((name.to_string(), value))
+            }
+            _ => {}
+        });
+
+        assert_eq!(count, Some(3));
+        assert_eq!(single, Some([1.0, 0.0, 0.0]));
+        assert_eq!(
+            lights,
+            vec![
+                ("u_lights[0]".to_string(), [1.0, 0.0, 0.0]),
+                ("u_lights[1]".to_string(), [0.0, 1.0, 0.0]),
+                ("u_lights[2]".to_string(), [0.0, 0.0, 1.0]),
+            ]
+        );
+
+        // the single light builder adds to the array, up to the maximum.
+        let uniform = (0..MAX_LIGHTS + 2).fold(MeshUniform::empty(), |uniform, i| {
+            uniform.light([i as f32, 0.0, 0.0])
+        });
+        let mut count = None;
+
+        uniform.visit_values(|name, value| {
+            if let ("u_light_count", UniformValue::SignedInt(value)) = (name, value) {
+                count = Some(value);
+            }
+        });
+
+        assert_eq!(count, Some(MAX_LIGHTS as i32));
+    }
+
+    #[test]
+    fn light_components() {
+        let mut world = World::<()>::new();
+        world.with_system(SystemType::Loop, LightSystem);
+
+        let (sun, lamp, torch, mesh) = (
+            world.entity(),
+            world.entity(),
+            world.entity(),
+            world.entity(),
+        );
+
+        world
+            .with(
+                sun,
+                DirectionalLight::new([0.0, -1.0, 0.0], [1.0, 1.0, 1.0]),
+            )
+            .with(
+                lamp,
+                PointLight::new([1.0, 2.0, 3.0], [1.0, 0.5, 0.0], 10.0),
+            )
+            .with(
+                torch,
+                SpotLight::new([4.0, 5.0, 6.0], [0.0, 0.0, 1.0], 0.5, [1.0, 1.0, 0.8]),
+            )
+            .with(mesh, MeshUniform::empty());
+
+        let lights = gather_lights(&mut world.entity_manager, &mut world.entity_query_table);
+        assert_eq!(
+            lights
+                .iter()
+                .map(|light| light.vector().inner())
+                .collect::<Vec<_>>(),
+            vec![[0.0, -1.0, 0.0], [1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]
+        );
+        assert_eq!(
+            lights
+                .iter()
+                .map(|light| light.light_type)
+                .collect::<Vec<_>>(),
+            vec![LightType::Directional, LightType::Point, LightType::Spot]
+        );
+        assert_eq!(lights[1].color.inner(), [1.0, 0.5, 0.0]);
+        assert_eq!(lights[2].direction.inner(), [0.0, 0.0, 1.0]);
+
+        world.update(SystemType::Loop, &());
+
+        let uniform = world
+            .entity_manager
+            .query_entity::<MeshUniform>(mesh)
+            .0
+            .unwrap();
+        let mut count = None;
+        let mut types = Vec::new();
+        let (mut range, mut angle) = (None, None);
+
+        uniform.visit_values(|name, value| match (name, value) {
+            ("u_light_count", UniformValue::SignedInt(value)) => count = Some(value),
+            ("u_light_ranges[1]", UniformValue::Float(value)) => range = Some(value),
+            ("u_light_angles[2]", UniformValue::Float(value)) => angle = Some(value),
+            (name, UniformValue::SignedInt(value)) if name.starts_with("u_light_types[") => {
+                types.push(value)
+            }
+            _ => {}
+        });
+
+        assert_eq!(count, Some(3));
+        assert_eq!(types, vec![0, 1, 2]);
+        assert_eq!((range, angle), (Some(10.0), Some(0.5)));
+
+        // once the light components are gone, the meshes don't keep the stale lights.
+        for light in [sun, lamp, torch] {
+            world.remove_entity(light);
+        }
+        world.update(SystemType::Loop, &());
+
+        let uniform = world
+            .entity_manager
+            .query_entity::<MeshUniform>(mesh)
+            .0
+            .unwrap();
+        let mut count = None;
+
+        uniform.visit_values(|name, value| {
+            if let ("u_light_count", UniformValue::SignedInt(value)) = (name, value) {
+                count = Some(value);
+            }
+        });
+
+        assert_eq!(count, Some(0));
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL driver, run with `cargo test -- --ignored`"]
+    fn render_target_pixels() {
+        assert_eq!(target_dimensions((256, 128)), (256, 128));
+        assert_eq!(target_dimensions((0, 0)), (1, 1));
+
+        let context = HeadlessContext::new((1, 1)).unwrap();
+        let mut target = RenderTarget::new(&context, (3, 3)).unwrap();
+
+        // a solid red target, whose bottom row is painted green.
+        let bottom = Rect {
+            left: 0,
+            bottom: 0,
+            width: 3,
+            height: 1,
+        };
+        target
+            .draw_to(|framebuffer| {
+                framebuffer.clear_color(1.0, 0.0, 0.0, 1.0);
+                framebuffer.clear(Some(&bottom), Some((0.0, 1.0, 0.0, 1.0)), false, None, None);
+            })
+            .unwrap();
+
+        assert_eq!(target.read_pixel(1, 1), Some((255, 0, 0, 255)));
+        assert_eq!(target.read_pixel(3, 1), None);
+
+        // the rows start at the bottom of the target.
+        assert_eq!(target.read_pixel(0, 0), Some((0, 255, 0, 255)));
+        assert_eq!(target.read_pixel(0, 2), Some((255, 0, 0, 255)));
+    }
+
+    #[test]
+    fn transparent_back_to_front() {
+        let mut world = World::<()>::new();
+        let camera = Camera::new([0.0, 0.0, -5.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]);
+
+        let mut entities = vec![];
+
+        for depth in [4.0, 10.0, 0.0] {
+            let entity = world.entity();
+            let mut transform = Transform::new();
+            transform.ref_matrix()[3][2] = depth;
+
+            world.with(entity, transform).with(entity, Transparent);
+            entities.push(entity);
+        }
+
+        let mut transparent = positions(&mut world.entity_manager, &entities);
+        sort_back_to_front(&mut transparent, *camera.ref_position());
+
+        // the farthest mesh is drawn first, so the nearer ones are blended over it.
+        let order = transparent.iter().map(|entry| entry.0).collect::<Vec<_>>();
+        assert_eq!(order, vec![entities[1], entities[0], entities[2]]);
+    }
+
+    #[test]
+    fn frustum_culling() {
+        let camera = Camera::new([0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]);
+        let perspective = Perspective::from_dimensions(800.0, 600.0, 3.0, 100.0, 0.1);
+        let frustum = Frustum::new(&perspective, &camera);
+
+        assert!(frustum.contains_sphere([0.0, 0.0, 10.0], 0.0));
+        assert!(!frustum.contains_sphere([0.0, 0.0, -10.0], 0.0));
+        assert!(!frustum.contains_sphere([0.0, 0.0, 200.0], 1.0));
+        assert!(!frustum.contains_sphere([100.0, 0.0, 10.0], 1.0));
+
+        // a sphere reaching into the frustum is kept, even though its center is outside.
+        assert!(frustum.contains_sphere([0.0, 0.0, -1.0], 2.0));
+
+        let mut world = World::<()>::new();
+        let mesh = world.entity();
+        world.with(mesh, BoundingSphere::new([0.0, 0.0, 0.0], 1.0));
+
+        let visible = Instanced::create(mesh as u32, (0.0, 0.0, 10.0));
+        let behind = Instanced::create(mesh as u32, (0.0, 0.0, -10.0));
+        let mut instances = HashMap::from([(mesh, vec![visible, behind])]);
+
+        cull_instances(&mut world.entity_manager, &frustum, &mut instances);
+        assert_eq!(instances[&mesh], vec![visible]);
+
+        // a mesh without any visible instances isn't drawn at all.
+        let mut instances = HashMap::from([(mesh, vec![behind])]);
+        cull_instances(&mut world.entity_manager, &frustum, &mut instances);
+        assert!(instances.is_empty());
+    }
+
+    #[test]
+    fn window_context_config() {
+        let default = WindowConfig::new().context_builder();
+        assert_eq!(default.pf_reqs.multisampling, None);
+        assert!(!default.gl_attr.vsync);
+        assert_eq!(default.pf_reqs.depth_bits, Some(24));
+
+        let config = WindowConfig::new().multisampling(4).vsync(true);
+        let builder = config.context_builder();
+        assert_eq!(builder.pf_reqs.multisampling, Some(4));
+        assert!(builder.gl_attr.vsync);
+
+        // a sample count which isn't a power of two leaves multisampling disabled.
+        let config = WindowConfig::new().multisampling(3);
+        assert_eq!(config.multisampling, None);
+    }
+
+    #[test]
+    fn screenshot_rows() {
+        let clear = [25, 50, 255, 255];
+        let marker = [255, 0, 0, 255];
+
+        // a 2x3 frame cleared to a single color, with a marker in the bottom left pixel, as read back from OpenGL.
+        let mut data = [clear; 6];
+        data[0] = marker;
+        let raw = RawImage2d {
+            data: data.concat().into(),
+            width: 2,
+            height: 3,
+            format: ClientFormat::U8U8U8U8,
+        };
+
+        let image = flip_rows(raw);
+
+        assert_eq!(image.dimensions(), (2, 3));
+        assert_eq!(image.get_pixel(1, 1).0, clear);
+        assert_eq!(image.get_pixel(0, 2).0, marker);
+        assert_eq!(image.get_pixel(0, 0).0, clear);
+    }
+
+    #[test]
+    fn configurable_clear_color() {
+        let mut world = World::<()>::new();
+        assert_eq!(clear_color(&world.entity_manager), (0.0, 0.0, 0.0, 1.0));
+
+        world.insert_resource(ClearColor::new([0.5, 0.7, 1.0]));
+        let color = clear_color(&world.entity_manager);
+
+        assert_eq!(color, (0.5, 0.7, 1.0, 1.0));
+        assert_eq!(
+            ClearFlags::ColorAndDepth.values(color, 1.0),
+            (Some((0.5, 0.7, 1.0, 1.0)), Some(1.0))
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn keyboard_edges() {
+        let key_event = |state| WindowEvent::KeyboardInput {
+            // SAFETY: the dummy id is only compared, never passed to the platform.
+            device_id: unsafe { DeviceId::dummy() },
+            input: KeyboardInput {
+                scancode: 0,
+                state,
+                virtual_keycode: Some(VirtualKeyCode::W),
+                modifiers: ModifiersState::empty(),
+            },
+            is_synthetic: false,
+        };
+
+        let mut world = World::<()>::new();
+        world.insert_resource(Input::new());
+
+        let holder = world.entity();
+        world.with(holder, Input::new());
+
+        update_input(&mut world.entity_manager, &key_event(ElementState::Pressed));
+
+        let input = world.resource::<Input>().unwrap();
+        assert!(input.is_pressed(VirtualKeyCode::W));
+        assert!(input.just_pressed(VirtualKeyCode::W));
+        assert!(!input.just_released(VirtualKeyCode::W));
+
+        let component = world.entity_manager.query_entity::<Input>(holder).0;
+        assert!(component.unwrap().just_pressed(VirtualKeyCode::W));
+
+        // the key stays held in the next frame, but it's no longer a new press.
+        end_input_frame(&mut world.entity_manager);
+        update_input(&mut world.entity_manager, &key_event(ElementState::Pressed));
+
+        let input = world.resource::<Input>().unwrap();
+        assert!(input.is_pressed(VirtualKeyCode::W));
+        assert!(!input.just_pressed(VirtualKeyCode::W));
+
+        end_input_frame(&mut world.entity_manager);
+        update_input(
+            &mut world.entity_manager,
+            &key_event(ElementState::Released),
+        );
+
+        let input = world.resource::<Input>().unwrap();
+        assert!(!input.is_pressed(VirtualKeyCode::W));
+        assert!(input.just_released(VirtualKeyCode::W));
+
+        end_input_frame(&mut world.entity_manager);
+        assert!(!world
+            .resource::<Input>()
+            .unwrap()
+            .just_released(VirtualKeyCode::W));
+    }
+
+    #[test]
+    fn fly_camera() {
+        let camera = Camera::new([1.0, 0.0, 0.0], [0.0, 0.0, 2.0], [0.0, 1.0, 0.0]);
+        let mut input = Input::new();
+
+        input.press(VirtualKeyCode::W);
+
+        // half a second at a speed of 4 covers 2 units along the view direction.
+        let offset = fly_offset(&camera, &input, 4.0 * 0.5);
+        assert_eq!(offset.inner(), [0.0, 0.0, 2.0]);
+
+        // moving diagonally covers the same distance as moving straight.
+        input.press(VirtualKeyCode::D);
+        let offset = fly_offset(&camera, &input, 2.0);
+        assert!((offset.length() - 2.0).abs() < 1e-5);
+        assert!(offset[0] > 0.0 && offset[2] > 0.0);
+
+        // opposing keys cancel each other out.
+        input.release(VirtualKeyCode::D);
+        input.press(VirtualKeyCode::S);
+        assert_eq!(fly_offset(&camera, &input, 2.0).inner(), [0.0, 0.0, 0.0]);
+
+        let mut world = World::<()>::new();
+        world.with_system(SystemType::Loop, FlyCameraSystem::new(1.0));
+
+        let entity = world.entity();
+        world
+            .with(entity, camera)
+            .with(entity, TimeDelta::new())
+            .insert_resource(Input::new());
+        world
+            .resource_mut::<Input>()
+            .unwrap()
+            .press(VirtualKeyCode::W);
+
+        std::thread::sleep(Duration::from_millis(5));
+        world.update(SystemType::Loop, &());
+
+        let camera = world
+            .entity_manager
+            .query_entity::<Camera>(entity)
+            .0
+            .unwrap();
+        let position = camera.ref_position();
+        assert_eq!((position[0], position[1]), (1.0, 0.0));
+        assert!(position[2] > 0.0);
+    }
+
+    #[test]
+    fn camera_pitch_clamp() {
+        let mut camera = Camera::new([0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]);
+        assert_eq!((camera.yaw(), camera.pitch()), (0.0, 0.0));
+
+        // moving the mouse up far beyond the vertical never flips the view over.
+        for _ in 0..100 {
+            camera.change_direction(0.0, 45.0);
+            assert!(camera.pitch() <= MAX_PITCH);
+        }
+
+        assert_eq!(camera.pitch(), MAX_PITCH);
+        assert!(camera.ref_direction()[2] > 0.0);
+        assert!(camera.ref_direction()[1] < 1.0);
+
+        camera.change_direction(0.0, -1000.0);
+        assert_eq!(camera.pitch(), -MAX_PITCH);
+
+        // turning right by 90 degrees looks along +x, without any roll.
+        let mut camera = Camera::new([0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]);
+        camera.change_direction(90.0, 0.0);
+
+        let direction = camera.ref_direction();
+        assert!((direction[0] - 1.0).abs() < 1e-6);
+        assert!(direction[1].abs() < 1e-6 && direction[2].abs() < 1e-6);
+    }
+
+    #[test]
+    fn camera_basis() {
+        let camera = Camera::new([1.0, 2.0, 3.0], [1.0, -0.5, 2.0], [0.0, 1.0, 0.0]);
+        let (forward, right, up) = (camera.forward(), camera.right(), camera.world_up());
+
+        for axis in [forward, right, up] {
+            assert!((axis.length() - 1.0).abs() < 1e-5);
+        }
+
+        assert!(right.dot(forward).abs() < 1e-6);
+        assert!(up.dot(forward).abs() < 1e-6);
+        assert!(up.dot(right).abs() < 1e-6);
+
+        // looking along +z with +y up, right is +x.
+        let camera = Camera::new([0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]);
+        assert_eq!(camera.right().inner(), [1.0, 0.0, 0.0]);
+        assert_eq!(camera.world_up().inner(), [0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn mouse_camera() {
+        let mut world = World::<()>::new();
+        world.with_post_loop_system(MouseCamera::new(0.5));
+
+        let entity = world.entity();
+        world.with(
+            entity,
+            Camera::new([0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+        );
+
+        let camera = world
+            .entity_manager
+            .query_entity::<Camera>(entity)
+            .0
+            .unwrap();
+        camera.input_event(CameraEvent::MouseMovement {
+            dx: 20.0,
+            dy: -10.0,
+        });
+
+        world.update(SystemType::Loop, &());
+
+        let camera = world
+            .entity_manager
+            .query_entity::<Camera>(entity)
+            .0
+            .unwrap();
+        assert_eq!((camera.yaw(), camera.pitch()), (10.0, 5.0));
+
+        let direction = camera.ref_direction();
+        assert!(direction[0] > 0.0 && direction[1] > 0.0);
+
+        // releasing the mouse stops the camera from turning, until the mouse is grabbed again.
+        camera.input_event(CameraEvent::KeyPressed(VirtualKeyCode::Escape));
+        camera.input_event(CameraEvent::MouseMovement { dx: 20.0, dy: 0.0 });
+        world.update(SystemType::Loop, &());
+
+        let camera = world
+            .entity_manager
+            .query_entity::<Camera>(entity)
+            .0
+            .unwrap();
+        assert_eq!(camera.yaw(), 10.0);
+
+        camera.input_event(CameraEvent::MouseButtonPressed(MouseButton::Left));
+        camera.input_event(CameraEvent::MouseMovement { dx: 20.0, dy: 0.0 });
+        world.update(SystemType::Loop, &());
+
+        let camera = world
+            .entity_manager
+            .query_entity::<Camera>(entity)
+            .0
+            .unwrap();
+        assert_eq!(camera.yaw(), 20.0);
+    }
+
+    #[test]
+    fn resize_events() {
+        let mut world = World::<()>::new();
+        let entity = world.entity();
+
+        world
+            .with(entity, Orthographic::new(-1.0, 1.0, -1.0, 1.0, 0.0, 10.0))
+            .insert_resource(Perspective::from_dimensions(800.0, 800.0, 3.0, 1024.0, 0.1));
+
+        let aspect_ratio = |world: &World<()>| {
+            let matrix = world.resource::<Perspective>().unwrap().inner();
+            matrix[1][1] / matrix[0][0]
+        };
+
+        assert!(!resize_event(
+            &mut world.entity_manager,
+            &WindowEvent::Focused(true)
+        ));
+        assert!(resize_event(
+            &mut world.entity_manager,
+            &WindowEvent::Resized(PhysicalSize::new(1600, 800))
+        ));
+        assert!((aspect_ratio(&world) - 2.0).abs() < 1e-5);
+
+        // the orthographic bounds widen around their center, keeping the vertical bounds.
+        let orthographic = world
+            .entity_manager
+            .query_entity::<Orthographic>(entity)
+            .0
+            .unwrap();
+        assert_eq!(
+            (orthographic.left, orthographic.right, orthographic.top),
+            (-2.0, 2.0, 1.0)
+        );
+
+        // moving to a monitor with another scale factor resizes the framebuffer as well.
+        let mut size = PhysicalSize::new(900, 1200);
+        assert!(resize_event(
+            &mut world.entity_manager,
+            &WindowEvent::ScaleFactorChanged {
+                scale_factor: 1.5,
+                new_inner_size: &mut size,
+            }
+        ));
+        assert!((aspect_ratio(&world) - 0.75).abs() < 1e-5);
+    }
+
+    /// Paints everything red, so drawn pixels stand out against the black clear color.
+    const RED_FRAGMENT_SHADER: &str = r#"
+        #version 140
+
+        out vec4 color;
+
+        void main() {
+            color = vec4(1.0, 0.0, 0.0, 1.0);
+        }
+    "#;
+
+    /// Creates a headless context of the given size, and a world with a camera looking down the z axis.
+    fn headless_world(dimensions: (u32, u32)) -> (HeadlessContext, World<HeadlessContext>) {
+        let context = HeadlessContext::new(dimensions).unwrap();
+        let mut world = World::<HeadlessContext>::new();
+        world.insert_resource(Camera::new(
+            [0.0, 0.0, -1.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 1.0, 0.0],
+        ));
+
+        (context, world)
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL driver, run with `cargo test -- --ignored`"]
+    fn headless_render() {
+        let (context, mut world) = headless_world((4, 4));
+
+        let mesh = world.entity();
+        let quad = Mesh::fullscreen_quad(&context, RED_FRAGMENT_SHADER).unwrap();
+        world.with(mesh, quad);
+
+        let target = world.entity();
+        let render_target = RenderTarget::new(&context, (4, 4)).unwrap();
+        world.with(target, render_target);
+
+        world.add_render_systems();
+        world.update_once(&context);
+
+        // the target is cleared to black, so a red pixel was drawn by the quad.
+        let (target,) = world.entity_manager.query_entity::<RenderTarget>(target);
+        assert_eq!(target.unwrap().read_pixel(2, 2), Some((255, 0, 0, 255)));
+    }
+
+    #[test]
+    fn fixed_timestep() {
+        let fixed_dt = Duration::from_secs(1) / 60;
+        let mut delta = TimeDelta::new();
+
+        let frames = [16, 17, 33, 8, 8, 50].map(Duration::from_millis);
+        let steps = frames
+            .iter()
+            .map(|frame| delta.accumulate_frame(*frame, fixed_dt).0)
+            .collect::<Vec<_>>();
+
+        assert_eq!(steps, vec![0, 1, 2, 1, 0, 3]);
+
+        // all 132ms but the leftover were simulated in whole 60Hz steps.
+        let (_, leftover) = delta.accumulate_frame(Duration::ZERO, fixed_dt);
+        let total = frames.iter().sum::<Duration>();
+
+        assert_eq!(fixed_dt * 7 + leftover, total);
+        assert!(leftover < fixed_dt);
+    }
+
+    #[test]
+    fn elapsed_time() {
+        let mut delta = TimeDelta::new();
+        thread::sleep(Duration::from_millis(5));
+
+        // the time before the first frame isn't counted.
+        delta.update_time_delta();
+        assert_eq!(delta.total_elapsed(), Duration::ZERO);
+        assert_eq!(delta.frame_count(), 1);
+
+        let mut last = delta.total_elapsed_secs();
+
+        for frame in 2..=4 {
+            thread::sleep(Duration::from_millis(2));
+            delta.update_time_delta();
+
+            assert!(delta.total_elapsed_secs() > last);
+            assert_eq!(delta.frame_count(), frame);
+            last = delta.total_elapsed_secs();
+        }
+
+        let uniform = MeshUniform::empty().time(last);
+        let mut time = None;
+
+        uniform.visit_values(|name, value| {
+            if let ("u_time", UniformValue::Float(value)) = (name, value) {
+                time = Some(value);
+            }
+        });
+
+        assert_eq!(time, Some(last));
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL driver, run with `cargo test -- --ignored`"]
+    fn shared_programs() {
+        let context = HeadlessContext::new((1, 1)).unwrap();
+        let mut cache = ProgramCache::new();
+        let vertices = fullscreen_quad_vertices();
+
+        let mut mesh = |fragment_shader, winding| {
+            Mesh::cached(
+                &context,
+                &mut cache,
+                &vertices,
+                NoIndices(PrimitiveType::TriangleStrip),
+                FULLSCREEN_VERTEX_SHADER,
+                fragment_shader,
+                winding,
+            )
+            .unwrap()
+        };
+
+        let white = "#version 140\nout vec4 color;\nvoid main() { color = vec4(1.0); }";
+        let first = mesh(white, Winding::CounterClockwise);
+        let second = mesh(white, Winding::Clockwise);
+        let other = mesh(
+            "#version 140\nout vec4 color;\nvoid main() { color = vec4(0.0); }",
+            Winding::CounterClockwise,
+        );
+
+        // the winding is kept per mesh, even though the program is shared.
+        assert_eq!(second.winding, Winding::Clockwise);
+        assert!(Rc::ptr_eq(&first.program, &second.program));
+        assert!(!Rc::ptr_eq(&first.program, &other.program));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn shader_file_changes() {
+        let vertex = env::temp_dir().join("skyward_hot_reload.vs");
+        let fragment = env::temp_dir().join("skyward_hot_reload.fs");
+
+        fs::write(&vertex, "void main() {}").unwrap();
+        fs::write(&fragment, "void main() {}").unwrap();
+
+        let mut files = ShaderFiles::new(&vertex, &fragment);
+        assert!(!files.changed());
+
+        // saving the fragment shader moves its modification time.
+        fs::write(&fragment, "void main() { discard; }").unwrap();
+        let saved = SystemTime::now() + Duration::from_secs(1);
+        File::options()
+            .write(true)
+            .open(&fragment)
+            .and_then(|file| file.set_modified(saved))
+            .unwrap();
+
+        assert!(files.changed());
+        assert!(!files.changed());
+
+        let (_, source) = files.read().unwrap();
+        assert_eq!(source, "void main() { discard; }");
+
+        // a deleted file can't be reloaded, so it doesn't count as a change.
+        fs::remove_file(&vertex).unwrap();
+        assert!(!files.changed());
+        assert!(matches!(files.read(), Err(RenderError::ShaderFile(_))));
+
+        fs::remove_file(&fragment).unwrap();
+    }
+
+    #[test]
+    fn empty_index_buffers() {
+        let creator = IndexBufferCreator::new();
+
+        assert!(creator.get_index_buffer_u32().is_none());
+        assert!(creator.get_index_buffer_u16().is_none());
+        assert!(creator.get_index_buffer_u8().is_none());
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL driver, run with `cargo test -- --ignored`"]
+    fn index_buffer_creation() {
+        let context = HeadlessContext::new((1, 1)).unwrap();
+        let mut creator = IndexBufferCreator::new();
+
+        creator
+            .create_index_buffer_u16(&context, &[0, 1, 2], PrimitiveType::TrianglesList)
+            .unwrap()
+            .create_index_buffer_u16(&context, &[0, 1], PrimitiveType::LinesList)
+            .unwrap();
+
+        let latest = creator.get_index_buffer_u16().unwrap();
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest.get_primitives_type(), PrimitiveType::LinesList);
+        assert!(creator.get_index_buffer_u32().is_none());
+    }
+
+    #[test]
+    fn vertex_build_errors() {
+        let positions = [(0.0, 0.0, 0.0), (1.0, 0.0, 0.0), (0.0, 1.0, 0.0)];
+        let normals = [(0.0, 0.0, 1.0); 3];
+        let tex_pos = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)];
+
+        let vertices = Vertex::build_vertices_with_tex(&positions, &normals, &tex_pos).unwrap();
+        assert_eq!(vertices.len(), 3);
+        assert_eq!(vertices[2].position, [0.0, 1.0, 0.0]);
+        assert_eq!(vertices[2].normal, [0.0, 0.0, 1.0]);
+        assert_eq!(vertices[2].tex_pos, [0.0, 1.0]);
+
+        let error = Vertex::build_vertices(&positions, &normals[..2]).unwrap_err();
+        assert!(matches!(
+            error,
+            VertexBuildError::LengthMismatch {
+                attribute: "normals",
+                vertices: 3,
+                actual: 2
+            }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "expected 3 normals for 3 vertices, got 2"
+        );
+
+        let error = Vertex::build_vertices_with_tex(&positions, &normals, &tex_pos[..1]);
+        assert!(matches!(
+            RenderError::from(error.unwrap_err()),
+            RenderError::VertexCountMismatch {
+                expected: 3,
+                actual: 1
+            }
+        ));
+    }
+
+    #[test]
+    fn sprite_batching() {
+        let quad = |x: f32| sprite_vertices(Vec2::new(x, 10.0), Vec2::new(4.0, 2.0), 0.0);
+
+        // the first two sprites share a texture, so they're drawn with a single call.
+        let (vertices, runs) = batch_vertices([(0, quad(0.0)), (0, quad(10.0)), (1, quad(20.0))]);
+
+        assert_eq!(vertices.len(), 18);
+        assert_eq!(runs, vec![(0, 0..12), (1, 12..18)]);
+        assert_eq!(vertices[0].position, [-2.0, 9.0]);
+        assert_eq!(vertices[2].position, [2.0, 11.0]);
+        assert_eq!(vertices[2].tex_pos, [1.0, 1.0]);
+
+        // a quarter turn swaps the extents of the quad.
+        let rotated = sprite_vertices(
+            Vec2::new(0.0, 0.0),
+            Vec2::new(4.0, 2.0),
+            std::f32::consts::FRAC_PI_2,
+        );
+        assert!((rotated[0].position[0] - 1.0).abs() < 1e-5);
+        assert!((rotated[0].position[1] + 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn text_layout() {
+        let metrics = FontMetrics::parse(
+            "info face=\"Test\" size=16\n\
+             common lineHeight=16 base=12 scaleW=64 scaleH=32 pages=1\n\
+             char id=65 x=0 y=0 width=8 height=12 xoffset=1 yoffset=0 xadvance=10\n\
+             char id=66 x=8 y=0 width=8 height=12 xoffset=0 yoffset=0 xadvance=9\n",
+        )
+        .unwrap();
+
+        let quads = layout_text(&metrics, "AB", Vec2::new(100.0, 50.0), 2.0);
+        assert_eq!(quads.len(), 2);
+
+        // the top left corner of each glyph, advanced by the scaled advance of the previous glyph.
+        assert_eq!(quads[0][5].position, [102.0, 50.0]);
+        assert_eq!(quads[1][5].position, [120.0, 50.0]);
+        assert_eq!(quads[1][5].tex_pos, [0.125, 1.0]);
+        assert_eq!(quads[1][1].position, [136.0, 26.0]);
+
+        // a newline starts over at the left, one line further down.
+        let quads = layout_text(&metrics, "A\nB", Vec2::new(0.0, 50.0), 1.0);
+        assert_eq!(quads[1][5].position, [0.0, 34.0]);
+
+        // missing glyphs are drawn as a box reaching down to the baseline.
+        let quads = layout_text(&metrics, "?", Vec2::new(0.0, 50.0), 1.0);
+        assert_eq!(quads[0][5].position, [0.0, 50.0]);
+        assert_eq!(quads[0][1].position, [6.0, 38.0]);
+
+        assert_eq!(FontMetrics::parse("char id=65 x=a").unwrap_err().line, 1);
+    }
+
+    #[test]
+    fn transform_composition() {
+        let mut transform = Transform::new();
+        transform
+            .translate(1.0, 2.0, 3.0)
+            .rotate(std::f32::consts::FRAC_PI_2, (0.0, 0.0, 1.0));
+
+        let identity = Transform::new().matrix;
+        let translation = Matrix4::from([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [1.0, 2.0, 3.0, 1.0],
+        ]);
+        let rotation = Matrix4::from([
+            [0.0, 1.0, 0.0, 0.0],
+            [-1.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+
+        let expected = multiply(rotation, multiply(translation, identity)).inner();
+        let actual = transform.inner();
+
+        for (actual, expected) in actual.iter().flatten().zip(expected.iter().flatten()) {
+            assert!((actual - expected).abs() < 1e-6, "{:?}", transform.inner());
+        }
+
+        transform.scale(2.0, 2.0, 2.0);
+        assert!((transform.inner()[2][2] - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn transform_hierarchy() {
+        let mut world = World::<()>::new();
+        world.with_system(SystemType::Loop, TransformHierarchySystem);
+
+        let translated = |x: f32, y: f32, z: f32| {
+            let mut transform = Transform::new();
+            transform.translate(x, y, z);
+            transform
+        };
+
+        let arm = world.entity();
+        let forearm = world.entity();
+        let hand = world.entity();
+
+        // the hand is added before its parent, so the order of the components doesn't follow the hierarchy.
+        world
+            .with(hand, translated(0.0, 0.0, 3.0))
+            .with(hand, Parent(forearm));
+        world.with(arm, translated(1.0, 0.0, 0.0));
+        world
+            .with(forearm, translated(0.0, 2.0, 0.0))
+            .with(forearm, Parent(arm));
+
+        world.update(SystemType::Loop, &());
+
+        // the positions are read the same way the renderer sorts the meshes by.
+        let position = |world: &mut World<()>, entity| {
+            positions(&mut world.entity_manager, &[entity])[0].1.inner()
+        };
+
+        assert_eq!(position(&mut world, hand), [1.0, 2.0, 3.0]);
+        assert_eq!(position(&mut world, forearm), [1.0, 2.0, 0.0]);
+        assert_eq!(position(&mut world, arm), [1.0, 0.0, 0.0]);
+        assert!(world
+            .entity_manager
+            .query_entity::<WorldTransform>(arm)
+            .0
+            .is_none());
+
+        // a removed parent detaches the child, which becomes a root.
+        world.entity_manager.remove_component::<Transform>(arm);
+        world.update(SystemType::Loop, &());
+
+        assert_eq!(position(&mut world, forearm), [0.0, 2.0, 0.0]);
+        assert_eq!(position(&mut world, hand), [0.0, 2.0, 3.0]);
+        assert!(world
+            .entity_manager
+            .query_entity::<Parent>(forearm)
+            .0
+            .is_none());
+
+        // entities in a cycle are skipped.
+        let mut parents = HashMap::new();
+        parents.insert(1, 2);
+        parents.insert(2, 1);
+        parents.insert(3, 1);
+
+        let locals = (1..=3)
+            .map(|entity| (entity, translated(1.0, 0.0, 0.0).matrix))
+            .collect::<HashMap<_, _>>();
+        let (worlds, detached) = world_matrices(&parents, &locals);
+
+        assert!(worlds.is_empty());
+        assert!(detached.is_empty());
+    }
+
+    #[test]
+    fn matrix_identity() {
+        let m = Matrix4::from([
+            [1.0, 2.0, 3.0, 4.0],
+            [5.0, 6.0, 7.0, 8.0],
+            [9.0, 10.0, 11.0, 12.0],
+            [13.0, 14.0, 15.0, 16.0],
+        ]);
+
+        assert_eq!((Matrix4::identity() * m).inner(), m.inner());
+        assert_eq!((m * Matrix4::identity()).inner(), m.inner());
+        assert_eq!((m * m).inner(), multiply(m, m).inner());
+
+        assert_eq!(
+            Transform::identity().matrix.inner(),
+            Matrix4::identity().inner()
+        );
+        assert_eq!(Matrix4::new().inner(), [[0.0; 4]; 4]);
+    }
+
+    #[test]
+    fn look_at_matches_camera() {
+        let camera = Camera::new([1.0, 2.0, 3.0], [0.5, -0.25, 1.0], [0.0, 1.0, 0.0]);
+        let eye = Vec3::new(1.0, 2.0, 3.0);
+        let target = eye + Vec3::new(0.5, -0.25, 1.0);
+
+        let look_at = Matrix4::look_at(eye, target, Vec3::new(0.0, 1.0, 0.0)).inner();
+        let expected = camera.world_matrix().inverse().unwrap().inner();
+
+        for (actual, expected) in look_at.iter().flatten().zip(expected.iter().flatten()) {
+            assert!((actual - expected).abs() < 1e-5, "{:?}", look_at);
+        }
+
+        assert_eq!(camera.view_matrix().inner(), look_at);
+
+        let identity = Matrix4::identity().inner();
+        assert_eq!(
+            Matrix4::look_at(eye, eye, Vec3::new(0.0, 1.0, 0.0)).inner(),
+            identity
+        );
+        assert_eq!(
+            Matrix4::look_at(
+                eye,
+                eye + Vec3::new(0.0, 2.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0)
+            )
+            .inner(),
+            identity
+        );
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL driver, run with `cargo test -- --ignored`"]
+    fn colored_mesh_render() {
+        let context = HeadlessContext::new((4, 4)).unwrap();
+        let mut world = World::<HeadlessContext>::new();
+        world.insert_resource(Camera::new(
+            [0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 1.0, 0.0],
+        ));
+
+        // a single triangle which covers the whole view.
+        let vertices = [
+            ColoredVertex {
+                position: [-3.0, -1.0, 1.0],
+                color: [0.0, 1.0, 0.0],
+            },
+            ColoredVertex {
+                position: [3.0, -1.0, 1.0],
+                color: [0.0, 1.0, 0.0],
+            },
+            ColoredVertex {
+                position: [0.0, 3.0, 1.0],
+                color: [0.0, 1.0, 0.0],
+            },
+        ];
+        let mesh = ColoredMesh::new(
+            &context,
+            &vertices,
+            NoIndices(PrimitiveType::TrianglesList),
+            Winding::CounterClockwise,
+        )
+        .unwrap();
+
+        let triangle = world.entity();
+        world.with(triangle, mesh).with(
+            triangle,
+            MeshUniform::empty().orthographic(Orthographic::new(-1.0, 1.0, -1.0, 1.0, -10.0, 10.0)),
+        );
+
+        let target = world.entity();
+        let render_target = RenderTarget::new(&context, (4, 4)).unwrap();
+        world.with(target, render_target);
+
+        world.add_render_systems();
+        world.update_once(&context);
+
+        let (target,) = world.entity_manager.query_entity::<RenderTarget>(target);
+        assert_eq!(target.unwrap().read_pixel(2, 2), Some((0, 255, 0, 255)));
+    }
+
+    #[test]
+    fn gizmo_lines() {
+        let from = Vec3::new(1.0, 2.0, 3.0);
+        let to = Vec3::new(-4.0, 5.0, 0.5);
+
+        let line = line_vertices(from, to, [1.0, 1.0, 0.0]);
+
+        assert_eq!(line.len(), 2);
+        assert_eq!(line[0].position, [1.0, 2.0, 3.0]);
+        assert_eq!(line[1].position, [-4.0, 5.0, 0.5]);
+        assert!(line.iter().all(|vertex| vertex.color == [1.0, 1.0, 0.0]));
+
+        let axes = axes_vertices(from, 2.0);
+
+        for (index, axis) in axes.chunks_exact(2).enumerate() {
+            let mut end = from.inner();
+            end[index] += 2.0;
+
+            assert_eq!(axis[0].position, from.inner());
+            assert_eq!(axis[1].position, end);
+            assert_eq!(axis[0].color, AXIS_COLORS[index]);
+        }
+    }
+
+    #[test]
+    fn mesh_aabb() {
+        // the corners of a cube with an edge length of 2, centered at (1, -2, 0.5).
+        let corners = (0..8).map(|corner| {
+            [
+                if corner & 1 == 0 { 0.0 } else { 2.0 },
+                if corner & 2 == 0 { -3.0 } else { -1.0 },
+                if corner & 4 == 0 { -0.5 } else { 1.5 },
+            ]
+        });
+
+        let aabb = Aabb::from_points(corners);
+
+        assert_eq!(aabb.min.inner(), [0.0, -3.0, -0.5]);
+        assert_eq!(aabb.max.inner(), [2.0, -1.0, 1.5]);
+        assert_eq!(aabb.center().inner(), [1.0, -2.0, 0.5]);
+        assert_eq!(aabb.size().inner(), [2.0, 2.0, 2.0]);
+
+        let empty = Aabb::from_points([]);
+
+        assert_eq!(empty.min.inner(), [0.0; 3]);
+        assert_eq!(empty.max.inner(), [0.0; 3]);
+    }
+
+    #[test]
+    fn screen_ray_picking() {
+        let camera = Camera::new([0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]);
+        let perspective = Perspective::from_dimensions(800.0, 600.0, 3.0, 100.0, 0.1);
+
+        // a ray through the center of the screen runs down the view axis, starting on the near plane.
+        let (origin, direction) = camera.screen_ray(0.0, 0.0, &perspective.matrix());
+
+        assert!(
+            (origin - Vec3::new(0.0, 0.0, 0.1)).length() < 1e-4,
+            "{:?}",
+            origin
+        );
+        assert!(
+            (direction - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-4,
+            "{:?}",
+            direction
+        );
+
+        let centered = Aabb::new([-1.0, -1.0, 4.0], [1.0, 1.0, 6.0]);
+        let t = ray_intersects_aabb(origin, direction, &centered).unwrap();
+
+        assert!((t - 3.9).abs() < 1e-3, "{}", t);
+
+        // the ray is parallel to the x and y slabs and runs beside the box.
+        let beside = Aabb::new([2.0, -1.0, 4.0], [4.0, 1.0, 6.0]);
+        assert!(ray_intersects_aabb(origin, direction, &beside).is_none());
+
+        let behind = Aabb::new([-1.0, -1.0, -6.0], [1.0, 1.0, -4.0]);
+        assert!(ray_intersects_aabb(origin, direction, &behind).is_none());
+
+        let around = Aabb::new([-1.0, -1.0, -1.0], [1.0, 1.0, 1.0]);
+        assert_eq!(ray_intersects_aabb(origin, direction, &around), Some(0.0));
+
+        let mut world = World::<()>::new();
+        let unit = Aabb::new([-1.0, -1.0, -1.0], [1.0, 1.0, 1.0]);
+        let translated = |x: f32, y: f32, z: f32| {
+            let mut matrix = Matrix4::identity();
+            matrix[3] = Vec4::new(x, y, z, 1.0);
+            WorldTransform(matrix)
+        };
+
+        let far = world.entity();
+        world
+            .with::<Aabb>(far, unit)
+            .with::<WorldTransform>(far, translated(0.0, 0.0, 10.0));
+
+        let near = world.entity();
+        world
+            .with::<Aabb>(near, unit)
+            .with::<WorldTransform>(near, translated(0.0, 0.0, 5.0));
+
+        let aside = world.entity();
+        world
+            .with::<Aabb>(aside, unit)
+            .with::<WorldTransform>(aside, translated(5.0, 0.0, 2.0));
+
+        let (entity, distance) = pick(&mut world.entity_manager, origin, direction).unwrap();
+
+        assert_eq!(entity, near);
+        assert!((distance - 3.9).abs() < 1e-3, "{}", distance);
+
+        let holder = world.entity();
+        world
+            .with::<Camera>(holder, camera)
+            .with::<Perspective>(holder, perspective)
+            .with_system(SystemType::Loop, PickingSystem)
+            .insert_resource(PickRequest {
+                ndc_x: 0.0,
+                ndc_y: 0.0,
+            });
+
+        world.update(SystemType::Loop, &());
+
+        assert_eq!(world.resource::<Picked>(), Some(&Picked(Some(near))));
+        assert!(world.resource::<PickRequest>().is_none());
+    }
+
+    #[test]
+    fn blend_presets() {
+        let opaque = DrawParametersComponent::opaque().0;
+
+        assert_eq!(opaque.blend, Blend::default());
+        assert_eq!(opaque.depth.test, DepthTest::IfLess);
+        assert!(opaque.depth.write);
+
+        let alpha = DrawParametersComponent::alpha_blend().0;
+        let expected = BlendingFunction::Addition {
+            source: LinearBlendingFactor::SourceAlpha,
+            destination: LinearBlendingFactor::OneMinusSourceAlpha,
+        };
+
+        assert_eq!(alpha.blend.color, expected);
+        assert_eq!(alpha.blend.alpha, expected);
+        assert_eq!(alpha.depth.test, DepthTest::IfLess);
+        assert!(!alpha.depth.write);
+
+        let additive = DrawParametersComponent::additive().0;
+
+        assert_eq!(
+            additive.blend.color,
+            BlendingFunction::Addition {
+                source: LinearBlendingFactor::SourceAlpha,
+                destination: LinearBlendingFactor::One,
+            }
+        );
+        assert_eq!(
+            additive.blend.alpha,
+            BlendingFunction::Addition {
+                source: LinearBlendingFactor::Zero,
+                destination: LinearBlendingFactor::One,
+            }
+        );
+        assert_eq!(additive.depth.test, DepthTest::IfLess);
+        assert!(!additive.depth.write);
+    }
+
+    #[test]
+    fn vec2_arithmetic() {
+        let mut a = Vec2::new(3.0, 4.0);
+        let b = Vec2::from([1.0, -2.0]);
+
+        assert_eq!(a.inner(), [3.0, 4.0]);
+        assert_eq!((a[0], a[1]), (3.0, 4.0));
+        assert_eq!(b.inner(), [1.0, -2.0]);
+
+        assert_eq!(a.dot(b), -5.0);
+        assert_eq!(a.length(), 5.0);
+        assert_eq!(a.normalize().inner(), [0.6, 0.8]);
+        assert_eq!(Vec2::new(0.0, 0.0).normalize().inner(), [0.0, 0.0]);
+
+        assert_eq!((a + b).inner(), [4.0, 2.0]);
+        assert_eq!((a - b).inner(), [2.0, 6.0]);
+        assert_eq!((b * 2.0).inner(), [2.0, -4.0]);
+
+        a[1] = -1.0;
+        assert_eq!(a.inner(), [3.0, -1.0]);
+    }
+
+    #[test]
+    fn instance_batching() {
+        let batches = instance_batches(5, 2).collect::<Vec<_>>();
+        assert_eq!(batches, vec![0..2, 2..4, 4..5]);
+
+        assert_eq!(instance_batches(4, 2).count(), 2);
+        assert_eq!(instance_batches(3, 0).count(), 3);
+        assert_eq!(instance_batches(0, 2).count(), 0);
+        assert_eq!(
+            instance_batches(100, MAX_INSTANCES_PER_BATCH).collect::<Vec<_>>(),
+            vec![0..100]
+        );
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL driver, run with `cargo test -- --ignored`"]
+    fn chunked_instances_render() {
+        // squeezes the quad into a column one pixel wide, moved along x by the position of the instance.
+        const COLUMN_VERTEX_SHADER: &str = r#"
+            #version 140
+
+            in vec3 position;
+            in vec3 world_position;
+
+            void main() {
+                gl_Position = vec4(position.x * 0.1 + world_position.x, position.y, 0.0, 1.0);
+            }
+        "#;
+
+        let (context, mut world) = headless_world((10, 10));
+
+        let config = world.entity();
+        world.with(config, RenderConfig::new().max_instances_per_batch(2));
+
+        let mesh = world.entity();
+        let quad = Mesh::new(
+            &context,
+            &fullscreen_quad_vertices(),
+            NoIndices(PrimitiveType::TriangleStrip).into(),
+            COLUMN_VERTEX_SHADER,
+            RED_FRAGMENT_SHADER,
+            Winding::CounterClockwise,
+        )
+        .unwrap();
+        world.with(mesh, quad);
+
+        // more than twice as many instances as fit into a batch, every one covering every other pixel column.
+        let instances = (0..5)
+            .map(|column| {
+                (
+                    Vec3::new(-0.9 + 0.4 * column as f32, 0.0, 0.0),
+                    Quaternion::identity(),
+                    Vec3::new(1.0, 1.0, 1.0),
+                )
+            })
+            .collect::<Vec<_>>();
+        world.with_instances(mesh, &instances);
+
+        let target = world.entity();
+        let render_target = RenderTarget::new(&context, (10, 10)).unwrap();
+        world.with(target, render_target);
+
+        world.add_render_systems();
+        world.update_once(&context);
+
+        // every batch is drawn with its own call, so the instances of the last batch reach the target as well.
+        let (target,) = world.entity_manager.query_entity::<RenderTarget>(target);
+        let target = target.unwrap();
+        let drawn = (0..10)
+            .filter(|column| target.read_pixel(*column, 5) == Some((255, 0, 0, 255)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(drawn, vec![0, 2, 4, 6, 8]);
+
+        let (buffer,) = world.entity_manager.query_entity::<InstanceBuffer>(mesh);
+        let lengths = buffer
+            .unwrap()
+            .buffers
+            .iter()
+            .map(|buffer| buffer.len())
+            .collect::<Vec<_>>();
+
+        assert_eq!(lengths, vec![2, 2, 1]);
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL driver, run with `cargo test -- --ignored`"]
+    fn headless_screenshot() {
+        // squeezes the quad into the bottom half of the frame.
+        const BOTTOM_VERTEX_SHADER: &str = r#"
+            #version 140
+
+            in vec3 position;
+
+            void main() {
+                gl_Position = vec4(position.x, position.y * 0.5 - 0.5, 0.0, 1.0);
+            }
+        "#;
+
+        let (context, mut world) = headless_world((2, 4));
+        world.insert_resource(ClearColor::new([0.0, 0.0, 1.0]));
+
+        let mesh = world.entity();
+        let quad = Mesh::new(
+            &context,
+            &fullscreen_quad_vertices(),
+            NoIndices(PrimitiveType::TriangleStrip).into(),
+            BOTTOM_VERTEX_SHADER,
+            RED_FRAGMENT_SHADER,
+            Winding::CounterClockwise,
+        )
+        .unwrap();
+        world.with(mesh, quad);
+
+        world.add_render_systems();
+        world.update_once(&context);
+
+        // the first row of the image is the top of the frame, which only shows the clear color.
+        let image = read_front_buffer(&context).unwrap();
+        let rows = (0..4).map(|y| image.get_pixel(0, y).0).collect::<Vec<_>>();
+
+        assert_eq!(image.dimensions(), (2, 4));
+        assert_eq!(
+            rows,
+            vec![
+                [0, 0, 255, 255],
+                [0, 0, 255, 255],
+                [255, 0, 0, 255],
+                [255, 0, 0, 255]
+            ]
+        );
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL driver, run with `cargo test -- --ignored`"]
+    fn sprites_without_meshes() {
+        let context = HeadlessContext::new((4, 4)).unwrap();
+        let mut world = World::<HeadlessContext>::new();
+        world.insert_resource(ClearColor::new([0.0, 0.0, 1.0]));
+
+        // a green sprite covering the 2x2 pixels in the middle of the frame, and neither a mesh nor a camera.
+        let green = RawImage2d::from_raw_rgba(vec![0u8, 255, 0, 255], (1, 1));
+        let texture = Rc::new(Texture2d::new(&context, green).unwrap());
+        let sprite = world.entity();
+        world.with(sprite, Sprite::new(texture, [2.0, 2.0]).size([2.0, 2.0]));
+
+        world.add_render_systems();
+        world.update_once(&context);
+
+        let image = read_front_buffer(&context).unwrap();
+        assert_eq!(image.get_pixel(0, 0).0, [0, 0, 255, 255]);
+        assert_eq!(image.get_pixel(2, 2).0, [0, 255, 0, 255]);
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL driver, run with `cargo test -- --ignored`"]
+    fn meshes_share_frame() {
+        // squeeze the quad into the left and the right half of the frame.
+        const LEFT_VERTEX_SHADER: &str = r#"
+            #version 140
+
+            in vec3 position;
+
+            void main() {
+                gl_Position = vec4(position.x * 0.5 - 0.5, position.y, 0.0, 1.0);
+            }
+        "#;
+        const RIGHT_VERTEX_SHADER: &str = r#"
+            #version 140
+
+            in vec3 position;
+
+            void main() {
+                gl_Position = vec4(position.x * 0.5 + 0.5, position.y, 0.0, 1.0);
+            }
+        "#;
+        const GREEN_FRAGMENT_SHADER: &str = r#"
+            #version 140
+
+            out vec4 color;
+
+            void main() {
+                color = vec4(0.0, 1.0, 0.0, 1.0);
+            }
+        "#;
+
+        let (context, mut world) = headless_world((4, 4));
+
+        for (vertex_shader, fragment_shader) in [
+            (LEFT_VERTEX_SHADER, RED_FRAGMENT_SHADER),
+            (RIGHT_VERTEX_SHADER, GREEN_FRAGMENT_SHADER),
+        ] {
+            let mesh = world.entity();
+            let half = Mesh::new(
+                &context,
+                &fullscreen_quad_vertices(),
+                NoIndices(PrimitiveType::TriangleStrip).into(),
+                vertex_shader,
+                fragment_shader,
+                Winding::CounterClockwise,
+            )
+            .unwrap();
+            world.with(mesh, half);
+        }
+
+        world.add_render_systems();
+        world.update_once(&context);
+
+        // the second mesh doesn't clear the frame the first one was drawn into.
+        let image = read_front_buffer(&context).unwrap();
+        assert_eq!(image.get_pixel(0, 2).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(3, 2).0, [0, 255, 0, 255]);
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL driver, run with `cargo test -- --ignored`"]
+    fn meshes_without_camera() {
+        let context = HeadlessContext::new((4, 4)).unwrap();
+        let mut world = World::<HeadlessContext>::new();
+        world.insert_resource(ClearColor::new([0.0, 0.0, 1.0]));
+
+        let mesh = world.entity();
+        let quad = Mesh::fullscreen_quad(&context, RED_FRAGMENT_SHADER).unwrap();
+        world.with(mesh, quad);
+
+        // the meshes are skipped and the missing camera is reported, but the frame is still cleared.
+        world.add_render_systems();
+        world.update_once(&context);
+
+        let image = read_front_buffer(&context).unwrap();
+        assert_eq!(image.get_pixel(2, 2).0, [0, 0, 255, 255]);
+    }
+}
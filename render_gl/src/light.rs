@@ -0,0 +1,215 @@
+use ecs::{
+    entity::{EntityManager, EntityQueryTable},
+    system::System,
+};
+use ecs_macro::EntityComponent;
+
+use crate::{container::Vec3, uniform::MeshUniform};
+
+/// A light radiating in every direction from a point, fading out at `range`.
+#[derive(EntityComponent, Debug, Clone, Copy)]
+pub struct PointLight {
+    pub position: Vec3,
+    pub color: Vec3,
+    pub range: f32,
+}
+
+impl PointLight {
+    pub fn new(position: impl Into<Vec3>, color: impl Into<Vec3>, range: f32) -> Self {
+        Self {
+            position: position.into(),
+            color: color.into(),
+            range,
+        }
+    }
+}
+
+/// A light infinitely far away, whose rays are parallel, such as the sun.
+#[derive(EntityComponent, Debug, Clone, Copy)]
+pub struct DirectionalLight {
+    pub direction: Vec3,
+    pub color: Vec3,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: impl Into<Vec3>, color: impl Into<Vec3>) -> Self {
+        Self {
+            direction: direction.into(),
+            color: color.into(),
+        }
+    }
+}
+
+/// A light shining from a point into a cone around `direction`.
+#[derive(EntityComponent, Debug, Clone, Copy)]
+pub struct SpotLight {
+    pub position: Vec3,
+    pub direction: Vec3,
+    /// The angle between the direction and the edge of the cone, in radians.
+    pub angle: f32,
+    pub color: Vec3,
+}
+
+impl SpotLight {
+    pub fn new(
+        position: impl Into<Vec3>,
+        direction: impl Into<Vec3>,
+        angle: f32,
+        color: impl Into<Vec3>,
+    ) -> Self {
+        Self {
+            position: position.into(),
+            direction: direction.into(),
+            angle,
+            color: color.into(),
+        }
+    }
+}
+
+/// The kind of a [Light], passed to the shaders in the `u_light_types` array of [MeshUniform].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightType {
+    Directional = 0,
+    Point = 1,
+    Spot = 2,
+}
+
+/// A light as it's passed to the shaders, gathered from one of the light components by [gather_lights].
+///
+/// Lights which don't fade out have a `range` of `0`, lights which aren't cones an `angle` of `0`.
+#[derive(Debug, Clone, Copy)]
+pub struct Light {
+    pub light_type: LightType,
+    /// The position of point and spot lights, zero for directional lights.
+    pub position: Vec3,
+    /// The direction of directional and spot lights, zero for point lights.
+    pub direction: Vec3,
+    pub color: Vec3,
+    pub range: f32,
+    pub angle: f32,
+}
+
+impl Light {
+    /// Returns the direction of directional lights and the position of all other lights, which is what the shaders
+    /// receive in the `u_lights` array.
+    pub fn vector(&self) -> Vec3 {
+        match self.light_type {
+            LightType::Directional => self.direction,
+            LightType::Point | LightType::Spot => self.position,
+        }
+    }
+}
+
+/// A plain vector is a white directional light, as passed to `MeshUniform::light`.
+impl From<Vec3> for Light {
+    fn from(direction: Vec3) -> Self {
+        DirectionalLight::new(direction, [1.0, 1.0, 1.0]).into()
+    }
+}
+
+impl From<[f32; 3]> for Light {
+    fn from(direction: [f32; 3]) -> Self {
+        Vec3::from(direction).into()
+    }
+}
+
+impl From<DirectionalLight> for Light {
+    fn from(light: DirectionalLight) -> Self {
+        Self {
+            light_type: LightType::Directional,
+            position: Vec3::new(0.0, 0.0, 0.0),
+            direction: light.direction,
+            color: light.color,
+            range: 0.0,
+            angle: 0.0,
+        }
+    }
+}
+
+impl From<PointLight> for Light {
+    fn from(light: PointLight) -> Self {
+        Self {
+            light_type: LightType::Point,
+            position: light.position,
+            direction: Vec3::new(0.0, 0.0, 0.0),
+            color: light.color,
+            range: light.range,
+            angle: 0.0,
+        }
+    }
+}
+
+impl From<SpotLight> for Light {
+    fn from(light: SpotLight) -> Self {
+        Self {
+            light_type: LightType::Spot,
+            position: light.position,
+            direction: light.direction,
+            color: light.color,
+            range: 0.0,
+            angle: light.angle,
+        }
+    }
+}
+
+/// Collects the lights of all light components, directional lights first, then point lights and spot lights.
+pub fn gather_lights(manager: &mut EntityManager, table: &mut EntityQueryTable) -> Vec<Light> {
+    let mut lights = Vec::new();
+
+    let entities = table
+        .query_single::<DirectionalLight>(manager)
+        .cloned()
+        .unwrap_or_default();
+    for entity in entities {
+        if let Some(light) = manager.query_entity::<DirectionalLight>(entity).0 {
+            lights.push((*light).into());
+        }
+    }
+
+    let entities = table
+        .query_single::<PointLight>(manager)
+        .cloned()
+        .unwrap_or_default();
+    for entity in entities {
+        if let Some(light) = manager.query_entity::<PointLight>(entity).0 {
+            lights.push((*light).into());
+        }
+    }
+
+    let entities = table
+        .query_single::<SpotLight>(manager)
+        .cloned()
+        .unwrap_or_default();
+    for entity in entities {
+        if let Some(light) = manager.query_entity::<SpotLight>(entity).0 {
+            lights.push((*light).into());
+        }
+    }
+
+    lights
+}
+
+/// Gathers the light components every frame, see [gather_lights], and hands them to every `MeshUniform`.
+///
+/// The lights of every mesh are replaced, so meshes have no lights while there are no light components.
+pub struct LightSystem;
+
+impl<T> System<T> for LightSystem {
+    fn update(
+        &mut self,
+        manager: &mut EntityManager,
+        table: &mut EntityQueryTable,
+        _: &T,
+    ) -> Option<()> {
+        let lights = gather_lights(manager, table);
+        let meshes = table.query_single::<MeshUniform>(manager)?.clone();
+
+        for entity in meshes {
+            if let Some(uniform) = manager.query_entity::<MeshUniform>(entity).0 {
+                uniform.set_lights(lights.clone());
+            }
+        }
+
+        None
+    }
+}
@@ -1,15 +1,175 @@
+use std::{cell::OnceCell, path::Path, rc::Rc};
+
 use ecs_macro::EntityComponent;
 use glium::{
-    index::IndicesSource, texture::Texture3d, Display, Program, ProgramCreationError, Texture2d,
-    VertexBuffer,
+    backend::Facade,
+    draw_parameters::BackfaceCullingMode,
+    index::{IndicesSource, NoIndices, PrimitiveType},
+    texture::{CompressedTexture2d, Cubemap, Texture3d},
+    IndexBuffer, Program, Texture2d, VertexBuffer,
+};
+
+use crate::{
+    bounds::Aabb,
+    container::Vec3,
+    draw::vertex::{ColoredVertex, ToBuffer, Vertex},
+    error::RenderError,
+    obj::ObjModel,
+    program::ProgramCache,
+    shader::ShaderFiles,
 };
 
-use crate::draw::vertex::{ToBuffer, Vertex};
+/// A pass-through vertex shader for [Mesh::fullscreen_quad], which hands the texture coordinates to the fragment
+/// shader as `v_tex_pos`.
+pub const FULLSCREEN_VERTEX_SHADER: &str = r#"
+#version 140
+
+in vec3 position;
+in vec2 tex_pos;
+
+out vec2 v_tex_pos;
+
+void main() {
+    v_tex_pos = tex_pos;
+    gl_Position = vec4(position.xy, 0.0, 1.0);
+}
+"#;
+
+/// The vertex shader of a [ColoredMesh], which hands the vertex colors to the fragment shader as `v_color`.
+pub const COLORED_VERTEX_SHADER: &str = r#"
+#version 140
+
+in vec3 position;
+in vec3 color;
+
+out vec3 v_color;
+
+uniform mat4 matrix;
+uniform mat4 view;
+uniform mat4 perspective;
+
+void main() {
+    v_color = color;
+    gl_Position = perspective * view * matrix * vec4(position, 1.0);
+}
+"#;
+
+/// The fragment shader of a [ColoredMesh], which draws the interpolated vertex colors.
+pub const COLORED_FRAGMENT_SHADER: &str = r#"
+#version 140
+
+in vec3 v_color;
+out vec4 color;
+
+void main() {
+    color = vec4(v_color, 1.0);
+}
+"#;
+
+/// Returns the vertices of a quad covering the whole clip space, with texture coordinates in `[0, 1]`.
+///
+/// The vertices are ordered to be drawn as a `PrimitiveType::TriangleStrip`.
+pub fn fullscreen_quad_vertices() -> [Vertex; 4] {
+    [
+        crate::vertex!([-1.0, -1.0, 0.0], [0.0, 0.0]),
+        crate::vertex!([1.0, -1.0, 0.0], [1.0, 0.0]),
+        crate::vertex!([-1.0, 1.0, 0.0], [0.0, 1.0]),
+        crate::vertex!([1.0, 1.0, 0.0], [1.0, 1.0]),
+    ]
+}
+
+/// The order in which the vertices of a front facing triangle appear on screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Winding {
+    Clockwise,
+    /// The OpenGL default, which the culling modes of `DrawParameters` are expected to be configured for.
+    #[default]
+    CounterClockwise,
+}
+
+impl Winding {
+    /// Returns the opposite winding.
+    pub fn flipped(&self) -> Self {
+        match self {
+            Winding::Clockwise => Winding::CounterClockwise,
+            Winding::CounterClockwise => Winding::Clockwise,
+        }
+    }
+
+    /// Adjusts a culling mode which is configured for counter-clockwise front faces to a mesh with this winding, so
+    /// its front faces are kept and its back faces are culled.
+    pub fn culling(&self, mode: BackfaceCullingMode) -> BackfaceCullingMode {
+        match (self, mode) {
+            (Winding::CounterClockwise, mode) => mode,
+            (Winding::Clockwise, BackfaceCullingMode::CullClockwise) => {
+                BackfaceCullingMode::CullCounterClockwise
+            }
+            (Winding::Clockwise, BackfaceCullingMode::CullCounterClockwise) => {
+                BackfaceCullingMode::CullClockwise
+            }
+            (Winding::Clockwise, mode) => mode,
+        }
+    }
+
+    /// Flips the winding of a triangle list in place, by swapping the last two indices of every triangle.
+    ///
+    /// This allows converting imported meshes to counter-clockwise winding at load time.
+    pub fn flip_indices<I: Copy>(indices: &mut [I]) {
+        for triangle in indices.chunks_exact_mut(3) {
+            triangle.swap(1, 2);
+        }
+    }
+}
 
 #[derive(Debug)]
 pub enum TextureType {
     Texture2d(Texture2d),
     Texture3d(Texture3d),
+    /// A block compressed texture, see [crate::dds::DdsImage].
+    Compressed(CompressedTexture2d),
+    /// Six square faces, sampled by direction, as used for skyboxes and environment maps.
+    Cubemap(Cubemap),
+}
+
+/// Checks that every index refers to one of the `vertices`.
+pub fn check_indices(indices: &[u32], vertices: usize) -> Result<(), RenderError> {
+    match indices.iter().find(|index| **index as usize >= vertices) {
+        Some(index) => Err(RenderError::IndexOutOfRange {
+            index: *index,
+            vertices,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// The indices a [Mesh] is drawn with.
+pub enum MeshIndices {
+    /// Indices which live outside of the mesh, such as `NoIndices` or an index buffer of an `IndexBufferCreator`.
+    Source(IndicesSource<'static>),
+    /// An index buffer owned by the mesh.
+    Owned(Box<IndexBuffer<u32>>),
+}
+
+impl MeshIndices {
+    /// Returns the indices in the form they're handed to a draw call.
+    pub fn source(&self) -> IndicesSource<'_> {
+        match self {
+            MeshIndices::Source(source) => source.clone(),
+            MeshIndices::Owned(buffer) => (&**buffer).into(),
+        }
+    }
+}
+
+impl From<IndicesSource<'static>> for MeshIndices {
+    fn from(value: IndicesSource<'static>) -> Self {
+        MeshIndices::Source(value)
+    }
+}
+
+impl From<IndexBuffer<u32>> for MeshIndices {
+    fn from(value: IndexBuffer<u32>) -> Self {
+        MeshIndices::Owned(Box::new(value))
+    }
 }
 
 /// A struct representing a 3D mesh.
@@ -27,14 +187,34 @@ pub struct Mesh {
     /// The index buffer for the mesh.
     ///
     /// The index buffer specifies how the vertices in the vertex buffer should be connected to form the mesh. It is an array of integers that reference the vertices in the vertex buffer.
-    pub index_buffer: IndicesSource<'static>,
+    pub index_buffer: MeshIndices,
     /// The program for rendering the mesh.
     ///
     /// The program consists of a vertex shader and a fragment shader. The vertex shader is responsible for transforming the vertices of the mesh, and the fragment shader is responsible for applying colors or textures to the surface of the mesh.
-    pub program: Program,
+    ///
+    /// The program is reference counted, so meshes created through a [ProgramCache] share it.
+    pub program: Rc<Program>,
+    /// The winding of the front faces of the mesh, which the backface culling mode is adjusted to while rendering.
+    pub winding: Winding,
+    /// The bounds of the vertices, computed by the first [Mesh::local_aabb] call.
+    aabb: OnceCell<(Vec3, Vec3)>,
 }
 
 impl Mesh {
+    /// Returns the minimum and maximum corner of the axis-aligned box around the vertex positions, in the local space
+    /// of the mesh, see [Aabb::from_points].
+    ///
+    /// The vertices are read back from the vertex buffer once, later calls return the cached bounds. A mesh whose
+    /// vertices can't be read is treated like an empty one. If the vertex buffer is replaced, the bounds are stale.
+    pub fn local_aabb(&self) -> (Vec3, Vec3) {
+        *self.aabb.get_or_init(|| {
+            let vertices = self.vertex_buffer.read().unwrap_or_default();
+            let aabb = Aabb::from_points(vertices.iter().map(|vertex| vertex.position));
+
+            (aabb.min, aabb.max)
+        })
+    }
+
     /// Creates a new `Mesh` instance.
     ///
     /// # Arguments
@@ -44,44 +224,242 @@ impl Mesh {
     /// * `index_buffer` - The index buffer for the mesh.
     /// * `vertex_shader` - The vertex shader source code.
     /// * `fragment_shader` - The fragment shader source code.
+    /// * `winding` - The winding of the front faces of the mesh.
     ///
     /// # Returns
     ///
-    /// A new `Mesh` instance, or a `ProgramCreationError` if there was a problem creating the program.
+    /// A new `Mesh` instance, or a [RenderError] if there was a problem creating the vertex buffer or the program.
     pub fn new(
-        display: &Display,
+        display: &impl Facade,
         vertices: &[Vertex],
         index_buffer: IndicesSource<'static>,
         vertex_shader: &'static str,
         fragment_shader: &'static str,
-    ) -> Result<Self, ProgramCreationError> {
-        let buffer = Vertex::to_buffer(display, vertices).unwrap();
-        let program = Program::from_source(display, vertex_shader, fragment_shader, None)?;
+        winding: Winding,
+    ) -> Result<Self, RenderError> {
+        let buffer = Vertex::to_buffer(display, vertices)?;
+        let program = Rc::new(Program::from_source(
+            display,
+            vertex_shader,
+            fragment_shader,
+            None,
+        )?);
 
         let constructed = Self {
             vertex_buffer: buffer,
-            index_buffer,
+            index_buffer: index_buffer.into(),
             program,
+            winding,
+            aabb: OnceCell::new(),
         };
 
         Ok(constructed)
     }
 
     pub fn buffered(
-        display: &Display,
+        display: &impl Facade,
         vertices: VertexBuffer<Vertex>,
         index_buffer: impl Into<IndicesSource<'static>>,
         vertex_shader: &'static str,
         fragment_shader: &'static str,
-    ) -> Result<Self, ProgramCreationError> {
-        let program = Program::from_source(display, vertex_shader, fragment_shader, None)?;
+        winding: Winding,
+    ) -> Result<Self, RenderError> {
+        let program = Rc::new(Program::from_source(
+            display,
+            vertex_shader,
+            fragment_shader,
+            None,
+        )?);
 
         let constructed = Self {
             vertex_buffer: vertices,
-            index_buffer: index_buffer.into(),
+            index_buffer: MeshIndices::Source(index_buffer.into()),
             program,
+            winding,
+            aabb: OnceCell::new(),
         };
 
         Ok(constructed)
     }
+
+    /// Creates a mesh like [Mesh::new], whose program is taken from the `cache` instead of compiled for this mesh.
+    ///
+    /// Meshes created with the same shader sources share one compiled program.
+    pub fn cached(
+        display: &impl Facade,
+        cache: &mut ProgramCache,
+        vertices: &[Vertex],
+        index_buffer: impl Into<IndicesSource<'static>>,
+        vertex_shader: &'static str,
+        fragment_shader: &'static str,
+        winding: Winding,
+    ) -> Result<Self, RenderError> {
+        let vertex_buffer = Vertex::to_buffer(display, vertices)?;
+        let program = cache.get_or_compile(display, vertex_shader, fragment_shader)?;
+
+        Ok(Self {
+            vertex_buffer,
+            index_buffer: MeshIndices::Source(index_buffer.into()),
+            program,
+            winding,
+            aabb: OnceCell::new(),
+        })
+    }
+
+    /// Creates a mesh like [Mesh::new], whose shaders are read from files instead of being embedded.
+    ///
+    /// The returned [ShaderFiles] should be added to the mesh entity as well, so the [ShaderWatcher] recompiles the
+    /// program whenever the files change.
+    ///
+    /// [ShaderWatcher]: crate::shader::ShaderWatcher
+    pub fn from_shader_files(
+        display: &impl Facade,
+        vertices: &[Vertex],
+        index_buffer: impl Into<IndicesSource<'static>>,
+        vertex_path: impl AsRef<Path>,
+        fragment_path: impl AsRef<Path>,
+        winding: Winding,
+    ) -> Result<(Self, ShaderFiles), RenderError> {
+        let files = ShaderFiles::new(vertex_path, fragment_path);
+        let program = Rc::new(files.compile(display)?);
+
+        let mesh = Self {
+            vertex_buffer: Vertex::to_buffer(display, vertices)?,
+            index_buffer: MeshIndices::Source(index_buffer.into()),
+            program,
+            winding,
+            aabb: OnceCell::new(),
+        };
+
+        Ok((mesh, files))
+    }
+
+    /// Creates a mesh which owns an index buffer built from `indices`, so no `IndexBufferCreator` is needed.
+    ///
+    /// # Returns
+    ///
+    /// The mesh, or a [RenderError] if an index refers to a vertex which doesn't exist, or there was a problem
+    /// creating the buffers or the program.
+    pub fn indexed(
+        display: &impl Facade,
+        vertices: &[Vertex],
+        indices: &[u32],
+        primitive_type: PrimitiveType,
+        vertex_shader: &'static str,
+        fragment_shader: &'static str,
+        winding: Winding,
+    ) -> Result<Self, RenderError> {
+        check_indices(indices, vertices.len())?;
+
+        let vertex_buffer = Vertex::to_buffer(display, vertices)?;
+        let index_buffer = IndexBuffer::new(display, primitive_type, indices)?;
+        let program = Rc::new(Program::from_source(
+            display,
+            vertex_shader,
+            fragment_shader,
+            None,
+        )?);
+
+        Ok(Self {
+            vertex_buffer,
+            index_buffer: index_buffer.into(),
+            program,
+            winding,
+            aabb: OnceCell::new(),
+        })
+    }
+
+    /// Creates an indexed mesh from the source of a Wavefront `.obj` file, see [ObjModel::parse].
+    ///
+    /// The faces are expected to be wound counter-clockwise, as is the convention for `.obj` files.
+    pub fn from_obj(
+        display: &impl Facade,
+        obj: &[u8],
+        vertex_shader: &'static str,
+        fragment_shader: &'static str,
+    ) -> Result<Self, RenderError> {
+        let model = ObjModel::parse(&String::from_utf8_lossy(obj))?;
+
+        Self::indexed(
+            display,
+            &model.vertices,
+            &model.indices,
+            PrimitiveType::TrianglesList,
+            vertex_shader,
+            fragment_shader,
+            Winding::CounterClockwise,
+        )
+    }
+
+    /// Creates a quad covering the whole screen, which is useful for post-processing and backgrounds.
+    ///
+    /// The quad uses [FULLSCREEN_VERTEX_SHADER] as its vertex shader, so the `fragment_shader` receives the texture
+    /// coordinates as `v_tex_pos`.
+    pub fn fullscreen_quad(
+        display: &impl Facade,
+        fragment_shader: &'static str,
+    ) -> Result<Self, RenderError> {
+        Self::new(
+            display,
+            &fullscreen_quad_vertices(),
+            NoIndices(PrimitiveType::TriangleStrip).into(),
+            FULLSCREEN_VERTEX_SHADER,
+            fragment_shader,
+            Winding::CounterClockwise,
+        )
+    }
+}
+
+/// A mesh whose vertices carry a color instead of texture coordinates, for debug shapes and gradients.
+///
+/// Colored meshes are drawn by the `GlRenderSystem` after the textured meshes, with the entity's `MeshUniform`,
+/// `Transform` and `DrawParametersComponent` just like a [Mesh]. They're neither instanced nor sorted.
+#[derive(EntityComponent)]
+pub struct ColoredMesh {
+    pub vertex_buffer: VertexBuffer<ColoredVertex>,
+    pub index_buffer: MeshIndices,
+    pub program: Rc<Program>,
+    /// The winding of the front faces of the mesh, see [Mesh::winding].
+    pub winding: Winding,
+}
+
+impl ColoredMesh {
+    /// Creates a colored mesh drawn with [COLORED_VERTEX_SHADER] and [COLORED_FRAGMENT_SHADER].
+    pub fn new(
+        display: &impl Facade,
+        vertices: &[ColoredVertex],
+        index_buffer: impl Into<IndicesSource<'static>>,
+        winding: Winding,
+    ) -> Result<Self, RenderError> {
+        let vertex_buffer = ColoredVertex::to_buffer(display, vertices)?;
+        let program = Program::from_source(
+            display,
+            COLORED_VERTEX_SHADER,
+            COLORED_FRAGMENT_SHADER,
+            None,
+        )?;
+
+        Ok(Self {
+            vertex_buffer,
+            index_buffer: MeshIndices::Source(index_buffer.into()),
+            program: Rc::new(program),
+            winding,
+        })
+    }
+
+    /// Creates a colored mesh which owns an index buffer built from `indices`, see [Mesh::indexed].
+    pub fn indexed(
+        display: &impl Facade,
+        vertices: &[ColoredVertex],
+        indices: &[u32],
+        primitive_type: PrimitiveType,
+        winding: Winding,
+    ) -> Result<Self, RenderError> {
+        check_indices(indices, vertices.len())?;
+
+        let mut mesh = Self::new(display, vertices, NoIndices(primitive_type), winding)?;
+        mesh.index_buffer = IndexBuffer::new(display, primitive_type, indices)?.into();
+
+        Ok(mesh)
+    }
 }
@@ -0,0 +1,160 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+use crate::draw::vertex::Vertex;
+
+/// An error in the source of a Wavefront `.obj` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjError {
+    /// The line the error occurred on, starting at 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for ObjError {}
+
+/// The indexed triangles of a Wavefront `.obj` file.
+///
+/// Every distinct combination of position, texture coordinate and normal referenced by a face becomes a single
+/// [Vertex], and faces with more than three corners are triangulated as fans. Texture coordinates and normals which
+/// aren't referenced by a face default to zero.
+#[derive(Debug, Clone)]
+pub struct ObjModel {
+    pub vertices: Vec<Vertex>,
+    /// Three indices per triangle, to be drawn as `PrimitiveType::TrianglesList`.
+    pub indices: Vec<u32>,
+}
+
+impl ObjModel {
+    /// Parses the positions (`v`), texture coordinates (`vt`), normals (`vn`) and faces (`f`) of an `.obj` file.
+    ///
+    /// Face indices may be negative, in which case they're relative to the end of the elements read so far. All
+    /// other statements, such as groups and materials, are ignored.
+    pub fn parse(source: &str) -> Result<Self, ObjError> {
+        let mut positions = Vec::<[f32; 3]>::new();
+        let mut tex_coords = Vec::<[f32; 2]>::new();
+        let mut normals = Vec::<[f32; 3]>::new();
+
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let mut corners = HashMap::<(usize, Option<usize>, Option<usize>), u32>::new();
+
+        for (number, line) in source.lines().enumerate() {
+            let line_number = number + 1;
+            let error = |message: String| ObjError {
+                line: line_number,
+                message,
+            };
+
+            let mut parts = line.split_whitespace();
+
+            match parts.next() {
+                Some("v") => positions.push(parse_floats(parts).map_err(error)?),
+                Some("vt") => tex_coords.push(parse_floats(parts).map_err(error)?),
+                Some("vn") => normals.push(parse_floats(parts).map_err(error)?),
+                Some("f") => {
+                    let mut face = Vec::new();
+
+                    for corner in parts {
+                        let mut elements = corner.split('/');
+
+                        let position = elements
+                            .next()
+                            .filter(|element| !element.is_empty())
+                            .ok_or_else(|| {
+                                error(format!("face corner `{}` has no position", corner))
+                            })?;
+                        let position = resolve(position, positions.len()).map_err(error)?;
+
+                        let tex_coord = match elements.next() {
+                            Some(element) if !element.is_empty() => {
+                                Some(resolve(element, tex_coords.len()).map_err(error)?)
+                            }
+                            _ => None,
+                        };
+
+                        let normal = match elements.next() {
+                            Some(element) if !element.is_empty() => {
+                                Some(resolve(element, normals.len()).map_err(error)?)
+                            }
+                            _ => None,
+                        };
+
+                        let key = (position, tex_coord, normal);
+                        let index = *corners.entry(key).or_insert_with(|| {
+                            vertices.push(Vertex {
+                                position: positions[position],
+                                tex_pos: tex_coord.map_or([0.0, 0.0], |index| tex_coords[index]),
+                                normal: normal.map_or([0.0, 0.0, 0.0], |index| normals[index]),
+                            });
+
+                            (vertices.len() - 1) as u32
+                        });
+
+                        face.push(index);
+                    }
+
+                    if face.len() < 3 {
+                        return Err(error(format!(
+                            "face has {} corners, at least 3 are required",
+                            face.len()
+                        )));
+                    }
+
+                    for corner in 1..face.len() - 1 {
+                        indices.extend([face[0], face[corner], face[corner + 1]]);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self { vertices, indices })
+    }
+}
+
+/// Parses the first `N` numbers of a statement, any further numbers (such as the optional `w` component) are ignored.
+fn parse_floats<'a, const N: usize>(
+    mut parts: impl Iterator<Item = &'a str>,
+) -> Result<[f32; N], String> {
+    let mut values = [0.0; N];
+
+    for value in &mut values {
+        let part = parts
+            .next()
+            .ok_or_else(|| format!("expected {} numbers", N))?;
+
+        *value = part
+            .parse()
+            .map_err(|_| format!("`{}` is not a number", part))?;
+    }
+
+    Ok(values)
+}
+
+/// Turns a 1-based, possibly negative (relative) face index into an index into the `count` elements read so far.
+fn resolve(index: &str, count: usize) -> Result<usize, String> {
+    let parsed: i64 = index
+        .parse()
+        .map_err(|_| format!("`{}` is not an index", index))?;
+
+    let resolved = match parsed {
+        0 => None,
+        positive if positive > 0 => Some(positive as usize - 1),
+        negative => count.checked_sub(negative.unsigned_abs() as usize),
+    };
+
+    resolved
+        .filter(|resolved| *resolved < count)
+        .ok_or_else(|| {
+            format!(
+                "index {} is out of range, {} elements are defined",
+                parsed, count
+            )
+        })
+}
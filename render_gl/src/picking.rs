@@ -0,0 +1,162 @@
+use ecs::{
+    entity::{EntityManager, EntityQueryTable},
+    system::System,
+};
+
+use crate::{
+    bounds::Aabb,
+    camera::{active_perspective, camera_view},
+    container::{Matrix4, Vec3, Vec4},
+    draw::internal::model_matrix,
+};
+
+/// Unprojects a point in normalized device coordinates into a world space ray, starting on the near plane.
+///
+/// `ndc_x` and `ndc_y` range from `-1` at the left and bottom edge of the screen to `1` at the right and top edge.
+///
+/// # Returns
+///
+/// The origin and the normalized direction of the ray, or `None` if the view or projection matrix can't be inverted.
+pub fn unproject_ray(
+    view: &Matrix4,
+    projection: &Matrix4,
+    ndc_x: f32,
+    ndc_y: f32,
+) -> Option<(Vec3, Vec3)> {
+    let (inverse_view, inverse_projection) = (view.inverse()?, projection.inverse()?);
+
+    let unproject = |ndc_z: f32| {
+        let view_space = inverse_projection.transform_vec4(Vec4::new(ndc_x, ndc_y, ndc_z, 1.0));
+        let world = inverse_view.transform_vec4(view_space);
+
+        Vec3::new(world[0], world[1], world[2]) * (1.0 / world[3])
+    };
+
+    let (near, far) = (unproject(-1.0), unproject(1.0));
+    let direction = (far - near).normalize();
+
+    if !direction.length().is_finite() || direction.length() == 0.0 {
+        return None;
+    }
+
+    Some((near, direction))
+}
+
+/// Returns the distance along the ray at which it enters the box, using the slab method, or `None` if it misses.
+///
+/// A ray starting inside the box hits it at `0`. Boxes which lie entirely behind the origin are missed, as are boxes
+/// next to a ray running parallel to their slabs.
+pub fn ray_intersects_aabb(origin: Vec3, direction: Vec3, aabb: &Aabb) -> Option<f32> {
+    let (mut near, mut far) = (f32::NEG_INFINITY, f32::INFINITY);
+
+    for axis in 0..3 {
+        let (min, max) = (aabb.min[axis], aabb.max[axis]);
+
+        if direction[axis] == 0.0 {
+            // a parallel ray never crosses the slab, so it has to run between its planes.
+            if origin[axis] < min || origin[axis] > max {
+                return None;
+            }
+
+            continue;
+        }
+
+        let (t1, t2) = (
+            (min - origin[axis]) / direction[axis],
+            (max - origin[axis]) / direction[axis],
+        );
+
+        near = near.max(t1.min(t2));
+        far = far.min(t1.max(t2));
+    }
+
+    if near > far || far < 0.0 {
+        return None;
+    }
+
+    Some(near.max(0.0))
+}
+
+/// Returns the entity with an [Aabb] which is hit first by the world space ray, and the distance to the hit.
+///
+/// The boxes are in the local space of their entities, so the ray is transformed by the inverse model matrix of every
+/// entity (see the `WorldTransform`, `Transform` and `MeshUniform` components). Entities without a model matrix are
+/// assumed to be at the origin.
+pub fn pick(manager: &mut EntityManager, origin: Vec3, direction: Vec3) -> Option<(usize, f32)> {
+    let boxes = manager.borrow_manager::<Aabb>()?.snapshot();
+    let mut nearest: Option<(usize, f32)> = None;
+
+    for (entity, aabb) in boxes.iter() {
+        let model = model_matrix(manager, entity).unwrap_or_else(Matrix4::identity);
+        let Some(inverse) = model.inverse() else {
+            continue;
+        };
+
+        let local = |vector: Vec3, w: f32| {
+            let local = inverse.transform_vec4(Vec4::new(vector[0], vector[1], vector[2], w));
+            Vec3::new(local[0], local[1], local[2])
+        };
+
+        let (local_origin, local_direction) = (local(origin, 1.0), local(direction, 0.0));
+        let Some(t) = ray_intersects_aabb(local_origin, local_direction, aabb) else {
+            continue;
+        };
+
+        // the distance is measured in world space, as the model matrices may scale the local distances differently.
+        let hit = model.transform_vec4(Vec4::new(
+            local_origin[0] + local_direction[0] * t,
+            local_origin[1] + local_direction[1] * t,
+            local_origin[2] + local_direction[2] * t,
+            1.0,
+        ));
+        let distance = (Vec3::new(hit[0], hit[1], hit[2]) - origin).length();
+
+        if nearest.is_none_or(|(_, nearest)| distance < nearest) {
+            nearest = Some((entity, distance));
+        }
+    }
+
+    nearest
+}
+
+/// A request to pick the entity under a point on the screen, in normalized device coordinates, handled by
+/// [PickingSystem].
+#[derive(Debug, Clone, Copy)]
+pub struct PickRequest {
+    pub ndc_x: f32,
+    pub ndc_y: f32,
+}
+
+/// The entity picked by [PickingSystem] for the last [PickRequest], `None` if nothing was hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Picked(pub Option<usize>);
+
+/// Answers a [PickRequest] resource with a [Picked] resource, see [pick].
+///
+/// The ray is cast from the camera the scene is rendered from, through the active perspective. The request is
+/// removed once it has been handled.
+pub struct PickingSystem;
+
+impl<T> System<T> for PickingSystem {
+    fn update(
+        &mut self,
+        manager: &mut EntityManager,
+        table: &mut EntityQueryTable,
+        _: &T,
+    ) -> Option<()> {
+        let request = manager.remove_resource::<PickRequest>()?;
+        let (view, _) = camera_view(manager, table)?;
+        let projection = active_perspective(manager, table)?.matrix();
+
+        let picked = unproject_ray(&view, &projection, request.ndc_x, request.ndc_y)
+            .and_then(|(origin, direction)| pick(manager, origin, direction))
+            .map(|(entity, _)| entity);
+
+        manager.insert_resource(Picked(picked));
+        None
+    }
+
+    fn runs_while_paused(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,60 @@
+use std::{collections::HashMap, rc::Rc};
+
+use glium::{backend::Facade, Program};
+
+use crate::error::RenderError;
+
+/// Compiled shader programs keyed by their vertex and fragment shader sources.
+///
+/// Compiling GLSL is expensive, so meshes created through the cache (see `Mesh::cached`) share one program per pair
+/// of sources instead of compiling their own.
+#[derive(Default)]
+pub struct ProgramCache {
+    programs: HashMap<(&'static str, &'static str), Rc<Program>>,
+}
+
+impl ProgramCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the program compiled from the given sources, compiling it on the first request.
+    ///
+    /// A program which fails to compile isn't cached, so the error is returned on every request.
+    pub fn get_or_compile(
+        &mut self,
+        display: &impl Facade,
+        vertex_shader: &'static str,
+        fragment_shader: &'static str,
+    ) -> Result<Rc<Program>, RenderError> {
+        let key = (vertex_shader, fragment_shader);
+
+        if let Some(program) = self.programs.get(&key) {
+            return Ok(program.clone());
+        }
+
+        let program = Rc::new(Program::from_source(
+            display,
+            vertex_shader,
+            fragment_shader,
+            None,
+        )?);
+        self.programs.insert(key, program.clone());
+
+        Ok(program)
+    }
+
+    /// Returns the number of compiled programs.
+    pub fn len(&self) -> usize {
+        self.programs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.programs.is_empty()
+    }
+
+    /// Drops the cached programs, meshes keep the programs they already hold.
+    pub fn clear(&mut self) {
+        self.programs.clear();
+    }
+}
@@ -0,0 +1,33 @@
+use std::path::Path;
+
+use glium::{backend::Facade, texture::RawImage2d};
+use image::RgbaImage;
+
+use crate::error::RenderError;
+
+/// Reads the pixels of the last presented frame.
+///
+/// The size of the framebuffer is queried on every call, so the image always matches the current window size, even if
+/// it changed since the previous frame.
+pub fn read_front_buffer(display: &impl Facade) -> Result<RgbaImage, RenderError> {
+    let raw: RawImage2d<u8> = display.get_context().read_front_buffer()?;
+    Ok(flip_rows(raw))
+}
+
+/// Captures the last presented frame into an image file, whose format is picked from the extension of the `path`.
+pub fn save_screenshot(display: &impl Facade, path: impl AsRef<Path>) -> Result<(), RenderError> {
+    read_front_buffer(display)?
+        .save(path)
+        .map_err(RenderError::SaveImage)
+}
+
+/// Converts pixels read back from OpenGL, whose first row is at the bottom, into an image whose first row is at the
+/// top.
+pub(crate) fn flip_rows(raw: RawImage2d<u8>) -> RgbaImage {
+    let row_length = raw.width as usize * 4;
+    let rows = raw.data.chunks_exact(row_length.max(1)).rev();
+    let data = rows.flatten().copied().collect::<Vec<_>>();
+
+    RgbaImage::from_raw(raw.width, raw.height, data)
+        .expect("a framebuffer read back as RGBA has 4 bytes per pixel")
+}
@@ -0,0 +1,126 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::SystemTime,
+};
+
+use ecs::{
+    entity::{EntityManager, EntityQueryTable},
+    system::System,
+};
+use ecs_macro::EntityComponent;
+use glium::{backend::Facade, Program};
+
+use crate::{error::RenderError, mesh::Mesh};
+
+/// The files the shaders of a mesh were loaded from, which [ShaderWatcher] recompiles the mesh's program from
+/// whenever they change.
+#[derive(EntityComponent, Debug, Clone)]
+pub struct ShaderFiles {
+    vertex: PathBuf,
+    fragment: PathBuf,
+    /// The modification times of the vertex and fragment shader when they were last checked.
+    modified: [Option<SystemTime>; 2],
+}
+
+impl ShaderFiles {
+    /// Records the current modification times of both files, so only later changes count as a change.
+    pub fn new(vertex: impl AsRef<Path>, fragment: impl AsRef<Path>) -> Self {
+        let vertex = vertex.as_ref().to_path_buf();
+        let fragment = fragment.as_ref().to_path_buf();
+        let modified = [modified(&vertex), modified(&fragment)];
+
+        Self {
+            vertex,
+            fragment,
+            modified,
+        }
+    }
+
+    /// Reads the sources of the vertex and fragment shader.
+    pub fn read(&self) -> Result<(String, String), RenderError> {
+        let vertex = fs::read_to_string(&self.vertex).map_err(RenderError::ShaderFile)?;
+        let fragment = fs::read_to_string(&self.fragment).map_err(RenderError::ShaderFile)?;
+
+        Ok((vertex, fragment))
+    }
+
+    /// Compiles the program from the current sources of the files.
+    pub fn compile(&self, display: &impl Facade) -> Result<Program, RenderError> {
+        let (vertex, fragment) = self.read()?;
+        Ok(Program::from_source(display, &vertex, &fragment, None)?)
+    }
+
+    /// Returns whether either file was modified since the last check, and remembers the new modification times.
+    ///
+    /// A file which can't be read counts as unchanged until it's readable again.
+    pub fn changed(&mut self) -> bool {
+        let current = [modified(&self.vertex), modified(&self.fragment)];
+        let changed = current
+            .iter()
+            .zip(&self.modified)
+            .any(|(current, last)| current.is_some() && current != last);
+
+        self.modified = current;
+        changed
+    }
+
+    pub fn vertex_path(&self) -> &Path {
+        &self.vertex
+    }
+
+    pub fn fragment_path(&self) -> &Path {
+        &self.fragment
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Recompiles the program of every mesh with [ShaderFiles] whose files changed since the previous frame.
+///
+/// A shader which fails to compile is logged and the mesh keeps its last working program, so the next save with
+/// the error fixed picks up again.
+pub struct ShaderWatcher;
+
+impl<F: Facade> System<F> for ShaderWatcher {
+    fn update(
+        &mut self,
+        manager: &mut EntityManager,
+        table: &mut EntityQueryTable,
+        display: &F,
+    ) -> Option<()> {
+        let entities = table.query_single::<ShaderFiles>(manager)?.clone();
+
+        for entity in entities {
+            let (mesh, files) = manager.query_entity_two::<Mesh, ShaderFiles>(entity);
+            let (Some(mesh), Some(files)) = (mesh, files) else {
+                continue;
+            };
+
+            if !files.changed() {
+                continue;
+            }
+
+            match files.compile(display) {
+                Ok(program) => mesh.program = Rc::new(program),
+                Err(error) => eprintln!(
+                    "Unable to reload shaders {} and {}, keeping the previous program: {}",
+                    files.vertex.display(),
+                    files.fragment.display(),
+                    error
+                ),
+            }
+        }
+
+        None
+    }
+
+    fn runs_while_paused(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,347 @@
+use std::{ops::Range, rc::Rc};
+
+use ecs::{
+    entity::{EntityManager, EntityQueryTable},
+    system::System,
+};
+use ecs_macro::EntityComponent;
+use glium::{
+    backend::Facade,
+    implement_vertex,
+    index::{NoIndices, PrimitiveType},
+    uniform, Blend, DrawParameters, Program, Surface, Texture2d, VertexBuffer,
+};
+
+use crate::{
+    buffer::{TransientBufferPool, TransientHandle},
+    container::{Matrix4, Vec2},
+    draw::internal::report,
+    error::RenderError,
+    uniform::perspective::Orthographic,
+};
+
+pub const SPRITE_VERTEX_SHADER: &str = r#"
+#version 140
+
+in vec2 position;
+in vec2 tex_pos;
+
+out vec2 v_tex_pos;
+
+uniform mat4 projection;
+
+void main() {
+    v_tex_pos = tex_pos;
+    gl_Position = projection * vec4(position, 0.0, 1.0);
+}
+"#;
+
+pub const SPRITE_FRAGMENT_SHADER: &str = r#"
+#version 140
+
+in vec2 v_tex_pos;
+
+out vec4 color;
+
+uniform sampler2D tex;
+
+void main() {
+    color = texture(tex, v_tex_pos);
+}
+"#;
+
+/// A textured 2D quad drawn on top of the scene, e.g. for UI, HUDs and 2D games.
+///
+/// Sprites are positioned in pixels from the bottom left corner of the screen, and drawn by [SpriteRenderSystem].
+#[derive(EntityComponent, Clone)]
+pub struct Sprite {
+    pub texture: Rc<Texture2d>,
+    /// The center of the sprite, in pixels.
+    pub position: Vec2,
+    /// The width and height of the sprite, in pixels.
+    pub size: Vec2,
+    /// The counter-clockwise rotation around the center, in radians.
+    pub rotation: f32,
+    /// Sprites with a higher `z` are drawn on top of the ones with a lower `z`.
+    pub z: f32,
+}
+
+impl Sprite {
+    /// Creates a sprite the size of its texture.
+    pub fn new(texture: Rc<Texture2d>, position: impl Into<Vec2>) -> Self {
+        let (width, height) = texture.dimensions();
+
+        Self {
+            texture,
+            position: position.into(),
+            size: Vec2::new(width as f32, height as f32),
+            rotation: 0.0,
+            z: 0.0,
+        }
+    }
+
+    pub fn size(mut self, size: impl Into<Vec2>) -> Self {
+        self.size = size.into();
+        self
+    }
+
+    pub fn rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn z(mut self, z: f32) -> Self {
+        self.z = z;
+        self
+    }
+
+    /// Returns the two triangles covering the sprite, see [sprite_vertices].
+    pub fn vertices(&self) -> [SpriteVertex; 6] {
+        sprite_vertices(self.position, self.size, self.rotation)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpriteVertex {
+    pub position: [f32; 2],
+    pub tex_pos: [f32; 2],
+}
+
+implement_vertex!(SpriteVertex, position, tex_pos);
+
+/// Returns the two triangles, wound counter-clockwise, of a quad with the given center and size which is rotated
+/// counter-clockwise by `rotation` radians around its center.
+pub fn sprite_vertices(position: Vec2, size: Vec2, rotation: f32) -> [SpriteVertex; 6] {
+    let (sin, cos) = rotation.sin_cos();
+
+    let corner = |x: f32, y: f32| {
+        let (offset_x, offset_y) = ((x - 0.5) * size[0], (y - 0.5) * size[1]);
+
+        SpriteVertex {
+            position: [
+                position[0] + offset_x * cos - offset_y * sin,
+                position[1] + offset_x * sin + offset_y * cos,
+            ],
+            tex_pos: [x, y],
+        }
+    };
+
+    let (bottom_left, bottom_right) = (corner(0.0, 0.0), corner(1.0, 0.0));
+    let (top_right, top_left) = (corner(1.0, 1.0), corner(0.0, 1.0));
+
+    [
+        bottom_left,
+        bottom_right,
+        top_right,
+        bottom_left,
+        top_right,
+        top_left,
+    ]
+}
+
+/// Concatenates the vertices of all quads into a single batch, and groups consecutive quads which share a texture
+/// (identified by its `key`) into runs, which can each be drawn with a single draw call.
+///
+/// # Returns
+///
+/// The vertices of the batch, and the key and vertex range of every run in drawing order.
+pub fn batch_vertices<K: PartialEq>(
+    quads: impl IntoIterator<Item = (K, [SpriteVertex; 6])>,
+) -> (Vec<SpriteVertex>, Vec<(K, Range<usize>)>) {
+    let mut vertices = Vec::new();
+    let mut runs: Vec<(K, Range<usize>)> = Vec::new();
+
+    for (key, quad) in quads {
+        let start = vertices.len();
+        vertices.extend(quad);
+
+        match runs.last_mut() {
+            Some((last, range)) if *last == key => range.end = vertices.len(),
+            _ => runs.push((key, start..vertices.len())),
+        }
+    }
+
+    (vertices, runs)
+}
+
+/// The sprites of the current frame, uploaded by [SpriteRenderSystem] and drawn on top of the scene by the
+/// `GlRenderSystem`.
+#[derive(EntityComponent, Default)]
+pub struct SpriteBatch {
+    program: Option<Rc<Program>>,
+    pool: TransientBufferPool<VertexBuffer<SpriteVertex>>,
+    buffer: Option<TransientHandle>,
+    runs: Vec<(Rc<Texture2d>, Range<usize>)>,
+    projection: Option<Matrix4>,
+    alpha_blending: bool,
+}
+
+impl SpriteBatch {
+    /// Returns the pool the vertex buffers of the batch are taken from.
+    pub fn pool(&self) -> &TransientBufferPool<VertexBuffer<SpriteVertex>> {
+        &self.pool
+    }
+}
+
+/// Batches all [Sprite]s into one vertex buffer every frame, ordered by their `z`.
+///
+/// The batch is drawn by the `GlRenderSystem` after the scene, with an orthographic projection covering the screen in
+/// pixels, so this system has to be registered before it. Its buffer is taken from a [TransientBufferPool], and handed
+/// out again in the next frame.
+pub struct SpriteRenderSystem {
+    /// Whether the sprites are alpha blended over the scene, enabled by default.
+    pub alpha_blending: bool,
+}
+
+impl SpriteRenderSystem {
+    pub fn new() -> Self {
+        Self {
+            alpha_blending: true,
+        }
+    }
+
+    pub fn alpha_blending(mut self, alpha_blending: bool) -> Self {
+        self.alpha_blending = alpha_blending;
+        self
+    }
+}
+
+impl Default for SpriteRenderSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Facade> System<F> for SpriteRenderSystem {
+    fn update(
+        &mut self,
+        manager: &mut EntityManager,
+        table: &mut EntityQueryTable,
+        display: &F,
+    ) -> Option<()> {
+        let mut sprites = match manager.borrow_manager::<Sprite>() {
+            Some(sprites) => sprites.components.iter().collect::<Vec<_>>(),
+            None => vec![],
+        };
+
+        // the sort is stable, so sprites with the same `z` keep the order they were added in.
+        sprites.sort_by(|a, b| a.z.total_cmp(&b.z));
+
+        let (vertices, runs) = batch_vertices(
+            sprites
+                .iter()
+                .map(|sprite| (ByTexture(sprite.texture.clone()), sprite.vertices())),
+        );
+        let runs = runs
+            .into_iter()
+            .map(|(texture, range)| (texture.0, range))
+            .collect::<Vec<_>>();
+
+        let entity = match table.query_first_single::<SpriteBatch>(manager) {
+            Some(entity) => *entity,
+            None => {
+                let entity = manager.entity();
+                manager.entity_with(entity, SpriteBatch::default());
+                entity
+            }
+        };
+
+        let batch = manager.query_entity::<SpriteBatch>(entity).0?;
+        let (width, height) = display.get_context().get_framebuffer_dimensions();
+
+        batch.alpha_blending = self.alpha_blending;
+        batch.projection =
+            Some(Orthographic::new(0.0, width as f32, 0.0, height as f32, -1.0, 1.0).matrix());
+        batch.runs = runs;
+
+        // the buffer of the last frame has been drawn, so it can be handed out again.
+        batch.pool.end_frame();
+        batch.buffer = None;
+
+        if vertices.is_empty() {
+            return None;
+        }
+
+        if batch.program.is_none() {
+            match Program::from_source(display, SPRITE_VERTEX_SHADER, SPRITE_FRAGMENT_SHADER, None)
+            {
+                Ok(program) => batch.program = Some(Rc::new(program)),
+                Err(error) => {
+                    eprintln!("{}", RenderError::from(error));
+                    batch.runs.clear();
+                    return None;
+                }
+            }
+        }
+
+        match batch.pool.request(display, &vertices) {
+            Ok(handle) => batch.buffer = Some(handle),
+            Err(error) => {
+                eprintln!("{}", RenderError::from(error));
+                batch.runs.clear();
+            }
+        }
+
+        None
+    }
+
+    fn runs_while_paused(&self) -> bool {
+        true
+    }
+}
+
+/// Compares textures by identity, so sprites sharing a texture end up in the same run.
+struct ByTexture(Rc<Texture2d>);
+
+impl PartialEq for ByTexture {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Draws the [SpriteBatch] uploaded for this frame, if there is one.
+pub(crate) fn draw_sprites<S: Surface>(
+    manager: &mut EntityManager,
+    table: &mut EntityQueryTable,
+    target: &mut S,
+) {
+    let Some(entity) = table.query_first_single::<SpriteBatch>(manager).copied() else {
+        return;
+    };
+    let Some(batch) = manager.query_entity::<SpriteBatch>(entity).0 else {
+        return;
+    };
+    let buffer = batch.buffer.and_then(|handle| batch.pool.buffer(handle));
+    let (Some(program), Some(buffer), Some(projection)) =
+        (&batch.program, buffer, batch.projection)
+    else {
+        return;
+    };
+
+    let draw_parameters = DrawParameters {
+        blend: match batch.alpha_blending {
+            true => Blend::alpha_blending(),
+            false => Blend::default(),
+        },
+        ..Default::default()
+    };
+
+    for (texture, range) in &batch.runs {
+        let Some(vertices) = buffer.slice(range.clone()) else {
+            continue;
+        };
+
+        let uniforms = uniform! {
+            projection: projection.inner(),
+            tex: &**texture,
+        };
+
+        report(target.draw(
+            vertices,
+            NoIndices(PrimitiveType::TrianglesList),
+            program,
+            &uniforms,
+            &draw_parameters,
+        ));
+    }
+}
@@ -0,0 +1,308 @@
+use std::{collections::HashMap, error::Error, fmt, rc::Rc};
+
+use glium::{
+    backend::Facade,
+    index::{NoIndices, PrimitiveType},
+    uniform, Blend, DrawParameters, Program, Surface, Texture2d, VertexBuffer,
+};
+use image::ImageFormat;
+
+use crate::{
+    container::Vec2,
+    error::RenderError,
+    sprite::{SpriteVertex, SPRITE_FRAGMENT_SHADER, SPRITE_VERTEX_SHADER},
+    uniform::{load_texture, perspective::Orthographic},
+};
+
+/// The character drawn for glyphs which are missing from a font, if the font has it.
+pub const FALLBACK_GLYPH: char = '\u{25A1}';
+
+/// An error in the source of a `.fnt` metrics table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontError {
+    /// The line the error occurred on, starting at 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for FontError {}
+
+/// The region of a glyph in the atlas and its placement relative to the cursor, all in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glyph {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// The offset from the cursor to the left edge of the glyph.
+    pub x_offset: f32,
+    /// The offset from the top of the line down to the top edge of the glyph.
+    pub y_offset: f32,
+    /// How far the cursor moves after the glyph.
+    pub x_advance: f32,
+}
+
+/// The metrics of a bitmap font, as stored in the text variant of the AngelCode BMFont `.fnt` format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontMetrics {
+    pub glyphs: HashMap<char, Glyph>,
+    /// The distance between two lines.
+    pub line_height: f32,
+    /// The distance from the top of a line to the baseline.
+    pub base: f32,
+    /// The size of the atlas texture the glyph regions refer to.
+    pub atlas_size: (f32, f32),
+}
+
+impl FontMetrics {
+    /// Parses the `common` and `char` statements of a `.fnt` file, all other statements (such as `info`, `page` and
+    /// `kerning`) are ignored.
+    pub fn parse(source: &str) -> Result<Self, FontError> {
+        let mut metrics = Self {
+            glyphs: HashMap::new(),
+            line_height: 0.0,
+            base: 0.0,
+            atlas_size: (0.0, 0.0),
+        };
+        let mut common = false;
+
+        for (number, line) in source.lines().enumerate() {
+            let error = |message: String| FontError {
+                line: number + 1,
+                message,
+            };
+
+            let mut parts = line.split_whitespace();
+            let statement = parts.next();
+
+            if !matches!(statement, Some("common" | "char")) {
+                continue;
+            }
+
+            let attributes = parts
+                .filter_map(|part| part.split_once('='))
+                .collect::<HashMap<_, _>>();
+            let attribute = |name: &str| -> Result<f32, FontError> {
+                let value = attributes
+                    .get(name)
+                    .ok_or_else(|| error(format!("missing attribute `{}`", name)))?;
+
+                value
+                    .parse()
+                    .map_err(|_| error(format!("`{}` is not a number", value)))
+            };
+
+            if statement == Some("common") {
+                metrics.line_height = attribute("lineHeight")?;
+                metrics.base = attribute("base")?;
+                metrics.atlas_size = (attribute("scaleW")?, attribute("scaleH")?);
+                common = true;
+                continue;
+            }
+
+            let id = attribute("id")?;
+            let character = char::from_u32(id as u32)
+                .ok_or_else(|| error(format!("`{}` is not a character", id)))?;
+
+            metrics.glyphs.insert(
+                character,
+                Glyph {
+                    x: attribute("x")?,
+                    y: attribute("y")?,
+                    width: attribute("width")?,
+                    height: attribute("height")?,
+                    x_offset: attribute("xoffset")?,
+                    y_offset: attribute("yoffset")?,
+                    x_advance: attribute("xadvance")?,
+                },
+            );
+        }
+
+        if !common {
+            return Err(FontError {
+                line: source.lines().count(),
+                message: String::from("missing `common` statement"),
+            });
+        }
+
+        Ok(metrics)
+    }
+
+    /// Returns the glyph of a character, or [FALLBACK_GLYPH] if the font doesn't have it.
+    ///
+    /// Returns `None` if neither is part of the font, in which case [FontMetrics::box_glyph] is drawn instead.
+    pub fn glyph(&self, character: char) -> Option<&Glyph> {
+        self.glyphs
+            .get(&character)
+            .or_else(|| self.glyphs.get(&FALLBACK_GLYPH))
+    }
+
+    /// Returns the box drawn for missing glyphs, which reaches from the top of the line to the baseline.
+    ///
+    /// The box has no region in the atlas, so it's filled with the color of the atlas' top left pixel.
+    pub fn box_glyph(&self) -> Glyph {
+        let width = (self.base / 2.0).max(1.0);
+
+        Glyph {
+            x: 0.0,
+            y: 0.0,
+            width,
+            height: self.base,
+            x_offset: 0.0,
+            y_offset: 0.0,
+            x_advance: width * 1.25,
+        }
+    }
+}
+
+/// Lays out `text` as one textured quad per glyph, starting with the top left corner of the first line at
+/// `position`, in pixels from the bottom left corner of the screen.
+///
+/// Every newline moves the cursor back to the start of the line and down by the line height. All metrics are
+/// multiplied by `scale`.
+pub fn layout_text(
+    metrics: &FontMetrics,
+    text: &str,
+    position: Vec2,
+    scale: f32,
+) -> Vec<[SpriteVertex; 6]> {
+    let (atlas_width, atlas_height) =
+        (metrics.atlas_size.0.max(1.0), metrics.atlas_size.1.max(1.0));
+    let mut cursor = (position[0], position[1]);
+    let mut quads = Vec::new();
+
+    for character in text.chars() {
+        if character == '\n' {
+            cursor = (position[0], cursor.1 - metrics.line_height * scale);
+            continue;
+        }
+
+        let (glyph, region) = match metrics.glyph(character) {
+            Some(glyph) => (*glyph, (glyph.width, glyph.height)),
+            None => (metrics.box_glyph(), (0.0, 0.0)),
+        };
+
+        let left = cursor.0 + glyph.x_offset * scale;
+        let top = cursor.1 - glyph.y_offset * scale;
+        let (right, bottom) = (left + glyph.width * scale, top - glyph.height * scale);
+
+        // the atlas is uploaded flipped, so its first row is at the top of the texture.
+        let (u_left, u_right) = (glyph.x / atlas_width, (glyph.x + region.0) / atlas_width);
+        let (v_top, v_bottom) = (
+            1.0 - glyph.y / atlas_height,
+            1.0 - (glyph.y + region.1) / atlas_height,
+        );
+
+        let vertex = |x: f32, y: f32, u: f32, v: f32| SpriteVertex {
+            position: [x, y],
+            tex_pos: [u, v],
+        };
+
+        let bottom_left = vertex(left, bottom, u_left, v_bottom);
+        let bottom_right = vertex(right, bottom, u_right, v_bottom);
+        let top_right = vertex(right, top, u_right, v_top);
+        let top_left = vertex(left, top, u_left, v_top);
+
+        quads.push([
+            bottom_left,
+            bottom_right,
+            top_right,
+            bottom_left,
+            top_right,
+            top_left,
+        ]);
+
+        cursor.0 += glyph.x_advance * scale;
+    }
+
+    quads
+}
+
+/// A font whose glyphs are read from an atlas texture, as described by its [FontMetrics].
+pub struct BitmapFont {
+    pub texture: Rc<Texture2d>,
+    pub metrics: FontMetrics,
+    program: Rc<Program>,
+}
+
+impl BitmapFont {
+    /// Creates a font from an uploaded atlas, compiling the program the text is drawn with.
+    pub fn new(
+        display: &impl Facade,
+        texture: Rc<Texture2d>,
+        metrics: FontMetrics,
+    ) -> Result<Self, RenderError> {
+        let program =
+            Program::from_source(display, SPRITE_VERTEX_SHADER, SPRITE_FRAGMENT_SHADER, None)?;
+
+        Ok(Self {
+            texture,
+            metrics,
+            program: Rc::new(program),
+        })
+    }
+
+    /// Decodes the glyph atlas image and parses the `.fnt` metrics table, see [FontMetrics::parse].
+    pub fn load(
+        display: &impl Facade,
+        format: ImageFormat,
+        atlas: &[u8],
+        metrics: &str,
+    ) -> Result<Self, RenderError> {
+        let metrics = FontMetrics::parse(metrics)?;
+        let texture = load_texture(format, display, atlas)?;
+
+        Self::new(display, Rc::new(texture), metrics)
+    }
+}
+
+/// Draws `text` into `frame` with an orthographic projection covering the frame in pixels, see [layout_text] for
+/// how the text is placed.
+///
+/// The text is alpha blended over the frame, so this is meant to be called after the scene has been drawn.
+pub fn draw_text<S: Surface>(
+    display: &impl Facade,
+    frame: &mut S,
+    font: &BitmapFont,
+    text: &str,
+    position: impl Into<Vec2>,
+    scale: f32,
+) -> Result<(), RenderError> {
+    let vertices = layout_text(&font.metrics, text, position.into(), scale)
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+    if vertices.is_empty() {
+        return Ok(());
+    }
+
+    let buffer = VertexBuffer::new(display, &vertices)?;
+    let (width, height) = frame.get_dimensions();
+    let projection = Orthographic::new(0.0, width as f32, 0.0, height as f32, -1.0, 1.0);
+
+    let uniforms = uniform! {
+        projection: projection.inner(),
+        tex: &*font.texture,
+    };
+    let draw_parameters = DrawParameters {
+        blend: Blend::alpha_blending(),
+        ..Default::default()
+    };
+
+    frame.draw(
+        &buffer,
+        NoIndices(PrimitiveType::TrianglesList),
+        &font.program,
+        &uniforms,
+        &draw_parameters,
+    )?;
+
+    Ok(())
+}
@@ -2,48 +2,101 @@ use std::io::Cursor;
 
 use ecs_macro::EntityComponent;
 use glium::{
-    texture::RawImage2d,
-    uniforms::{UniformValue, Uniforms},
-    Display, Texture2d,
+    backend::Facade,
+    framebuffer::SimpleFrameBuffer,
+    texture::{CubeLayer, Cubemap, MipmapsOption, RawImage2d},
+    uniforms::{
+        MagnifySamplerFilter, SamplerBehavior, SamplerWrapFunction, UniformValue, Uniforms,
+    },
+    BlitTarget, Display, Surface, Texture2d,
 };
 use image::ImageFormat;
 
 use crate::{
-    container::{Matrix4, Vec3},
+    container::Matrix4,
+    dds::DdsImage,
     draw::transform::Transform,
+    error::RenderError,
+    light::Light,
     mesh::TextureType,
 };
 
-use self::perspective::Perspective;
+use self::{
+    perspective::{Orthographic, Perspective, Projection},
+    sampler::SamplerConfig,
+};
 
 pub mod perspective;
+pub mod sampler;
+
+/// The maximum number of lights passed to the shaders, lights beyond this are ignored.
+pub const MAX_LIGHTS: usize = 8;
 
 #[derive(EntityComponent, Debug)]
 pub struct MeshUniform {
-    matrix: Matrix4,
+    matrix: Option<Matrix4>,
     view_matrix: Option<Matrix4>,
-    light: Option<Vec3>,
-    perspective: Option<Perspective>,
+    lights: Vec<Light>,
+    time: Option<f32>,
+    perspective: Option<Projection>,
     texture: Option<TextureType>,
     diffuse_texture: Option<TextureType>,
     normal_texture: Option<TextureType>,
+    cubemap_texture: Option<TextureType>,
+    texture_sampler: SamplerConfig,
+    diffuse_sampler: SamplerConfig,
+    normal_sampler: SamplerConfig,
+    cubemap_sampler: SamplerConfig,
 }
 
 impl MeshUniform {
+    /// Creates a new `MeshUniform` with a model matrix, which is only used if the entity has no `Transform`.
     pub fn new(matrix: Matrix4) -> Self {
         Self {
-            matrix,
+            matrix: Some(matrix),
+            ..Self::empty()
+        }
+    }
+
+    /// Creates a new `MeshUniform` without a model matrix.
+    ///
+    /// The model matrix is taken from the entity's `Transform` component while rendering, or the identity matrix if
+    /// the entity has no `Transform`.
+    pub fn empty() -> Self {
+        Self {
+            matrix: None,
             view_matrix: None,
-            light: None,
+            lights: Vec::new(),
+            time: None,
             texture: None,
             perspective: None,
             diffuse_texture: None,
             normal_texture: None,
+            cubemap_texture: None,
+            texture_sampler: SamplerConfig::default(),
+            diffuse_sampler: SamplerConfig::default(),
+            normal_sampler: SamplerConfig::default(),
+            cubemap_sampler: SamplerConfig::new().wrap(SamplerWrapFunction::Clamp),
         }
     }
 
-    pub fn light(mut self, light: impl Into<Vec3>) -> Self {
-        self.light = Some(light.into());
+    /// Adds a light, the first light is also passed on its own as `u_light` for shaders which only support one.
+    ///
+    /// A plain vector is added as the direction of a white directional light.
+    pub fn light(mut self, light: impl Into<Light>) -> Self {
+        if self.lights.len() < MAX_LIGHTS {
+            self.lights.push(light.into());
+        }
+        self
+    }
+
+    /// Sets the lights, passed to the shaders together with their number as `u_light_count`.
+    ///
+    /// Every light is passed as an element of the arrays `u_lights` (see [Light::vector]), `u_light_types`,
+    /// `u_light_directions`, `u_light_colors`, `u_light_ranges` and `u_light_angles`. At most [MAX_LIGHTS] lights are
+    /// kept, the rest is dropped.
+    pub fn lights(mut self, lights: impl IntoIterator<Item = impl Into<Light>>) -> Self {
+        self.set_lights(lights.into_iter().map(Into::into).collect());
         self
     }
 
@@ -62,26 +115,113 @@ impl MeshUniform {
         self
     }
 
+    /// Sets the cubemap (`cubemap_tex`), which is sampled by direction, e.g. for a skybox.
+    pub fn cubemap_texture(mut self, cubemap: Cubemap) -> Self {
+        self.cubemap_texture = Some(TextureType::Cubemap(cubemap));
+        self
+    }
+
+    /// Sets how the texture (`tex`) is sampled.
+    pub fn sampler(mut self, sampler: SamplerConfig) -> Self {
+        self.texture_sampler = sampler;
+        self
+    }
+
+    /// Sets how the diffuse texture (`diffuse_tex`) is sampled.
+    pub fn diffuse_sampler(mut self, sampler: SamplerConfig) -> Self {
+        self.diffuse_sampler = sampler;
+        self
+    }
+
+    /// Sets how the normal texture (`norm_tex`) is sampled.
+    pub fn normal_sampler(mut self, sampler: SamplerConfig) -> Self {
+        self.normal_sampler = sampler;
+        self
+    }
+
+    /// Sets how the cubemap (`cubemap_tex`) is sampled, which is clamped to the edges of the faces by default to hide
+    /// their seams.
+    pub fn cubemap_sampler(mut self, sampler: SamplerConfig) -> Self {
+        self.cubemap_sampler = sampler;
+        self
+    }
+
+    /// Returns the textures by the name of their uniform, together with the behavior they're sampled with.
+    pub(crate) fn textures(&self) -> [(&'static str, Option<&TextureType>, SamplerBehavior); 4] {
+        [
+            (
+                "tex",
+                self.texture.as_ref(),
+                self.texture_sampler.behavior(),
+            ),
+            (
+                "diffuse_tex",
+                self.diffuse_texture.as_ref(),
+                self.diffuse_sampler.behavior(),
+            ),
+            (
+                "norm_tex",
+                self.normal_texture.as_ref(),
+                self.normal_sampler.behavior(),
+            ),
+            (
+                "cubemap_tex",
+                self.cubemap_texture.as_ref(),
+                self.cubemap_sampler.behavior(),
+            ),
+        ]
+    }
+
+    /// Replaces the lights, see [MeshUniform::lights].
+    pub fn set_lights(&mut self, mut lights: Vec<Light>) {
+        lights.truncate(MAX_LIGHTS);
+        self.lights = lights;
+    }
+
+    /// Sets the time in seconds passed to the shaders as `u_time`, usually `TimeDelta::total_elapsed_secs`.
+    pub fn time(mut self, secs: f32) -> Self {
+        self.set_time(secs);
+        self
+    }
+
+    /// Replaces the time, see [MeshUniform::time].
+    pub fn set_time(&mut self, secs: f32) {
+        self.time = Some(secs);
+    }
+
     pub fn perspective(mut self, perspective: Perspective) -> Self {
-        self.perspective = Some(perspective.clone());
+        self.perspective = Some(perspective.into());
         self
     }
 
+    /// Sets an orthographic projection, which takes the place of the perspective in the `perspective` uniform.
+    pub fn orthographic(mut self, orthographic: Orthographic) -> Self {
+        self.perspective = Some(orthographic.into());
+        self
+    }
+
+    /// Adapts the perspective to a resized surface, see [Perspective::update_dimensions].
+    pub fn update_dimensions(&mut self, width: f32, height: f32) {
+        if let Some(projection) = self.perspective.as_mut() {
+            projection.update_dimensions(width, height);
+        }
+    }
+
     pub fn view_matrix(&mut self, matrix: Matrix4) -> &mut Self {
         self.view_matrix = Some(matrix);
         self
     }
 
     pub fn matrix(&mut self, matrix: Matrix4) {
-        self.matrix = matrix;
+        self.matrix = Some(matrix);
     }
 
     pub fn transform(&mut self, transform: &Transform) {
-        self.matrix = transform.matrix.clone();
+        self.matrix = Some(transform.matrix);
     }
 
-    pub fn ref_matrix(&mut self) -> &mut Matrix4 {
-        &mut self.matrix
+    pub fn ref_matrix(&mut self) -> Option<&mut Matrix4> {
+        self.matrix.as_mut()
     }
 
     /// Creates a new `Mesh` instance with an image texture.
@@ -94,32 +234,31 @@ impl MeshUniform {
     ///
     /// # Returns
     ///
-    /// A new `Mesh` instance with an image texture.
+    /// The `MeshUniform` with the texture set, or a [RenderError] if the image couldn't be decoded or uploaded.
     ///
     /// # Examples
     ///
-    /// ```
-    /// use skyward::render::draw::mesh::{Mesh, Vertex};
+    /// ```ignore
+    /// use skyward::render::draw::mesh::{Mesh, Vertex, Winding};
     /// use glium::{Display, ImageFormat};
     /// use std::fs::File;
     /// use std::io::Read;
     ///
-    /// let mesh = Mesh::new(display, &[], &[], "", "")
+    /// let mesh = Mesh::new(display, &[], &[], "", "", Winding::CounterClockwise)
     ///     .unwrap()
-    ///     .with_img_2d_texture(ImageFormat::Png, display, include_bytes!("picture.png"));
+    ///     .with_img_2d_texture(ImageFormat::Png, display, include_bytes!("picture.png"))
+    ///     .unwrap();
     /// ```
     pub fn with_img_2d_texture(
         mut self,
         format: ImageFormat,
         display: &Display,
         bytes: &[u8],
-    ) -> Self {
-        let image = image::load(Cursor::new(bytes), format).unwrap().to_rgba8();
-        let dimensions = image.dimensions();
-        let image = RawImage2d::from_raw_rgba_reversed(&image.into_raw(), dimensions);
-        let texture = Texture2d::new(display, image).unwrap();
-        self.texture = Some(TextureType::Texture2d(texture));
-        self
+    ) -> Result<Self, RenderError> {
+        self.texture = Some(TextureType::Texture2d(load_texture(
+            format, display, bytes,
+        )?));
+        Ok(self)
     }
 
     /// Creates a new `Mesh` instance with an image texture.
@@ -132,32 +271,31 @@ impl MeshUniform {
     ///
     /// # Returns
     ///
-    /// A new `Mesh` instance with an image texture.
+    /// The `MeshUniform` with the texture set, or a [RenderError] if the image couldn't be decoded or uploaded.
     ///
     /// # Examples
     ///
-    /// ```
-    /// use skyward::render::draw::mesh::{Mesh, Vertex};
+    /// ```ignore
+    /// use skyward::render::draw::mesh::{Mesh, Vertex, Winding};
     /// use glium::{Display, ImageFormat};
     /// use std::fs::File;
     /// use std::io::Read;
     ///
-    /// let mesh = Mesh::new(display, &[], &[], "", "")
+    /// let mesh = Mesh::new(display, &[], &[], "", "", Winding::CounterClockwise)
     ///     .unwrap()
-    ///     .with_img_2d_texture(ImageFormat::Png, display, include_bytes!("picture.png"));
+    ///     .with_img_2d_texture(ImageFormat::Png, display, include_bytes!("picture.png"))
+    ///     .unwrap();
     /// ```
     pub fn with_img_2d_diff_texture(
         mut self,
         format: ImageFormat,
         display: &Display,
         bytes: &[u8],
-    ) -> Self {
-        let image = image::load(Cursor::new(bytes), format).unwrap().to_rgba8();
-        let dimensions = image.dimensions();
-        let image = RawImage2d::from_raw_rgba_reversed(&image.into_raw(), dimensions);
-        let texture = Texture2d::new(display, image).unwrap();
-        self.diffuse_texture = Some(TextureType::Texture2d(texture));
-        self
+    ) -> Result<Self, RenderError> {
+        self.diffuse_texture = Some(TextureType::Texture2d(load_texture(
+            format, display, bytes,
+        )?));
+        Ok(self)
     }
 
     /// Creates a new `Mesh` instance with an image texture.
@@ -170,41 +308,227 @@ impl MeshUniform {
     ///
     /// # Returns
     ///
-    /// A new `Mesh` instance with an image texture.
+    /// The `MeshUniform` with the texture set, or a [RenderError] if the image couldn't be decoded or uploaded.
     ///
     /// # Examples
     ///
-    /// ```
-    /// use skyward::render::draw::mesh::{Mesh, Vertex};
+    /// ```ignore
+    /// use skyward::render::draw::mesh::{Mesh, Vertex, Winding};
     /// use glium::{Display, ImageFormat};
     /// use std::fs::File;
     /// use std::io::Read;
     ///
-    /// let mesh = Mesh::new(display, &[], &[], "", "")
+    /// let mesh = Mesh::new(display, &[], &[], "", "", Winding::CounterClockwise)
     ///     .unwrap()
-    ///     .with_img_2d_texture(ImageFormat::Png, display, include_bytes!("picture.png"));
+    ///     .with_img_2d_texture(ImageFormat::Png, display, include_bytes!("picture.png"))
+    ///     .unwrap();
     /// ```
     pub fn with_img_2d_norm_texture(
         mut self,
         format: ImageFormat,
         display: &Display,
         bytes: &[u8],
-    ) -> Self {
-        let image = image::load(Cursor::new(bytes), format).unwrap().to_rgba8();
-        let dimensions = image.dimensions();
-        let image = RawImage2d::from_raw_rgba_reversed(&image.into_raw(), dimensions);
-        let texture = Texture2d::new(display, image).unwrap();
-        self.normal_texture = Some(TextureType::Texture2d(texture));
-        self
+    ) -> Result<Self, RenderError> {
+        self.normal_texture = Some(TextureType::Texture2d(load_texture(
+            format, display, bytes,
+        )?));
+        Ok(self)
+    }
+
+    /// Sets an image texture with a full chain of generated mipmaps, sampled with trilinear filtering.
+    ///
+    /// Images of any size are supported, for non-power-of-two sizes every mip level is rounded down, see
+    /// [mip_levels].
+    pub fn with_img_2d_texture_mipmapped(
+        mut self,
+        format: ImageFormat,
+        display: &impl Facade,
+        bytes: &[u8],
+    ) -> Result<Self, RenderError> {
+        let texture =
+            load_texture_with_mipmaps(format, display, bytes, MipmapsOption::AutoGeneratedMipmaps)?;
+        self.texture = Some(TextureType::Texture2d(texture));
+        self.texture_sampler = SamplerConfig::trilinear();
+        Ok(self)
+    }
+
+    /// Sets the cubemap from six images of the same format, in the order +X, -X, +Y, -Y, +Z, -Z.
+    ///
+    /// Every face has to be a square of the same size, otherwise [RenderError::CubemapFaceSize] is returned.
+    pub fn with_cubemap_from_faces(
+        self,
+        display: &impl Facade,
+        faces: [&[u8]; 6],
+        format: ImageFormat,
+    ) -> Result<Self, RenderError> {
+        let faces = decode_cubemap_faces(format, faces)?;
+        let size = faces[0].width;
+        let cubemap = Cubemap::empty(display, size)?;
+
+        let target = BlitTarget {
+            left: 0,
+            bottom: 0,
+            width: size as i32,
+            height: size as i32,
+        };
+
+        for (face, layer) in faces.into_iter().zip(CUBE_LAYERS) {
+            let face = Texture2d::new(display, face)?;
+            let framebuffer = SimpleFrameBuffer::new(display, cubemap.main_level().image(layer))?;
+
+            face.as_surface().blit_whole_color_to(
+                &framebuffer,
+                &target,
+                MagnifySamplerFilter::Linear,
+            );
+        }
+
+        Ok(self.cubemap_texture(cubemap))
+    }
+
+    /// Sets the texture from the bytes of a DDS file, which is uploaded in its block compressed form.
+    ///
+    /// Supported formats are BC1, BC3 and BC5, see [DdsImage::parse].
+    pub fn with_dds_texture(
+        mut self,
+        display: &Display,
+        bytes: &[u8],
+    ) -> Result<Self, RenderError> {
+        let image = DdsImage::parse(bytes).ok_or(RenderError::InvalidDds)?;
+        let texture = image.upload(display)?;
+        self.texture = Some(TextureType::Compressed(texture));
+        Ok(self)
+    }
+}
+
+/// Decodes an image and uploads it as texture, flipped so the first row of the image is at the top.
+pub(crate) fn load_texture(
+    format: ImageFormat,
+    display: &impl Facade,
+    bytes: &[u8],
+) -> Result<Texture2d, RenderError> {
+    Ok(Texture2d::new(display, decode_image(format, bytes)?)?)
+}
+
+/// Like [load_texture], with an explicit choice of the mipmaps which are allocated and generated.
+fn load_texture_with_mipmaps(
+    format: ImageFormat,
+    display: &impl Facade,
+    bytes: &[u8],
+    mipmaps: MipmapsOption,
+) -> Result<Texture2d, RenderError> {
+    let image = decode_image(format, bytes)?;
+    Ok(Texture2d::with_mipmaps(display, image, mipmaps)?)
+}
+
+fn decode_image(format: ImageFormat, bytes: &[u8]) -> Result<RawImage2d<'static, u8>, RenderError> {
+    let image = image::load(Cursor::new(bytes), format)?.to_rgba8();
+    let dimensions = image.dimensions();
+    Ok(RawImage2d::from_raw_rgba_reversed(
+        &image.into_raw(),
+        dimensions,
+    ))
+}
+
+/// The layers of a cubemap in the order its faces are passed to [MeshUniform::with_cubemap_from_faces].
+const CUBE_LAYERS: [CubeLayer; 6] = [
+    CubeLayer::PositiveX,
+    CubeLayer::NegativeX,
+    CubeLayer::PositiveY,
+    CubeLayer::NegativeY,
+    CubeLayer::PositiveZ,
+    CubeLayer::NegativeZ,
+];
+
+/// Decodes the six faces of a cubemap, checking that they're squares of the same size.
+pub(crate) fn decode_cubemap_faces(
+    format: ImageFormat,
+    faces: [&[u8]; 6],
+) -> Result<Vec<RawImage2d<'static, u8>>, RenderError> {
+    let mut decoded = Vec::with_capacity(faces.len());
+
+    for (face, bytes) in faces.iter().enumerate() {
+        let image = decode_image(format, bytes)?;
+        let actual = (image.width, image.height);
+        let expected = decoded
+            .first()
+            .map_or((image.width, image.width), |first: &RawImage2d<u8>| {
+                (first.width, first.width)
+            });
+
+        if actual != expected {
+            return Err(RenderError::CubemapFaceSize {
+                face,
+                expected,
+                actual,
+            });
+        }
+
+        decoded.push(image);
+    }
+
+    Ok(decoded)
+}
+
+/// The number of mip levels, including the base level, of a full mipmap chain for a texture of the given
+/// `dimensions`.
+///
+/// Every level halves the previous one, rounding down, until both sides are 1 pixel. This matches the number of
+/// levels glium allocates for `MipmapsOption::AutoGeneratedMipmaps`.
+pub fn mip_levels(dimensions: (u32, u32)) -> u32 {
+    let largest = dimensions.0.max(dimensions.1);
+
+    if largest == 0 {
+        1
+    } else {
+        32 - largest.leading_zeros()
     }
 }
 
 impl Uniforms for MeshUniform {
     fn visit_values<'b, F: FnMut(&str, glium::uniforms::UniformValue<'b>)>(&'b self, mut f: F) {
-        f("matrix", UniformValue::Mat4(self.matrix.inner()));
+        if let Some(matrix) = self.matrix {
+            f("matrix", UniformValue::Mat4(matrix.inner()));
+        }
+
+        if let Some(light) = self.lights.first() {
+            f("u_light", UniformValue::Vec3(light.vector().inner()));
+        }
+
+        f(
+            "u_light_count",
+            UniformValue::SignedInt(self.lights.len() as i32),
+        );
+
+        for (index, light) in self.lights.iter().enumerate() {
+            f(
+                &format!("u_lights[{}]", index),
+                UniformValue::Vec3(light.vector().inner()),
+            );
+            f(
+                &format!("u_light_types[{}]", index),
+                UniformValue::SignedInt(light.light_type as i32),
+            );
+            f(
+                &format!("u_light_directions[{}]", index),
+                UniformValue::Vec3(light.direction.inner()),
+            );
+            f(
+                &format!("u_light_colors[{}]", index),
+                UniformValue::Vec3(light.color.inner()),
+            );
+            f(
+                &format!("u_light_ranges[{}]", index),
+                UniformValue::Float(light.range),
+            );
+            f(
+                &format!("u_light_angles[{}]", index),
+                UniformValue::Float(light.angle),
+            );
+        }
 
-        if let Some(light) = self.light {
-            f("u_light", UniformValue::Vec3(light.inner()));
+        if let Some(time) = self.time {
+            f("u_time", UniformValue::Float(time));
         }
 
         if let Some(perspective) = self.perspective {
@@ -215,21 +539,21 @@ impl Uniforms for MeshUniform {
             f("view", UniformValue::Mat4(view_matrix.inner()));
         }
 
-        for entry in [
-            (&self.texture, "tex"),
-            (&self.diffuse_texture, "diffuse_tex"),
-            (&self.normal_texture, "norm_tex"),
-        ] {
-            let texture = entry.0;
-            let id = entry.1;
-
+        for (id, texture, sampler) in self.textures() {
             if let Some(texture) = texture {
                 match texture {
                     TextureType::Texture2d(texture) => {
-                        f(id, UniformValue::Texture2d(&texture, None))
+                        f(id, UniformValue::Texture2d(texture, Some(sampler)))
                     }
                     TextureType::Texture3d(texture) => {
-                        f(id, UniformValue::Texture3d(&texture, None))
+                        f(id, UniformValue::Texture3d(texture, Some(sampler)))
+                    }
+                    TextureType::Compressed(texture) => f(
+                        id,
+                        UniformValue::CompressedTexture2d(texture, Some(sampler)),
+                    ),
+                    TextureType::Cubemap(texture) => {
+                        f(id, UniformValue::Cubemap(texture, Some(sampler)))
                     }
                 };
             }
@@ -1,9 +1,10 @@
 use std::f32::consts::PI;
 
+use ecs::entity::EntityManager;
 use ecs_macro::EntityComponent;
-use glium::Display;
+use glium::{glutin::event::WindowEvent, Display};
 
-use crate::container::Matrix4;
+use crate::{container::Matrix4, uniform::MeshUniform};
 
 #[derive(EntityComponent, Debug, Clone, Copy)]
 pub struct Perspective {
@@ -22,6 +23,11 @@ impl Perspective {
         let entries = display.get_framebuffer_dimensions();
         let (width, height) = (entries.0 as f32, entries.1 as f32);
 
+        Self::from_dimensions(width, height, fov_div, zfar, znear)
+    }
+
+    /// Creates a new `Perspective` for a surface of the given dimensions, without querying a `Display`.
+    pub fn from_dimensions(width: f32, height: f32, fov_div: f32, zfar: f32, znear: f32) -> Self {
         Self {
             width,
             height,
@@ -66,6 +72,14 @@ impl Perspective {
         self
     }
 
+    /// Adapts the aspect ratio to a resized surface.
+    ///
+    /// Both dimensions are clamped to at least 1, as a minimized window reports a size of zero.
+    pub fn update_dimensions(&mut self, width: f32, height: f32) {
+        self.width = width.max(1.0);
+        self.height = height.max(1.0);
+    }
+
     pub fn matrix(&self) -> Matrix4 {
         let fov = PI / self.fov_div;
         let f = 1.0 / (fov / 2.0).tan();
@@ -87,3 +101,152 @@ impl Perspective {
         self.matrix().inner()
     }
 }
+
+/// An orthographic projection, which maps the box between the given planes onto normalized device coordinates
+/// without any foreshortening, as used for HUDs and isometric views.
+///
+/// Like [Perspective], the camera looks along the positive z axis, so `near` is mapped to a depth of -1 and `far` to 1.
+#[derive(EntityComponent, Debug, Clone, Copy)]
+pub struct Orthographic {
+    pub left: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub top: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Orthographic {
+    pub fn new(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        Self {
+            left,
+            right,
+            bottom,
+            top,
+            near,
+            far,
+        }
+    }
+
+    /// Adapts the horizontal bounds to the aspect ratio of a resized surface, keeping the vertical bounds and the
+    /// horizontal center.
+    ///
+    /// Both dimensions are clamped to at least 1, as a minimized window reports a size of zero.
+    pub fn update_dimensions(&mut self, width: f32, height: f32) {
+        let aspect_ratio = width.max(1.0) / height.max(1.0);
+        let center = (self.left + self.right) / 2.0;
+        let half_width = (self.top - self.bottom) * aspect_ratio / 2.0;
+
+        self.left = center - half_width;
+        self.right = center + half_width;
+    }
+
+    pub fn matrix(&self) -> Matrix4 {
+        let width = self.right - self.left;
+        let height = self.top - self.bottom;
+        let depth = self.far - self.near;
+
+        Matrix4::from([
+            [2.0 / width, 0.0, 0.0, 0.0],
+            [0.0, 2.0 / height, 0.0, 0.0],
+            [0.0, 0.0, 2.0 / depth, 0.0],
+            [
+                -(self.right + self.left) / width,
+                -(self.top + self.bottom) / height,
+                -(self.far + self.near) / depth,
+                1.0,
+            ],
+        ])
+    }
+
+    pub fn inner(&self) -> [[f32; 4]; 4] {
+        self.matrix().inner()
+    }
+}
+
+/// Either kind of projection, which is handed to the shaders as the `perspective` uniform.
+#[derive(Debug, Clone, Copy)]
+pub enum Projection {
+    Perspective(Perspective),
+    Orthographic(Orthographic),
+}
+
+impl Projection {
+    pub fn matrix(&self) -> Matrix4 {
+        match self {
+            Projection::Perspective(perspective) => perspective.matrix(),
+            Projection::Orthographic(orthographic) => orthographic.matrix(),
+        }
+    }
+
+    pub fn inner(&self) -> [[f32; 4]; 4] {
+        self.matrix().inner()
+    }
+
+    /// Adapts the projection to a resized surface, see [Perspective::update_dimensions] and
+    /// [Orthographic::update_dimensions].
+    pub fn update_dimensions(&mut self, width: f32, height: f32) {
+        match self {
+            Projection::Perspective(perspective) => perspective.update_dimensions(width, height),
+            Projection::Orthographic(orthographic) => orthographic.update_dimensions(width, height),
+        }
+    }
+}
+
+/// Adapts every projection to a surface resized to `dimensions`, see [resize_event] for calling this from the event
+/// loop.
+///
+/// This updates the `Perspective` and `Orthographic` resources and components, and the projection of all
+/// `MeshUniform`s. The viewport itself follows the size of the frame automatically, as the render system draws to the
+/// whole frame unless a viewport is configured.
+pub fn resize(manager: &mut EntityManager, dimensions: (u32, u32)) {
+    let (width, height) = (dimensions.0 as f32, dimensions.1 as f32);
+
+    if let Some(perspective) = manager.resource_mut::<Perspective>() {
+        perspective.update_dimensions(width, height);
+    }
+
+    if let Some(orthographic) = manager.resource_mut::<Orthographic>() {
+        orthographic.update_dimensions(width, height);
+    }
+
+    for (_, perspective) in manager.iter::<Perspective>() {
+        perspective.update_dimensions(width, height);
+    }
+
+    for (_, orthographic) in manager.iter::<Orthographic>() {
+        orthographic.update_dimensions(width, height);
+    }
+
+    for (_, uniform) in manager.iter::<MeshUniform>() {
+        uniform.update_dimensions(width, height);
+    }
+}
+
+/// Calls [resize] for window events which change the size of the framebuffer, which are `WindowEvent::Resized` and
+/// `WindowEvent::ScaleFactorChanged`, as moving the window to a monitor with a different DPI scale resizes it in
+/// physical pixels.
+///
+/// Returns whether the event resized the framebuffer.
+pub fn resize_event(manager: &mut EntityManager, event: &WindowEvent) -> bool {
+    let dimensions = match event {
+        WindowEvent::Resized(size) => (*size).into(),
+        WindowEvent::ScaleFactorChanged { new_inner_size, .. } => (**new_inner_size).into(),
+        _ => return false,
+    };
+
+    resize(manager, dimensions);
+    true
+}
+
+impl From<Perspective> for Projection {
+    fn from(value: Perspective) -> Self {
+        Projection::Perspective(value)
+    }
+}
+
+impl From<Orthographic> for Projection {
+    fn from(value: Orthographic) -> Self {
+        Projection::Orthographic(value)
+    }
+}
@@ -0,0 +1,74 @@
+use glium::uniforms::{
+    MagnifySamplerFilter, MinifySamplerFilter, SamplerBehavior, SamplerWrapFunction,
+};
+
+/// How a texture is sampled, which is handed to the shaders together with the texture.
+///
+/// The default matches the default sampling of glium: trilinear minification, linear magnification and mirrored
+/// wrapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SamplerConfig {
+    pub minify: MinifySamplerFilter,
+    pub magnify: MagnifySamplerFilter,
+    /// The wrapping along every axis of the texture.
+    pub wrap: SamplerWrapFunction,
+}
+
+impl Default for SamplerConfig {
+    fn default() -> Self {
+        Self {
+            minify: MinifySamplerFilter::LinearMipmapLinear,
+            magnify: MagnifySamplerFilter::Linear,
+            wrap: SamplerWrapFunction::Mirror,
+        }
+    }
+}
+
+impl SamplerConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Linear filtering between and within the two closest mip levels, the texture needs mipmaps for this to have an
+    /// effect on minification.
+    pub fn trilinear() -> Self {
+        Self {
+            minify: MinifySamplerFilter::LinearMipmapLinear,
+            magnify: MagnifySamplerFilter::Linear,
+            wrap: SamplerWrapFunction::Repeat,
+        }
+    }
+
+    /// Nearest neighbor filtering, clamped to the edges of the texture, which keeps pixel art crisp.
+    pub fn pixelated() -> Self {
+        Self {
+            minify: MinifySamplerFilter::Nearest,
+            magnify: MagnifySamplerFilter::Nearest,
+            wrap: SamplerWrapFunction::Clamp,
+        }
+    }
+
+    pub fn minify(mut self, minify: MinifySamplerFilter) -> Self {
+        self.minify = minify;
+        self
+    }
+
+    pub fn magnify(mut self, magnify: MagnifySamplerFilter) -> Self {
+        self.magnify = magnify;
+        self
+    }
+
+    pub fn wrap(mut self, wrap: SamplerWrapFunction) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn behavior(&self) -> SamplerBehavior {
+        SamplerBehavior {
+            minify_filter: self.minify,
+            magnify_filter: self.magnify,
+            wrap_function: (self.wrap, self.wrap, self.wrap),
+            ..Default::default()
+        }
+    }
+}
@@ -8,18 +8,124 @@ use glium::{
     glutin::{
         event::Event,
         event_loop::{ControlFlow, EventLoop, EventLoopBuilder, EventLoopWindowTarget},
-        platform::windows::EventLoopBuilderExtWindows,
-        window::WindowBuilder,
-        ContextBuilder,
+        window::{Icon, WindowBuilder},
+        ContextBuilder, NotCurrent,
     },
     Display,
 };
 
-use crate::buffer::IndexBufferCreator;
+use crate::{buffer::IndexBufferCreator, draw::internal::RenderSystems};
 
 pub struct Window<T> {
     world: World<T>,
     platform: Box<dyn PlatformHandle<T>>,
+    config: WindowConfig,
+}
+
+/// Configures the window created by [Window::init].
+#[derive(Default)]
+pub struct WindowConfig {
+    pub icon: Option<Icon>,
+    /// The number of samples per pixel used for anti-aliasing, `None` disables multisampling.
+    pub multisampling: Option<u16>,
+    /// Whether presenting a frame waits for the vertical sync of the monitor.
+    pub vsync: bool,
+}
+
+impl WindowConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the window icon from encoded image bytes (e.g. the contents of a PNG file).
+    ///
+    /// If the image can't be decoded, a warning is printed and the default icon is used instead.
+    pub fn icon(mut self, bytes: &[u8]) -> Self {
+        self.icon = load_icon(bytes);
+        self
+    }
+
+    /// Enables multisample anti-aliasing with the given number of samples per pixel.
+    ///
+    /// The count has to be a power of two, other counts are ignored with a warning.
+    pub fn multisampling(mut self, samples: u16) -> Self {
+        if samples.is_power_of_two() {
+            self.multisampling = Some(samples);
+        } else {
+            eprintln!(
+                "Multisampling requires a power of two samples, got {}, disabling multisampling",
+                samples
+            );
+            self.multisampling = None;
+        }
+
+        self
+    }
+
+    pub fn vsync(mut self, vsync: bool) -> Self {
+        self.vsync = vsync;
+        self
+    }
+
+    /// Builds the context requested by this config, always with a 24 bit depth buffer.
+    pub(crate) fn context_builder(&self) -> ContextBuilder<'static, NotCurrent> {
+        let builder = ContextBuilder::new()
+            .with_depth_buffer(24)
+            .with_vsync(self.vsync);
+
+        match self.multisampling {
+            Some(samples) => builder.with_multisampling(samples),
+            None => builder,
+        }
+    }
+}
+
+/// Builds the event loop of the window.
+///
+/// On Windows, the event loop may be created on any thread rather than only the main thread. Other platforms don't
+/// support this, so they use the default event loop, which has to be created on the main thread.
+#[cfg(target_os = "windows")]
+fn build_event_loop() -> EventLoop<()> {
+    use glium::glutin::platform::windows::EventLoopBuilderExtWindows;
+
+    EventLoopBuilder::new().with_any_thread(true).build()
+}
+
+/// Builds the event loop of the window, see the Windows variant of this function.
+#[cfg(not(target_os = "windows"))]
+fn build_event_loop() -> EventLoop<()> {
+    EventLoopBuilder::new().build()
+}
+
+/// Decodes an image into a window [Icon].
+///
+/// # Returns
+///
+/// The decoded icon, or `None` if the image couldn't be decoded.
+pub fn load_icon(bytes: &[u8]) -> Option<Icon> {
+    let image = match image::load_from_memory(bytes) {
+        Ok(image) => image.to_rgba8(),
+        Err(error) => {
+            eprintln!(
+                "Unable to decode window icon, using the default icon: {}",
+                error
+            );
+            return None;
+        }
+    };
+
+    let (width, height) = image.dimensions();
+
+    match Icon::from_rgba(image.into_raw(), width, height) {
+        Ok(icon) => Some(icon),
+        Err(error) => {
+            eprintln!(
+                "Unable to create window icon, using the default icon: {}",
+                error
+            );
+            None
+        }
+    }
 }
 
 impl<T> Window<T>
@@ -36,16 +142,22 @@ where
         let constructed = Self {
             world,
             platform: Box::new(platform),
+            config: WindowConfig::default(),
         };
 
         Ok(constructed)
     }
 
-    fn create_display(title: &str) -> Result<(Display, EventLoop<()>), DisplayCreationError> {
-        let event_loop = EventLoopBuilder::new().with_any_thread(true).build();
+    fn create_display(
+        title: &str,
+        config: WindowConfig,
+    ) -> Result<(Display, EventLoop<()>), DisplayCreationError> {
+        let event_loop = build_event_loop();
 
-        let window_builder = WindowBuilder::new().with_title(title);
-        let context_builder = ContextBuilder::new().with_depth_buffer(24);
+        let context_builder = config.context_builder();
+        let window_builder = WindowBuilder::new()
+            .with_title(title)
+            .with_window_icon(config.icon);
 
         let display = Display::new(window_builder, context_builder, &event_loop)?;
 
@@ -57,7 +169,7 @@ where
         let leaked_buffer = Box::leak(buffer_creator);
 
         let mut platform = self.platform;
-        let (display, event_loop) = Self::create_display(title)?;
+        let (display, event_loop) = Self::create_display(title, self.config)?;
 
         platform.init_world(self.world, &display, leaked_buffer);
 
@@ -74,6 +186,16 @@ where
         self
     }
 
+    /// Adds a system with an explicit priority, see `World::with_system_ordered`.
+    pub fn system_ordered<F>(mut self, system_type: SystemType, priority: i32, system: F) -> Self
+    where
+        F: System<T> + 'static,
+    {
+        self.world
+            .with_system_ordered(system_type, priority, system);
+        self
+    }
+
     pub fn register<F>(mut self) -> Self
     where
         F: Component,
@@ -82,11 +204,24 @@ where
         self
     }
 
+    pub fn config(mut self, config: WindowConfig) -> Self {
+        self.config = config;
+        self
+    }
+
     pub fn borrow_world(&mut self) -> &mut World<T> {
         &mut self.world
     }
 }
 
+impl Window<Display> {
+    /// Registers the internal render systems, see [RenderSystems::add_render_systems].
+    pub fn render_systems(mut self) -> Self {
+        self.world.add_render_systems();
+        self
+    }
+}
+
 pub trait PlatformHandle<T> {
     // fn initialize_display(&mut self, display: Display);
     // fn initialize_cache(&mut self, buffer_creator: &'static mut IndexBufferCreator);
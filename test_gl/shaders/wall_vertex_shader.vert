@@ -4,6 +4,8 @@ in vec3 position;
 in vec3 normal;
 in vec2 tex_pos;
 in vec3 world_position;
+in vec4 rotation;
+in vec3 scale;
 
 out vec3 v_normal;
 out vec3 v_position;
@@ -13,13 +15,18 @@ uniform mat4 matrix;
 uniform mat4 view;
 uniform mat4 perspective;
 
+vec3 rotate(vec4 q,vec3 v){
+    return v+2.*cross(q.xyz,cross(q.xyz,v)+q.w*v);
+}
+
 void main(){
     mat4 modelview=view*matrix;
+    vec3 local=rotate(rotation,position*.0005*scale);
     
-    gl_Position=perspective*modelview*vec4(position*.0005+world_position,1.);
+    gl_Position=perspective*modelview*vec4(local+world_position,1.);
     // gl_Position=perspective*modelview*vec4(position,1.);
     
-    v_normal=transpose(inverse(mat3(modelview)))*normal;
+    v_normal=transpose(inverse(mat3(modelview)))*rotate(rotation,normal/scale);
     v_position=gl_Position.xyz/gl_Position.w;
     v_tex_coords=tex_pos;
 }
\ No newline at end of file
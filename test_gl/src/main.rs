@@ -2,10 +2,7 @@ use ecs::world::SystemType;
 use glium::{backend::glutin::DisplayCreationError, Display};
 use platform::SimplePlatform;
 use render_gl::{
-    draw::{
-        internal::GlRenderSystem,
-        transform::{DrawParametersComponent, Transform},
-    },
+    draw::transform::{DrawParametersComponent, Transform},
     uniform::MeshUniform,
     window::Window,
 };
@@ -23,8 +20,8 @@ pub const WALL_MESH_ENTITY: usize = 3;
 #[profiling::function]
 fn main() -> Result<(), DisplayCreationError> {
     Window::<Display>::create(SimplePlatform::new())?
-        .system(SystemType::Loop, GlRenderSystem)
         .system(SystemType::Loop, WallRotateSystem)
+        .render_systems()
         .register::<MeshUniform>()
         .register::<Transform>()
         .register::<DrawParametersComponent>()
@@ -1,10 +1,13 @@
 use std::time::Instant;
 
-use ecs::world::{SystemType, World};
+use ecs::{
+    entity::{EntityManager, EntityQueryTable},
+    world::{SystemType, World},
+};
 use glium::{
     glutin::{
         dpi::PhysicalPosition,
-        event::{Event, WindowEvent},
+        event::{ElementState, Event, KeyboardInput, WindowEvent},
         event_loop::ControlFlow,
         window::{CursorGrabMode, Fullscreen},
     },
@@ -14,13 +17,18 @@ use glium::{
 use image::ImageFormat;
 use render_gl::{
     buffer::IndexBufferCreator,
-    camera::Camera,
-    container::Matrix4,
+    camera::{Camera, CameraEvent, MouseCamera},
+    container::{Matrix4, Quaternion, Vec3},
     draw::{
-        delta::TimeDelta, instanced::Instanced, transform::DrawParametersComponent, vertex::Vertex,
+        config::ClearColor, delta::TimeDelta, instanced::InstanceSpawner,
+        transform::DrawParametersComponent, vertex::Vertex,
+    },
+    input::{end_input_frame, update_input, Input},
+    mesh::{Mesh, Winding},
+    uniform::{
+        perspective::{resize_event, Perspective},
+        MeshUniform,
     },
-    mesh::Mesh,
-    uniform::{perspective::Perspective, MeshUniform},
     window::PlatformHandle,
 };
 
@@ -28,7 +36,6 @@ pub struct SimplePlatform {
     fps_counter: u64,
     fps_counter_time: Instant,
     world: Option<World<Display>>,
-    last_delta: f32,
 }
 
 impl SimplePlatform {
@@ -37,7 +44,6 @@ impl SimplePlatform {
             fps_counter: 0,
             fps_counter_time: Instant::now(),
             world: None,
-            last_delta: 0.0,
         }
     }
 }
@@ -47,7 +53,7 @@ impl PlatformHandle<Display> for SimplePlatform {
         &mut self,
         mut world: ecs::world::World<Display>,
         display: &Display,
-        _: &'static mut IndexBufferCreator,
+        _: &mut IndexBufferCreator,
     ) {
         let gl_window = display.gl_window();
         let window = gl_window.window();
@@ -65,6 +71,9 @@ impl PlatformHandle<Display> for SimplePlatform {
         );
 
         world.with::<TimeDelta>(delta, TimeDelta::new());
+        world.insert_resource(ClearColor::new([0.0, 0.0, 1.0]));
+        world.insert_resource(Input::new());
+        world.with_post_loop_system(MouseCamera::new(0.1));
 
         let wall_mesh_entity = world.entity_at(crate::WALL_MESH_ENTITY);
         println!("{}", wall_mesh_entity);
@@ -79,9 +88,11 @@ impl PlatformHandle<Display> for SimplePlatform {
                     [0.0, 0.0, 2.0, 1.0f32],
                 ]))
                 .light([-1.0, 0.4, 0.9])
-                .perspective(Perspective::new(&display, 3.0, 1024.0, 0.1))
-                .with_img_2d_diff_texture(ImageFormat::Jpeg, &display, diff_tex)
-                .with_img_2d_norm_texture(ImageFormat::Png, &display, normal_tex),
+                .perspective(Perspective::new(display, 3.0, 1024.0, 0.1))
+                .with_img_2d_diff_texture(ImageFormat::Jpeg, display, diff_tex)
+                .unwrap()
+                .with_img_2d_norm_texture(ImageFormat::Png, display, normal_tex)
+                .unwrap(),
             )
             .with::<DrawParametersComponent>(
                 wall_mesh_entity,
@@ -98,16 +109,18 @@ impl PlatformHandle<Display> for SimplePlatform {
             .with::<Mesh>(
                 wall_mesh_entity,
                 Mesh::buffered(
-                    &display,
+                    display,
                     Vertex::from_vertices_with_tex(
-                        &display,
+                        display,
                         &crate::wall::VERTICES,
                         &crate::wall::NORMALS,
                         &crate::wall::TEX_POS,
-                    ),
+                    )
+                    .unwrap(),
                     NoIndices(PrimitiveType::TriangleStrip),
                     include_str!("../shaders/wall_vertex_shader.vert"),
                     include_str!("../shaders/wall_fragment_shader.fs"),
+                    Winding::CounterClockwise,
                 )
                 .unwrap(),
             );
@@ -122,23 +135,19 @@ impl PlatformHandle<Display> for SimplePlatform {
             })
             .collect::<Vec<_>>();
 
-        let mut i = 0;
-        for src in walls.iter_mut() {
-            (src.0).0 += (src.1).0 * 0.00001;
-            (src.0).1 += (src.1).1 * 0.00001;
-            (src.0).2 += (src.1).2 * 0.00001;
-
-            let entity = world.entity_at(wall_mesh_entity + 1 + i);
-            i += 1;
-
-            world.with::<Instanced>(
-                entity,
-                Instanced::create(
-                    crate::WALL_MESH_ENTITY as u32,
-                    ((src.0).0, (src.0).1, (src.0).2),
-                ),
-            );
-        }
+        let positions = walls
+            .iter_mut()
+            .map(|src| {
+                (src.0).0 += (src.1).0 * 0.00001;
+                (src.0).1 += (src.1).1 * 0.00001;
+                (src.0).2 += (src.1).2 * 0.00001;
+
+                let position = Vec3::new((src.0).0, (src.0).1, (src.0).2);
+                (position, Quaternion::identity(), Vec3::new(1.0, 1.0, 1.0))
+            })
+            .collect::<Vec<_>>();
+
+        world.with_instances(wall_mesh_entity, &positions);
 
         self.world = Some(world);
     }
@@ -164,12 +173,20 @@ impl PlatformHandle<Display> for SimplePlatform {
         }
 
         let world = self.world.as_mut().unwrap();
-        world.update(SystemType::Loop, &display);
+
+        if let Event::WindowEvent { event, .. } = &event {
+            update_input(&mut world.entity_manager, event);
+        }
+
+        world.update(SystemType::Loop, display);
+        end_input_frame(&mut world.entity_manager);
 
         let table = &mut world.entity_query_table;
         let manager = &mut world.entity_manager;
 
         if let Event::WindowEvent { event, .. } = event {
+            resize_event(manager, &event);
+
             match event {
                 WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
                 WindowEvent::Focused(focused) => {
@@ -189,29 +206,40 @@ impl PlatformHandle<Display> for SimplePlatform {
                     }
                 }
                 WindowEvent::CursorMoved { position, .. } => {
-                    let entity = table.query_first_single::<Camera>(manager).unwrap();
-                    let camera = manager.query_entity::<Camera>(*entity).0.unwrap();
-
-                    let (x, y): (f32, f32) = position.into();
-                    let (last_x, last_y) = camera.get_last_pos();
-
-                    let dx: f32 = x - last_x;
-                    let dy: f32 = y - last_y;
-
-                    let move_x = (dx as f32 * 50.5) * self.last_delta;
-                    let move_y = (dy as f32 * 50.5) * self.last_delta;
-
-                    camera.change_direction(move_x, move_y);
-
                     let gl_window = display.gl_window();
                     let window = gl_window.window();
 
+                    // the cursor is kept at the center of the window, so its offset is the movement of the mouse.
                     let (width, height): (u32, u32) = window.inner_size().into();
-                    let new_pos = PhysicalPosition::new(width as f64 / 2.0, height as f64 / 2.0);
-
-                    camera.update_pos(new_pos.into());
-                    window.set_cursor_position(new_pos).unwrap();
+                    let center = PhysicalPosition::new(width as f64 / 2.0, height as f64 / 2.0);
+                    let (dx, dy) = (position.x - center.x, position.y - center.y);
+
+                    if dx != 0.0 || dy != 0.0 {
+                        camera_event(
+                            table,
+                            manager,
+                            CameraEvent::MouseMovement {
+                                dx: dx as f32,
+                                dy: dy as f32,
+                            },
+                        );
+                        window.set_cursor_position(center).unwrap();
+                    }
                 }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(key),
+                            ..
+                        },
+                    ..
+                } => camera_event(table, manager, CameraEvent::KeyPressed(key)),
+                WindowEvent::MouseInput {
+                    state: ElementState::Pressed,
+                    button,
+                    ..
+                } => camera_event(table, manager, CameraEvent::MouseButtonPressed(button)),
                 _ => (),
             };
         }
@@ -223,10 +251,17 @@ impl PlatformHandle<Display> for SimplePlatform {
             .unwrap();
 
         let delta = manager.query_entity::<TimeDelta>(*entity).0.unwrap();
-        self.last_delta = delta.get_time_delta_sec();
-
         delta.update_time_delta();
 
         // *control_flow = ControlFlow::WaitUntil(Instant::now() + Duration::from_millis(10));
     }
 }
+
+/// Queues an input event on the first camera, which is handled by the [MouseCamera] system.
+fn camera_event(table: &mut EntityQueryTable, manager: &mut EntityManager, event: CameraEvent) {
+    if let Some(entity) = table.query_first_single::<Camera>(manager).copied() {
+        if let Some(camera) = manager.query_entity::<Camera>(entity).0 {
+            camera.input_event(event);
+        }
+    }
+}